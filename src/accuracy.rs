@@ -0,0 +1,191 @@
+use crate::piece::Color;
+
+use std::fmt;
+use std::iter;
+
+// Lichess-style accuracy derived from centipawn evaluations.
+// Evaluations are given from white's perspective, one per position, starting
+// with the initial position, such that the move of ply i leads from evals[i]
+// to evals[i + 1].
+
+pub fn win_percent(cp: i32) -> f64 {
+    let cp = cp.clamp(-1000, 1000) as f64;
+    50.0 + 50.0 * (2.0 / (1.0 + (-0.00368208 * cp).exp()) - 1.0)
+}
+
+fn move_accuracy(before: f64, after: f64) -> f64 {
+    let loss = (before - after).max(0.0);
+    (103.1668 * (-0.04354 * loss).exp() - 3.1669).clamp(0.0, 100.0)
+}
+
+// Accuracy of each move of the given side, weighted by how volatile the winning chances were
+// around it, such that moves in sharp positions count for more.
+pub fn move_accuracies(evals: &[i32], color: &Color) -> Vec<(f64, f64)> {
+    let offset = match color {
+        Color::White => 0,
+        Color::Black => 1,
+    };
+
+    let wins: Vec<f64> = evals.iter().map(|cp| win_percent(*cp)).collect();
+    let size = (evals.len().saturating_sub(1) / 10).clamp(2, 8);
+    if wins.len() < size {
+        return Vec::new();
+    }
+
+    // A window of positions per move, the first moves sharing the first full window.
+    let weights = iter::repeat_n(&wins[..size], size - 2)
+        .chain(wins.windows(size))
+        .map(|window| deviation(window).clamp(0.5, 12.0));
+
+    wins.windows(2)
+        .zip(weights)
+        .skip(offset)
+        .step_by(2)
+        .map(|(w, weight)| match color {
+            Color::White => (move_accuracy(w[0], w[1]), weight),
+            Color::Black => (move_accuracy(100.0 - w[0], 100.0 - w[1]), weight),
+        })
+        .collect()
+}
+
+pub fn accuracy(evals: &[i32], color: &Color) -> Option<f64> {
+    let accuracies = move_accuracies(evals, color);
+
+    if accuracies.is_empty() {
+        return None;
+    }
+
+    let weighted = accuracies.iter().map(|(a, w)| a * w).sum::<f64>()
+        / accuracies.iter().map(|(_, w)| w).sum::<f64>();
+    // Harmonic mean punishes single blunders harder than the arithmetic mean.
+    let n = accuracies.len() as f64;
+    let harmonic = n / accuracies
+        .iter()
+        .map(|(a, _)| 1.0 / a.max(1.0))
+        .sum::<f64>();
+
+    Some((weighted + harmonic) / 2.0)
+}
+
+// Standard deviation of the values.
+fn deviation(values: &[f64]) -> f64 {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt()
+}
+
+// Annotation of a move by how much of the mover's winning chances it gave away.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Judgement {
+    Inaccuracy,
+    Mistake,
+    Blunder,
+}
+
+impl Judgement {
+    // Compares the evaluations after the best and the played move, both from the mover's perspective.
+    // The thresholds are lichess' drops in winning chances, expressed as win percentages.
+    pub fn of(best: i32, played: i32) -> Option<Self> {
+        match win_percent(best) - win_percent(played) {
+            loss if loss >= 15.0 => Some(Judgement::Blunder),
+            loss if loss >= 10.0 => Some(Judgement::Mistake),
+            loss if loss >= 5.0 => Some(Judgement::Inaccuracy),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            Judgement::Inaccuracy => "?!",
+            Judgement::Mistake => "?",
+            Judgement::Blunder => "??",
+        }
+    }
+
+    // Numeric annotation glyph of the move's symbol.
+    pub fn nag(&self) -> u8 {
+        match self {
+            Judgement::Inaccuracy => 6,
+            Judgement::Mistake => 2,
+            Judgement::Blunder => 4,
+        }
+    }
+
+    pub fn from_nag(nag: u8) -> Option<Self> {
+        match nag {
+            6 => Some(Judgement::Inaccuracy),
+            2 => Some(Judgement::Mistake),
+            4 => Some(Judgement::Blunder),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Judgement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Judgement::Inaccuracy => write!(f, "Inaccuracy"),
+            Judgement::Mistake => write!(f, "Mistake"),
+            Judgement::Blunder => write!(f, "Blunder"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{accuracy, move_accuracies, win_percent, Judgement};
+    use crate::piece::Color;
+
+    #[test]
+    fn win_percents() {
+        assert_eq!(win_percent(0), 50.0);
+        assert!(win_percent(300) > 75.0 && win_percent(300) < 76.0);
+        assert_eq!(win_percent(5000), win_percent(1000));
+        assert_eq!(win_percent(-200) + win_percent(200), 100.0);
+    }
+
+    #[test]
+    fn perfect_play() {
+        let evals = [20; 21];
+
+        assert_eq!(accuracy(&evals, &Color::White).map(f64::round), Some(100.0));
+        assert_eq!(accuracy(&evals, &Color::Black).map(f64::round), Some(100.0));
+        assert_eq!(accuracy(&[20], &Color::White), None);
+    }
+
+    #[test]
+    fn blunders_count_against_the_mover() {
+        // Black gives away a piece on the sixth move.
+        let mut evals = [30; 21];
+        evals[12..].fill(330);
+
+        let white = accuracy(&evals, &Color::White).unwrap();
+        let black = accuracy(&evals, &Color::Black).unwrap();
+        assert!(white > 99.0);
+        assert!(black < 80.0);
+    }
+
+    #[test]
+    fn volatile_positions_weigh_more() {
+        let mut evals = [0; 41];
+        evals[30..].fill(-600);
+
+        let weights: Vec<f64> = move_accuracies(&evals, &Color::White)
+            .into_iter()
+            .map(|(_, weight)| weight)
+            .collect();
+        assert_eq!(weights.len(), 20);
+        assert_eq!(weights[0], 0.5);
+        assert!(weights[15] > weights[0]);
+    }
+
+    #[test]
+    fn judgements() {
+        assert_eq!(Judgement::of(50, 40), None);
+        assert_eq!(Judgement::of(0, -400), Some(Judgement::Blunder));
+        assert_eq!(
+            Judgement::from_nag(Judgement::Mistake.nag()),
+            Some(Judgement::Mistake)
+        );
+    }
+}
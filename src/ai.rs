@@ -0,0 +1,140 @@
+use crate::accuracy::Judgement;
+use crate::board::Board;
+use crate::book::Book;
+use crate::engine::Score;
+use crate::error::CatchAllError;
+use crate::lan::Lan;
+use crate::piece::Color;
+use crate::search::{Limits, Search, TABLE_MEGABYTES};
+
+use std::fmt;
+use std::time::Duration;
+
+// A hint is a quick full-strength search.
+const HINT_TIME: Duration = Duration::from_millis(500);
+// Time spent on each of the two positions when judging a move.
+const REVIEW_TIME: Duration = Duration::from_millis(300);
+// Time spent estimating a position for the evaluation bar.
+const EVALUATION_TIME: Duration = Duration::from_millis(500);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Difficulty {
+    Beginner,
+    Casual,
+    Intermediate,
+    Advanced,
+    Master,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 5] = [
+        Difficulty::Beginner,
+        Difficulty::Casual,
+        Difficulty::Intermediate,
+        Difficulty::Advanced,
+        Difficulty::Master,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Difficulty::ALL
+            .into_iter()
+            .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(name))
+    }
+
+    // Weaker levels search shallower and blur their judgement with random noise.
+    pub fn limits(&self) -> Limits {
+        let (depth, millis, noise) = match self {
+            Difficulty::Beginner => (1, 100, 200),
+            Difficulty::Casual => (2, 250, 100),
+            Difficulty::Intermediate => (3, 500, 40),
+            Difficulty::Advanced => (5, 1500, 10),
+            Difficulty::Master => (64, 3000, 0),
+        };
+
+        Limits {
+            depth,
+            time: Some(Duration::from_millis(millis)),
+            noise,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Beginner => write!(f, "Beginner"),
+            Difficulty::Casual => write!(f, "Casual"),
+            Difficulty::Intermediate => write!(f, "Intermediate"),
+            Difficulty::Advanced => write!(f, "Advanced"),
+            Difficulty::Master => write!(f, "Master"),
+        }
+    }
+}
+
+// Picks a move for the given side, blocking until the search is done.
+// A book, if given, is consulted first and the search only runs once it has no move.
+#[rustfmt::skip]
+pub fn think(mut board: Board, color: Color, difficulty: Difficulty, book: Option<&Book>) -> Result<Option<Lan>, CatchAllError> {
+    if let Some(lan) = book.and_then(|book| book.pick(&mut board, &color)) {
+        return Ok(Some(lan));
+    }
+
+    Search::new(TABLE_MEGABYTES)
+        .search(&mut board, &color, &difficulty.limits())
+        .map(|result| result.best_move)
+}
+
+// Suggests a move for the given side, blocking until the search is done.
+pub fn hint(mut board: Board, color: Color) -> Result<Option<Lan>, CatchAllError> {
+    let limits = Limits {
+        depth: Difficulty::Master.limits().depth,
+        time: Some(HINT_TIME),
+        noise: 0,
+    };
+
+    Search::new(TABLE_MEGABYTES)
+        .search(&mut board, &color, &limits)
+        .map(|result| result.best_move)
+}
+
+// Judges a move by searching the position before and after it, blocking until both searches are done.
+#[rustfmt::skip]
+pub fn review(mut before: Board, color: Color, mut after: Board) -> Result<Option<Judgement>, CatchAllError> {
+    let limits = Limits {
+        depth: Difficulty::Master.limits().depth,
+        time: Some(REVIEW_TIME),
+        noise: 0,
+    };
+    let mut search = Search::new(TABLE_MEGABYTES);
+
+    let best = search.search(&mut before, &color, &limits)?;
+    let lan = match best.best_move {
+        Some(lan) => lan,
+        None => return Ok(None),
+    };
+
+    // Playing the engine's choice needs no second opinion.
+    before.make(&lan.from, &lan.to, lan.promotion)?;
+    if before.hash(&color.opposite()) == after.hash(&color.opposite()) {
+        return Ok(None);
+    }
+
+    // One ply less deep, so that both evaluations look equally far ahead.
+    let depth = best.depth.saturating_sub(1).max(1);
+    let played = search.search(&mut after, &color.opposite(), &Limits::depth(depth))?;
+
+    Ok(Judgement::of(best.score, -played.score))
+}
+
+// Estimates the position for the side to move, blocking until the search is done.
+pub fn evaluate(mut board: Board, color: Color) -> Result<Score, CatchAllError> {
+    let limits = Limits {
+        depth: Difficulty::Master.limits().depth,
+        time: Some(EVALUATION_TIME),
+        noise: 0,
+    };
+
+    let result = Search::new(TABLE_MEGABYTES).search(&mut board, &color, &limits)?;
+
+    Ok(Score::from_search(result.score, limits.depth))
+}
@@ -0,0 +1,153 @@
+use crate::accuracy::Judgement;
+use crate::board::Board;
+use crate::engine::Score;
+use crate::error::CatchAllError;
+use crate::game::{Game, Ply};
+use crate::outcome::Outcome;
+use crate::pgn::{Node, Pgn};
+use crate::piece::Color;
+use crate::san::San;
+use crate::search::{Limits, Search, SearchResult, TABLE_MEGABYTES};
+
+// Depth every position of the game is searched to.
+const ANNOTATION_DEPTH: usize = 5;
+// Length of the engine's line given as an alternative to a questionable move, in plies.
+const VARIATION_PLIES: usize = 6;
+// Lichess' evaluation of the initial position, standing in for the position before the first move.
+const INITIAL_CENTIPAWNS: i32 = 15;
+// Mates count as the largest advantage winning chances tell apart.
+const MATE_CENTIPAWNS: i32 = 1000;
+
+// Runs the engine over every position of the game and annotates its moves with the evaluation
+// they lead to. Moves giving away winning chances are classified, and the engine's better line
+// is given as a variation. Blocks until all positions have been searched.
+#[rustfmt::skip]
+pub fn annotate(game: &Game, tags: Vec<(String, String)>) -> Result<Pgn, CatchAllError> {
+    let plies = game.plies()?;
+    let mut search = Search::new(TABLE_MEGABYTES);
+    // Judging searches keep a table of their own, as entries of the deeper ones would cut them short.
+    let mut shallow = Search::new(TABLE_MEGABYTES);
+    let limits = Limits::depth(ANNOTATION_DEPTH);
+
+    // Every position of the game, including the final one.
+    let positions: Vec<(&Board, Color)> = plies
+        .iter()
+        .map(|ply| (&ply.board, ply.color))
+        .chain([(game.board(), game.color())])
+        .collect();
+
+    let results = positions
+        .iter()
+        .map(|(board, color)| search.search(&mut (*board).clone(), color, &limits))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut pgn = Pgn::from_game(game, tags);
+    pgn.tags.push(("Annotator".to_string(), "chess".to_string()));
+
+    for (i, node) in pgn.moves.iter_mut().enumerate() {
+        annotate_move(&mut shallow, node, &plies[i], &results[i], positions[i + 1], &results[i + 1])?;
+    }
+
+    Ok(pgn)
+}
+
+#[rustfmt::skip]
+fn annotate_move(search: &mut Search, node: &mut Node, ply: &Ply, best: &SearchResult, (board, color): (&Board, Color), played: &SearchResult) -> Result<(), CatchAllError> {
+    let mut comment = Vec::new();
+
+    // Positions where the game ended have nothing left to evaluate.
+    if played.best_move.is_some() {
+        let score = Score::from_search(played.score, ANNOTATION_DEPTH);
+        let score = match color {
+            Color::White => score,
+            Color::Black => score.flipped(),
+        };
+        comment.push(format!("[%eval {}]", eval(score)));
+    }
+
+    // Only a move other than the engine's choice is judged, against a search one ply less deep,
+    // so that both scores look equally far ahead.
+    let judgement = match best.best_move {
+        Some(lan) if lan != ply.lan => {
+            let limits = Limits::depth(ANNOTATION_DEPTH - 1);
+            let played = search.search(&mut board.clone(), &color, &limits)?;
+            Judgement::of(best.score, -played.score)
+        }
+        _ => None,
+    };
+
+    if let Some(judgement) = judgement {
+        let line = &best.pv[..best.pv.len().min(VARIATION_PLIES)];
+        let line = San::format_line(&ply.board, &ply.color, line)?;
+
+        if let Some(san) = line.first() {
+            comment.push(format!("{}. {} was best.", judgement, san));
+        }
+
+        node.nags.push(judgement.nag());
+        node.variations.push(
+            line.into_iter()
+                .map(|san| Node {
+                    san,
+                    nags: Vec::new(),
+                    comment_before: None,
+                    comment: None,
+                    variations: Vec::new(),
+                    draw_offer: false,
+                })
+                .collect(),
+        );
+    }
+
+    if !comment.is_empty() {
+        node.comment = Some(comment.join(" "));
+    }
+
+    Ok(())
+}
+
+// Evaluation in the notation of the %eval command: pawns, or moves to mate prefixed with #.
+fn eval(score: Score) -> String {
+    match score {
+        Score::Centipawns(cp) => format!("{:.2}", cp as f64 / 100.0),
+        Score::Mate(moves) => format!("#{}", moves),
+    }
+}
+
+// Evaluations in centipawns from white's perspective, one per position starting with the one
+// before the first move, read back from the %eval commands of an annotated game. Only the final
+// position may go without one, as there is nothing left to search once the game is over.
+pub fn evaluations(pgn: &Pgn) -> Option<Vec<i32>> {
+    let last = pgn.moves.len().checked_sub(1)?;
+    let outcome = pgn.game.outcome();
+    let mut evals = vec![INITIAL_CENTIPAWNS];
+
+    for (i, node) in pgn.moves.iter().enumerate() {
+        let eval = match (node.comment.as_deref().and_then(read_eval), outcome) {
+            (Some(eval), _) => eval,
+            (None, Some(Outcome::Checkmate(Color::White))) if i == last => MATE_CENTIPAWNS,
+            (None, Some(Outcome::Checkmate(Color::Black))) if i == last => -MATE_CENTIPAWNS,
+            (None, Some(Outcome::Stalemate | Outcome::Draw(_))) if i == last => 0,
+            _ => return None,
+        };
+        evals.push(eval);
+    }
+
+    Some(evals)
+}
+
+fn read_eval(comment: &str) -> Option<i32> {
+    let start = comment.find("[%eval ")? + "[%eval ".len();
+    let value = comment[start..].split(']').next()?.trim();
+
+    match value.strip_prefix('#') {
+        Some(moves) => moves.parse::<i32>().ok().map(|moves| match moves < 0 {
+            true => -MATE_CENTIPAWNS,
+            false => MATE_CENTIPAWNS,
+        }),
+        None => value
+            .parse::<f64>()
+            .ok()
+            .map(|pawns| (pawns * 100.0).round() as i32),
+    }
+}
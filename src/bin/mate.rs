@@ -0,0 +1,52 @@
+use chess::fen::Fen;
+use chess::mate;
+use chess::san::San;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: chess-mate <max plies> <fen>";
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (plies, fen) = match args.split_first() {
+        Some((plies, fen)) if !fen.is_empty() => (plies.parse::<usize>().ok(), fen.join(" ")),
+        _ => (None, String::new()),
+    };
+
+    let plies = match plies {
+        Some(plies) => plies,
+        None => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let fen = match Fen::parse(&fen) {
+        Ok(fen) => fen,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let line = mate::solve_mate(&fen.board, &fen.color, plies).and_then(|line| {
+        line.map(|line| San::format_line(&fen.board, &fen.color, &line))
+            .transpose()
+    });
+
+    match line {
+        Ok(Some(line)) => {
+            println!("Mate in {}: {}", line.len().div_ceil(2), line.join(" "));
+            ExitCode::SUCCESS
+        }
+        Ok(None) => {
+            println!("No forced mate within {} plies", plies);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
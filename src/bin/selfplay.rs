@@ -0,0 +1,67 @@
+use chess::ai::Difficulty;
+use chess::database::Database;
+use chess::selfplay::{self, Player};
+
+use std::process::ExitCode;
+
+const USAGE: &str =
+    "usage: chess-selfplay <games> <first difficulty> <second difficulty> [pgn file]";
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    let (games, first, second) = match args.as_slice() {
+        [games, first, second, ..] => (
+            games.parse::<usize>().ok(),
+            Difficulty::from_name(first),
+            Difficulty::from_name(second),
+        ),
+        _ => (None, None, None),
+    };
+
+    let (games, first, second) = match (games, first, second) {
+        (Some(games), Some(first), Some(second)) => {
+            (games, Player::new(first), Player::new(second))
+        }
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let database = args.get(3).map(|path| Database::new(path));
+
+    let result = selfplay::run(&first, &second, games, |pgn, tally| {
+        println!(
+            "Round {}: {} {} {} (+{} -{} ={})",
+            pgn.tag("Round").unwrap_or("?"),
+            pgn.tag("White").unwrap_or("?"),
+            pgn.tag("Result").unwrap_or("*"),
+            pgn.tag("Black").unwrap_or("?"),
+            tally.wins,
+            tally.losses,
+            tally.draws,
+        );
+
+        if let Some(Err(e)) = database.as_ref().map(|database| database.append(pgn)) {
+            eprintln!("{}", e);
+        }
+    });
+
+    match result {
+        Ok(tally) => {
+            println!(
+                "{} scored {}/{} against {}",
+                first.name,
+                tally.score(),
+                games,
+                second.name
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,40 @@
+use chess::ai::Difficulty;
+use chess::clock::{Bonus, TimeControl};
+use chess::piece::Color;
+use chess::tui;
+
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: chess-tui [time control] [computer difficulty]";
+
+pub fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // The computer takes black if given a difficulty, otherwise two humans share the terminal.
+    let control = match args.first() {
+        Some(name) => TimeControl::from_name(name),
+        None => Some(TimeControl::Unlimited),
+    };
+    let engine = match args.get(1) {
+        Some(name) => {
+            Difficulty::from_name(name).map(|difficulty| Some((Color::Black, difficulty)))
+        }
+        None => Some(None),
+    };
+
+    let (control, engine) = match (control, engine) {
+        (Some(control), Some(engine)) if args.len() <= 2 => (control, engine),
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match tui::run(engine, control, Bonus::None) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
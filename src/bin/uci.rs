@@ -0,0 +1,5 @@
+use std::io;
+
+pub fn main() -> io::Result<()> {
+    chess::uci::run()
+}
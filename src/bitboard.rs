@@ -0,0 +1,223 @@
+use crate::piece::Color;
+use crate::position::Position;
+
+use std::sync::OnceLock;
+
+// Set of squares, bit rank * 8 + file standing for the square at file and rank.
+pub type Bitboard = u64;
+
+const ROOK_DIRECTIONS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const BISHOP_DIRECTIONS: [(i32, i32); 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+const KNIGHT_OFFSETS: [(i32, i32); 8] = [
+    (1, 2),
+    (2, 1),
+    (2, -1),
+    (1, -2),
+    (-1, -2),
+    (-2, -1),
+    (-2, 1),
+    (-1, 2),
+];
+const KING_OFFSETS: [(i32, i32); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+static ROOKS: OnceLock<Table> = OnceLock::new();
+static BISHOPS: OnceLock<Table> = OnceLock::new();
+
+pub fn square(pos: &Position) -> usize {
+    pos.rank * 8 + pos.file
+}
+
+pub fn bit(pos: &Position) -> Bitboard {
+    1 << square(pos)
+}
+
+pub fn positions(mut bitboard: Bitboard) -> impl Iterator<Item = Position> {
+    std::iter::from_fn(move || {
+        (bitboard != 0).then(|| {
+            let square = bitboard.trailing_zeros() as usize;
+            bitboard &= bitboard - 1;
+            Position::new(square % 8, square / 8)
+        })
+    })
+}
+
+pub fn rook_attacks(pos: &Position, occupancy: Bitboard) -> Bitboard {
+    ROOKS
+        .get_or_init(|| Table::new(&ROOK_DIRECTIONS))
+        .attacks(square(pos), occupancy)
+}
+
+pub fn bishop_attacks(pos: &Position, occupancy: Bitboard) -> Bitboard {
+    BISHOPS
+        .get_or_init(|| Table::new(&BISHOP_DIRECTIONS))
+        .attacks(square(pos), occupancy)
+}
+
+pub fn queen_attacks(pos: &Position, occupancy: Bitboard) -> Bitboard {
+    rook_attacks(pos, occupancy) | bishop_attacks(pos, occupancy)
+}
+
+pub fn knight_attacks(pos: &Position) -> Bitboard {
+    leaper_attacks(pos, &KNIGHT_OFFSETS)
+}
+
+pub fn king_attacks(pos: &Position) -> Bitboard {
+    leaper_attacks(pos, &KING_OFFSETS)
+}
+
+pub fn pawn_attacks(color: &Color, pos: &Position) -> Bitboard {
+    match color {
+        Color::White => leaper_attacks(pos, &[(-1, 1), (1, 1)]),
+        Color::Black => leaper_attacks(pos, &[(-1, -1), (1, -1)]),
+    }
+}
+
+fn on_board(file: i32, rank: i32) -> bool {
+    (0..8).contains(&file) && (0..8).contains(&rank)
+}
+
+fn leaper_attacks(pos: &Position, offsets: &[(i32, i32)]) -> Bitboard {
+    offsets
+        .iter()
+        .map(|(df, dr)| (pos.file as i32 + df, pos.rank as i32 + dr))
+        .filter(|(file, rank)| on_board(*file, *rank))
+        .fold(0, |attacks, (file, rank)| attacks | 1 << (rank * 8 + file))
+}
+
+// Slides along each direction up to and including the first occupied square.
+fn ray_attacks(square: usize, occupancy: Bitboard, directions: &[(i32, i32)]) -> Bitboard {
+    let mut attacks = 0;
+
+    for (df, dr) in directions {
+        let (mut file, mut rank) = ((square % 8) as i32 + df, (square / 8) as i32 + dr);
+
+        while on_board(file, rank) {
+            let bit = 1 << (rank * 8 + file);
+            attacks |= bit;
+            if occupancy & bit != 0 {
+                break;
+            }
+            file += df;
+            rank += dr;
+        }
+    }
+
+    attacks
+}
+
+// Squares whose occupancy affects the attacks, which excludes the last square of each ray.
+fn relevant_mask(square: usize, directions: &[(i32, i32)]) -> Bitboard {
+    let mut mask = 0;
+
+    for (df, dr) in directions {
+        let (mut file, mut rank) = ((square % 8) as i32 + df, (square / 8) as i32 + dr);
+
+        while on_board(file + df, rank + dr) {
+            mask |= 1 << (rank * 8 + file);
+            file += df;
+            rank += dr;
+        }
+    }
+
+    mask
+}
+
+struct Magic {
+    mask: Bitboard,
+    magic: u64,
+    shift: u32,
+    offset: usize,
+}
+
+// Perfect hash from the relevant occupancy of each square to its slider attacks.
+struct Table {
+    magics: Vec<Magic>,
+    attacks: Vec<Bitboard>,
+}
+
+impl Table {
+    fn new(directions: &[(i32, i32)]) -> Self {
+        let mut magics = Vec::with_capacity(64);
+        let mut attacks = Vec::new();
+        let mut seed = 0x9e3779b97f4a7c15_u64;
+
+        for square in 0..64 {
+            let mask = relevant_mask(square, directions);
+            let shift = 64 - mask.count_ones();
+            let size = 1 << mask.count_ones();
+
+            // Every subset of the mask, enumerated with the Carry-Rippler trick.
+            let mut subsets = Vec::with_capacity(size);
+            let mut subset: Bitboard = 0;
+            loop {
+                subsets.push((subset, ray_attacks(square, subset, directions)));
+                subset = subset.wrapping_sub(mask) & mask;
+                if subset == 0 {
+                    break;
+                }
+            }
+
+            let offset = attacks.len();
+            attacks.resize(offset + size, 0);
+
+            // Sparse random candidates are tried until one maps all subsets without collisions.
+            let magic = loop {
+                let magic = random(&mut seed) & random(&mut seed) & random(&mut seed);
+                if (mask.wrapping_mul(magic) >> 56).count_ones() < 6 {
+                    continue;
+                }
+
+                let table = &mut attacks[offset..offset + size];
+                let mut used = vec![false; size];
+
+                let perfect = subsets.iter().all(|(occupancy, attack)| {
+                    let index = (occupancy.wrapping_mul(magic) >> shift) as usize;
+                    match used[index] {
+                        false => {
+                            used[index] = true;
+                            table[index] = *attack;
+                            true
+                        }
+                        true => table[index] == *attack,
+                    }
+                });
+
+                if perfect {
+                    break magic;
+                }
+            };
+
+            magics.push(Magic {
+                mask,
+                magic,
+                shift,
+                offset,
+            });
+        }
+
+        Self { magics, attacks }
+    }
+
+    fn attacks(&self, square: usize, occupancy: Bitboard) -> Bitboard {
+        let magic = &self.magics[square];
+        let index = ((occupancy & magic.mask).wrapping_mul(magic.magic) >> magic.shift) as usize;
+        self.attacks[magic.offset + index]
+    }
+}
+
+// Xorshift64 pseudo-random numbers, seeded deterministically.
+fn random(seed: &mut u64) -> u64 {
+    *seed ^= *seed << 13;
+    *seed ^= *seed >> 7;
+    *seed ^= *seed << 17;
+    *seed
+}
@@ -1,47 +1,80 @@
-use crate::error::CatchAllError;
+use crate::bitboard::{self, Bitboard};
+use crate::castling::CastlingRights;
+use crate::error::{CatchAllError, FenError, IllegalMoveReason};
+use crate::fen::Fen;
+use crate::lan::Lan;
+use crate::outcome::Outcome;
 use crate::path::Path;
-use crate::piece::{Color, MoveCounter, Piece};
+use crate::piece::{Color, MoveCounter, Piece, Promotion};
 use crate::position::{Distance, Position};
-use crate::r#move::Direction;
-use crate::r#move::{Action, Move};
-use std::collections::HashMap;
+use crate::r#move::{Action, Direction, Move, MoveKind};
+use crate::squares::Squares;
+use crate::zobrist;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MoveCache {
     pub from: Position,
     pub to: Position,
-    pub captured: Option<Piece>,
+    pub piece: Piece,
+    pub captured: Option<(Position, Piece)>,
+    pub rook: Option<(Position, Position)>,
+    pub castling: CastlingRights,
+    pub enpassant: Option<Position>,
+    pub halfmove_clock: u32,
+    pub key: u64,
 }
 
-impl MoveCache {
-    pub fn new(from: Position, to: Position, captured: Option<Piece>) -> Self {
-        Self { from, to, captured }
-    }
+#[derive(Debug, Clone)]
+pub struct Pin {
+    pub position: Position,
+    pub pinner: Position,
+    // Squares the pinned piece may move to without exposing its king, including the pinner.
+    pub line: Vec<Position>,
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Board {
-    pieces: HashMap<Position, Piece>,
-    cache: Option<MoveCache>,
+    pieces: Squares,
+    // Stack of played moves, holding everything needed to revert them.
+    cache: Vec<MoveCache>,
+    castling: CastlingRights,
+    // Square skipped by a double pawn push on the previous ply.
     enpassant: Option<Position>,
+    // Half-moves since the last pawn move or capture.
+    halfmove_clock: u32,
+    // Zobrist key of everything but the side to move, updated along with the position.
+    key: u64,
 }
 
 impl Board {
+    pub fn empty() -> Self {
+        Self {
+            pieces: Squares::new(),
+            cache: Vec::new(),
+            castling: CastlingRights::none(),
+            enpassant: None,
+            halfmove_clock: 0,
+            key: 0,
+        }
+    }
+
     #[rustfmt::skip]
     pub fn new() -> Self {
-        let mut board = Self {
-            pieces: HashMap::new(),
-            cache: None,
-            enpassant: None,
-        };
+        let mut board = Self::empty();
+        board.castling = CastlingRights::default();
 
-        board.pieces.insert(Position::new(0, 0), Piece::Rook( Color::White, MoveCounter(0)));
+        board.pieces.insert(Position::new(0, 0), Piece::Rook( Color::White));
         board.pieces.insert(Position::new(1, 0), Piece::Knight( Color::White));
         board.pieces.insert(Position::new(2, 0), Piece::Bishop( Color::White));
         board.pieces.insert(Position::new(3, 0), Piece::Queen( Color::White));
-        board.pieces.insert(Position::new(4, 0), Piece::King( Color::White, MoveCounter(0)));
+        board.pieces.insert(Position::new(4, 0), Piece::King( Color::White));
         board.pieces.insert(Position::new(5, 0), Piece::Bishop( Color::White));
         board.pieces.insert(Position::new(6, 0), Piece::Knight( Color::White));
-        board.pieces.insert(Position::new(7, 0), Piece::Rook( Color::White, MoveCounter(0)));
+        board.pieces.insert(Position::new(7, 0), Piece::Rook( Color::White));
         board.pieces.insert(Position::new(0, 1), Piece::Pawn( Color::White, MoveCounter(0)));
         board.pieces.insert(Position::new(1, 1), Piece::Pawn( Color::White, MoveCounter(0)));
         board.pieces.insert(Position::new(2, 1), Piece::Pawn( Color::White, MoveCounter(0)));
@@ -58,26 +91,209 @@ impl Board {
         board.pieces.insert(Position::new(5, 6), Piece::Pawn( Color::Black, MoveCounter(0)));
         board.pieces.insert(Position::new(6, 6), Piece::Pawn( Color::Black, MoveCounter(0)));
         board.pieces.insert(Position::new(7, 6), Piece::Pawn( Color::Black, MoveCounter(0)));
-        board.pieces.insert(Position::new(0, 7), Piece::Rook( Color::Black, MoveCounter(0)));
+        board.pieces.insert(Position::new(0, 7), Piece::Rook( Color::Black));
         board.pieces.insert(Position::new(1, 7), Piece::Knight( Color::Black));
         board.pieces.insert(Position::new(2, 7), Piece::Bishop( Color::Black));
         board.pieces.insert(Position::new(3, 7), Piece::Queen( Color::Black));
-        board.pieces.insert(Position::new(4, 7), Piece::King( Color::Black, MoveCounter(0)));
+        board.pieces.insert(Position::new(4, 7), Piece::King( Color::Black));
         board.pieces.insert(Position::new(5, 7), Piece::Bishop( Color::Black));
         board.pieces.insert(Position::new(6, 7), Piece::Knight( Color::Black));
-        board.pieces.insert(Position::new(7, 7), Piece::Rook( Color::Black, MoveCounter(0)));
+        board.pieces.insert(Position::new(7, 7), Piece::Rook( Color::Black));
+
+        board.key = board.pieces.iter().fold(zobrist::castling_rights(&board.castling), |key, (pos, piece)| key ^ zobrist::piece(piece, pos));
 
         board
     }
 
     #[rustfmt::skip]
-    pub fn advance(&mut self, color: &Color, from: &Position, to: &Position) -> Result<(), CatchAllError> {
-        self.assess_turn(color, from, to)?;
-        self.update(from, to)?;
+    pub fn advance(&mut self, color: &Color, from: &Position, to: &Position, promotion: Option<Promotion>) -> Result<(), CatchAllError> {
+        self.assess_turn(color, from, to, &promotion)?;
+        self.update(from, to, &promotion)?;
 
         Ok(())
     }
 
+    // Plays a move taken from legal_moves without validating it again.
+    #[rustfmt::skip]
+    pub fn make(&mut self, from: &Position, to: &Position, promotion: Option<Promotion>) -> Result<(), CatchAllError> {
+        self.update(from, to, &promotion)
+    }
+
+    pub fn pieces(&self) -> impl Iterator<Item = (&Position, &Piece)> {
+        self.pieces.iter()
+    }
+
+    pub fn last_move(&self) -> Option<&MoveCache> {
+        self.cache.last()
+    }
+
+    // Moves made on the board so far, oldest first.
+    pub fn history(&self) -> &[MoveCache] {
+        &self.cache
+    }
+
+    pub fn halfmove_clock(&self) -> u32 {
+        self.halfmove_clock
+    }
+
+    pub fn enpassant(&self) -> Option<Position> {
+        self.enpassant
+    }
+
+    // The en passant square, as long as a pawn of the side to move stands ready to capture on it.
+    pub fn capturable_enpassant(&self, color: &Color) -> Option<Position> {
+        let ranks = match color {
+            Color::White => -1,
+            Color::Black => 1,
+        };
+
+        self.enpassant.filter(|target| {
+            [-1, 1]
+                .into_iter()
+                .filter_map(|files| target.offset(files, ranks))
+                .any(|pos| matches!(self.pieces.get(&pos), Some(Piece::Pawn(c, _)) if c == color))
+        })
+    }
+
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        Fen::parse(fen).map(|fen| fen.board)
+    }
+
+    pub fn place(&mut self, pos: &Position, piece: Piece) -> Option<Piece> {
+        // Pawns off their initial rank must not be allowed a double push.
+        let piece = match piece {
+            Piece::Pawn(Color::White, _) if pos.rank != 1 => {
+                Piece::Pawn(Color::White, MoveCounter(1))
+            }
+            Piece::Pawn(Color::Black, _) if pos.rank != 6 => {
+                Piece::Pawn(Color::Black, MoveCounter(1))
+            }
+            Piece::Pawn(color, _) => Piece::Pawn(color, MoveCounter(0)),
+            piece => piece,
+        };
+
+        self.key ^= zobrist::piece(&piece, pos);
+        let replaced = self.pieces.insert(*pos, piece);
+        self.key ^= replaced
+            .as_ref()
+            .map_or(0, |piece| zobrist::piece(piece, pos));
+
+        replaced
+    }
+
+    pub fn remove(&mut self, pos: &Position) -> Option<Piece> {
+        let removed = self.pieces.remove(pos);
+        self.key ^= removed
+            .as_ref()
+            .map_or(0, |piece| zobrist::piece(piece, pos));
+
+        removed
+    }
+
+    // Validates an arbitrary position with the given color to move.
+    pub fn validate(&self, color: &Color) -> Result<(), CatchAllError> {
+        [Color::White, Color::Black]
+            .iter()
+            .try_for_each(|side| self.validate_material(side))?;
+
+        // Check if no pawn stands on the first or last rank.
+        self.pieces
+            .iter()
+            .any(|(pos, piece)| {
+                matches!(piece, Piece::Pawn(_, _)) && (pos.rank == 0 || pos.rank == 7)
+            })
+            .eq(&false)
+            .then_some(())
+            .ok_or(CatchAllError::PawnOnBackRank)?;
+
+        // Check if the side that just moved did not leave its king in check.
+        self.in_check(&color.opposite())?
+            .eq(&false)
+            .then_some(())
+            .ok_or(CatchAllError::OpponentInCheck)?;
+
+        self.validate_castling()?;
+        self.validate_enpassant(color)
+    }
+
+    fn validate_material(&self, color: &Color) -> Result<(), CatchAllError> {
+        let count = |kind: fn(&Piece) -> bool| {
+            self.pieces
+                .values()
+                .filter(|piece| &piece.color() == color && kind(piece))
+                .count()
+        };
+
+        (count(|p| matches!(p, Piece::King(_))) == 1)
+            .then_some(())
+            .ok_or(CatchAllError::KingCount)?;
+
+        // Every piece beyond the initial set must have been promoted from a pawn.
+        let pawns = count(|p| matches!(p, Piece::Pawn(_, _)));
+        let promoted = count(|p| matches!(p, Piece::Queen(_))).saturating_sub(1)
+            + count(|p| matches!(p, Piece::Rook(_))).saturating_sub(2)
+            + count(|p| matches!(p, Piece::Bishop(_))).saturating_sub(2)
+            + count(|p| matches!(p, Piece::Knight(_))).saturating_sub(2);
+
+        (pawns + promoted <= 8)
+            .then_some(())
+            .ok_or(CatchAllError::TooManyPieces)
+    }
+
+    fn validate_castling(&self) -> Result<(), CatchAllError> {
+        [
+            (Color::White, Direction::Right, 0),
+            (Color::White, Direction::Left, 0),
+            (Color::Black, Direction::Right, 7),
+            (Color::Black, Direction::Left, 7),
+        ]
+        .iter()
+        .all(|(color, direction, rank)| {
+            // The rook may stand on any file on its side of the king, as in Chess960.
+            let king = self.king(color).ok().filter(|(pos, _)| pos.rank == *rank);
+            let rook = self.castling.rook_file(color, direction);
+
+            !self.castling.allowed(color, direction)
+                || match (king, rook) {
+                    (Some((king, _)), Some(file)) => {
+                        self.pieces.get(&Position::new(file, *rank)) == Some(&Piece::Rook(*color))
+                            && match direction {
+                                Direction::Right => file > king.file,
+                                _ => file < king.file,
+                            }
+                    }
+                    _ => false,
+                }
+        })
+        .then_some(())
+        .ok_or(CatchAllError::BadCastle)
+    }
+
+    fn validate_enpassant(&self, color: &Color) -> Result<(), CatchAllError> {
+        let target = match self.enpassant {
+            Some(target) => target,
+            None => return Ok(()),
+        };
+
+        // The pawn that skipped the target must stand in front of it, with both squares it crossed empty.
+        let (rank, pawn, origin) = match color {
+            Color::White => (5, 4, 6),
+            Color::Black => (2, 3, 1),
+        };
+
+        (target.rank == rank
+            && self
+                .pieces
+                .get(&Position::new(target.file, pawn))
+                .is_some_and(|piece| matches!(piece, Piece::Pawn(_, _)) && &piece.color() != color)
+            && !self.pieces.contains_key(&target)
+            && !self
+                .pieces
+                .contains_key(&Position::new(target.file, origin)))
+        .then_some(())
+        .ok_or(CatchAllError::BadEnPassant)
+    }
+
     pub fn at(&self, pos: &Position) -> Result<&Piece, CatchAllError> {
         self.pieces
             .get(pos)
@@ -88,7 +304,7 @@ impl Board {
         self.pieces
             .iter()
             .find(|(_, v)| match v {
-                Piece::King(c, _) if c == color => true,
+                Piece::King(c) if c == color => true,
                 _ => false,
             })
             .ok_or(CatchAllError::NoKing)
@@ -96,30 +312,165 @@ impl Board {
 
     pub fn in_check(&self, color: &Color) -> Result<bool, CatchAllError> {
         let (pos, _) = self.king(color)?;
-        Ok(self.pieces.iter().any(|(k, v)| {
-            &v.color() != color
-                && v.can_reach(&Move::new(k, pos, Action::Regular)).is_ok()
-                && self
-                    .assess_move(k, &Move::new(k, pos, Action::Regular))
-                    .is_ok()
-        }))
+        Ok(self.is_attacked(pos, &color.opposite()))
     }
 
-    fn piece_at(&self, pos: &Position, color: &Color) -> Result<&Piece, CatchAllError> {
+    pub fn attacked_squares(&self, color: &Color) -> HashSet<Position> {
+        let occupancy = self.occupancy();
+        let attacks = self
+            .pieces
+            .iter()
+            .filter(|(_, piece)| &piece.color() == color)
+            .fold(0, |attacks, (from, piece)| {
+                attacks | Board::attacks(from, piece, occupancy)
+            });
+
+        bitboard::positions(attacks).collect()
+    }
+
+    // Number of pieces of the given color attacking each square, leaving out unattacked ones.
+    pub fn attack_counts(&self, color: &Color) -> HashMap<Position, usize> {
+        let occupancy = self.occupancy();
+        let mut counts = HashMap::new();
+
+        for (from, piece) in self
+            .pieces
+            .iter()
+            .filter(|(_, piece)| &piece.color() == color)
+        {
+            for pos in bitboard::positions(Board::attacks(from, piece, occupancy)) {
+                *counts.entry(pos).or_insert(0) += 1;
+            }
+        }
+
+        counts
+    }
+
+    pub fn is_attacked(&self, pos: &Position, color: &Color) -> bool {
+        let occupancy = self.occupancy();
         self.pieces
-            .get(pos)
-            .map_or(Err(CatchAllError::EmptyField), |p| {
+            .iter()
+            .filter(|(_, piece)| &piece.color() == color)
+            .any(|(from, piece)| Board::attacks(from, piece, occupancy) & bitboard::bit(pos) != 0)
+    }
+
+    // A piece attacks every square it could capture on, regardless of what occupies it.
+    pub fn attacks(from: &Position, piece: &Piece, occupancy: Bitboard) -> Bitboard {
+        match piece {
+            Piece::Pawn(color, _) => bitboard::pawn_attacks(color, from),
+            Piece::Knight(_) => bitboard::knight_attacks(from),
+            Piece::Bishop(_) => bitboard::bishop_attacks(from, occupancy),
+            Piece::Rook(_) => bitboard::rook_attacks(from, occupancy),
+            Piece::Queen(_) => bitboard::queen_attacks(from, occupancy),
+            Piece::King(_) => bitboard::king_attacks(from),
+        }
+    }
+
+    pub fn occupancy(&self) -> Bitboard {
+        self.pieces
+            .keys()
+            .fold(0, |occupancy, pos| occupancy | bitboard::bit(pos))
+    }
+
+    pub fn occupancy_of(&self, color: &Color) -> Bitboard {
+        self.pieces
+            .iter()
+            .filter(|(_, piece)| &piece.color() == color)
+            .fold(0, |occupancy, (pos, _)| occupancy | bitboard::bit(pos))
+    }
+
+    // The en passant file is left out of the running key, since whether it counts depends on
+    // the pawns of the side to move.
+    pub fn hash(&self, color: &Color) -> u64 {
+        self.key
+            ^ zobrist::side(color)
+            ^ zobrist::enpassant_target(&self.capturable_enpassant(color))
+    }
+
+    pub fn castling(&self) -> CastlingRights {
+        self.castling
+    }
+
+    pub fn set_castling(&mut self, castling: CastlingRights) {
+        self.key ^= zobrist::castling_rights(&self.castling) ^ zobrist::castling_rights(&castling);
+        self.castling = castling;
+    }
+
+    pub fn set_enpassant(&mut self, enpassant: Option<Position>) {
+        self.enpassant = enpassant;
+    }
+
+    pub fn set_halfmove_clock(&mut self, halfmove_clock: u32) {
+        self.halfmove_clock = halfmove_clock;
+    }
+
+    pub fn to_fen(&self, color: &Color, fullmove_number: usize) -> String {
+        self.fen(color, fullmove_number, self.castling.to_string())
+    }
+
+    pub fn to_shredder_fen(&self, color: &Color, fullmove_number: usize) -> String {
+        self.fen(color, fullmove_number, self.castling.to_shredder())
+    }
+
+    fn fen(&self, color: &Color, fullmove_number: usize, castling: String) -> String {
+        let placement = (0..8)
+            .rev()
+            .map(|rank| {
+                let (mut row, empty) = (0..8).fold((String::new(), 0), |(mut row, empty), file| {
+                    match self.pieces.get(&Position::new(file, rank)) {
+                        Some(piece) => {
+                            if empty > 0 {
+                                row.push_str(&empty.to_string());
+                            }
+                            row.push(piece.symbol());
+                            (row, 0)
+                        }
+                        None => (row, empty + 1),
+                    }
+                });
+
+                if empty > 0 {
+                    row.push_str(&empty.to_string());
+                }
+
+                row
+            })
+            .collect::<Vec<_>>()
+            .join("/");
+
+        let side = match color {
+            Color::White => "w",
+            Color::Black => "b",
+        };
+
+        let enpassant = self
+            .enpassant
+            .map_or("-".to_string(), |pos| pos.to_string());
+
+        format!(
+            "{} {} {} {} {} {}",
+            placement, side, castling, enpassant, self.halfmove_clock, fullmove_number
+        )
+    }
+
+    fn piece_at(&self, pos: &Position, color: &Color) -> Result<&Piece, CatchAllError> {
+        self.pieces.get(pos).map_or(
+            Err(CatchAllError::IllegalMove(IllegalMoveReason::EmptySquare)),
+            |p| {
                 (&p.color() == color)
                     .then(|| p)
-                    .ok_or(CatchAllError::EmptyField)
-            })
+                    .ok_or(CatchAllError::IllegalMove(IllegalMoveReason::WrongTurn))
+            },
+        )
     }
 
     fn action(&self, pos: &Position, color: &Color) -> Result<Action, CatchAllError> {
         self.pieces.get(pos).map_or(Ok(Action::Regular), |p| {
             (&p.color() != color)
                 .then(|| Action::Capture)
-                .ok_or(CatchAllError::EmptyField)
+                .ok_or(CatchAllError::IllegalMove(
+                    IllegalMoveReason::OwnPieceOnTarget,
+                ))
         })
     }
 
@@ -128,51 +479,124 @@ impl Board {
             .contains_key(&pos)
             .eq(&false)
             .then(|| ())
-            .ok_or(CatchAllError::BlockedPath)
+            .ok_or(CatchAllError::IllegalMove(IllegalMoveReason::PathBlocked))
     }
 
-    fn update(&mut self, from: &Position, to: &Position) -> Result<(), CatchAllError> {
-        let captured = self.pieces.get(to).map(|p| p.clone());
-        let mut piece = self.pieces.remove(from).ok_or(CatchAllError::EmptyField)?;
-        piece.update();
+    #[rustfmt::skip]
+    fn update(&mut self, from: &Position, to: &Position, promotion: &Option<Promotion>) -> Result<(), CatchAllError> {
+        let original = self.pieces.get(from).ok_or(CatchAllError::EmptyField)?.clone();
+        let rook = self.castle_rook(&original, from, to)?;
 
-        match piece {
-            Piece::Pawn(Color::White, _) if to.rank == 7 => {
-                self.pieces.insert(to.clone(), Piece::Queen(Color::White));
-            }
-            Piece::Pawn(Color::Black, _) if to.rank == 0 => {
-                self.pieces.insert(to.clone(), Piece::Queen(Color::Black));
-            }
-            _ => {
-                self.pieces.insert(to.clone(), piece);
+        self.pieces.remove(from);
+
+        // A pawn moving onto the en passant square captures the pawn that skipped it.
+        let captured = match (&original, self.enpassant) {
+            (Piece::Pawn(_, _), Some(target)) if &target == to => {
+                let pos = Position::new(to.file, from.rank);
+                self.pieces.remove(&pos).map(|p| (pos, p))
             }
+            _ => self.pieces.remove(to).map(|p| (*to, p)),
+        };
+
+        let mut piece = original.clone();
+        piece.update();
+
+        let mut key = self.key ^ zobrist::piece(&original, from);
+        key ^= captured.as_ref().map_or(0, |(pos, piece)| zobrist::piece(piece, pos));
+
+        if let Some((rook_from, rook_to)) = rook {
+            let rook = self.pieces.remove(&rook_from).ok_or(CatchAllError::BadCastle)?;
+            key ^= zobrist::piece(&rook, &rook_from) ^ zobrist::piece(&rook, &rook_to);
+            self.pieces.insert(rook_to, rook);
         }
 
-        self.cache = Some(MoveCache::new(from.clone(), to.clone(), captured));
+        let promoted = promotion.unwrap_or(Promotion::Queen);
+
+        let piece = match piece {
+            Piece::Pawn(Color::White, _) if to.rank == 7 => promoted.piece(Color::White),
+            Piece::Pawn(Color::Black, _) if to.rank == 0 => promoted.piece(Color::Black),
+            piece => piece,
+        };
+        key ^= zobrist::piece(&piece, to);
+        self.pieces.insert(*to, piece);
+
+        // The en passant square only exists for the ply directly following a double pawn push.
+        let enpassant = match (&original, Distance::new(from, to)) {
+            (Piece::Pawn(_, _), Distance { file: 0, rank: 2 }) => from.offset(0, 1),
+            (Piece::Pawn(_, _), Distance { file: 0, rank: -2 }) => from.offset(0, -1),
+            _ => None,
+        };
+
+        // Pawn moves and captures reset the half-move clock.
+        let halfmove_clock = match (&original, &captured) {
+            (Piece::Pawn(_, _), _) | (_, Some(_)) => 0,
+            _ => self.halfmove_clock + 1,
+        };
+
+        let mut castling = self.castling;
+        castling.update(&original, from, to);
+
+        self.cache.push(MoveCache {
+            from: *from,
+            to: *to,
+            piece: original,
+            captured,
+            rook,
+            castling: self.castling,
+            enpassant: self.enpassant,
+            halfmove_clock: self.halfmove_clock,
+            key: self.key,
+        });
+
+        key ^= zobrist::castling_rights(&self.castling);
+        self.castling = castling;
+        self.enpassant = enpassant;
+        self.halfmove_clock = halfmove_clock;
+        self.key = key ^ zobrist::castling_rights(&self.castling);
 
         Ok(())
     }
 
-    fn revert(&mut self) -> Result<(), CatchAllError> {
-        let cache = self.cache.clone().ok_or(CatchAllError::EmptyMoveCache)?;
-        let mut piece = self
-            .pieces
+    pub fn revert(&mut self) -> Result<(), CatchAllError> {
+        let cache = self.cache.pop().ok_or(CatchAllError::EmptyMoveCache)?;
+
+        // Restore the cached piece rather than the one on the board, which may have been promoted.
+        self.pieces
             .remove(&cache.to)
             .ok_or(CatchAllError::EmptyField)?;
 
-        piece.revert();
+        self.pieces.insert(cache.from, cache.piece);
 
-        self.pieces.insert(cache.from, piece);
+        if let Some((pos, captured)) = cache.captured {
+            self.pieces.insert(pos, captured);
+        }
 
-        if let Some(captured) = cache.captured {
-            self.pieces.insert(cache.to, captured);
+        if let Some((rook_from, rook_to)) = cache.rook {
+            let rook = self
+                .pieces
+                .remove(&rook_to)
+                .ok_or(CatchAllError::BadCastle)?;
+            self.pieces.insert(rook_from, rook);
         }
 
-        self.cache = None;
+        self.castling = cache.castling;
+        self.enpassant = cache.enpassant;
+        self.halfmove_clock = cache.halfmove_clock;
+        self.key = cache.key;
 
         Ok(())
     }
 
+    #[rustfmt::skip]
+    fn assess_promotion(piece: &Piece, to: &Position, promotion: &Option<Promotion>) -> Result<(), CatchAllError> {
+        match (piece, promotion) {
+            (_, None) => Ok(()),
+            (Piece::Pawn(Color::White, _), Some(_)) if to.rank == 7 => Ok(()),
+            (Piece::Pawn(Color::Black, _), Some(_)) if to.rank == 0 => Ok(()),
+            _ => Err(CatchAllError::IllegalMove(IllegalMoveReason::InvalidPromotion)),
+        }
+    }
+
     fn assess_move(&self, pos: &Position, mv: &Move) -> Result<(), CatchAllError> {
         Path::new(&pos, mv)?
             .iter()
@@ -181,121 +605,324 @@ impl Board {
 
     #[rustfmt::skip]
     fn resolve_check(&mut self, from: &Position, to: &Position, color: &Color) -> Result<(), CatchAllError> {
-        self.update(from, to)?;
+        self.update(from, to, &None)?;
 
         let res = self.in_check(color);
 
         self.revert()?;
 
-        res?.then(|| ()).map_or(Ok(()), |_| Err(CatchAllError::InCheck))?;
-
-        println!("Legal move: {:?} -> {:?}", from, to);
-
-        Ok(())
+        res?.then_some(()).map_or(Ok(()), |_| {
+            Err(CatchAllError::IllegalMove(
+                IllegalMoveReason::LeavesKingInCheck,
+            ))
+        })
     }
 
-    fn castle_rook(&mut self, color: &Color, direction: &Direction) -> Result<(), CatchAllError> {
-        let (from, to) = match (color, direction) {
-            (Color::White, Direction::Left) => (Position::new(0, 0), Position::new(3, 0)),
-            (Color::White, Direction::Right) => (Position::new(7, 0), Position::new(5, 0)),
-            (Color::Black, Direction::Left) => (Position::new(0, 7), Position::new(3, 7)),
-            (Color::Black, Direction::Right) => (Position::new(7, 7), Position::new(5, 7)),
-            _ => Err(CatchAllError::BadCastle)?,
+    // Returns the from and to positions of the rook if the king castles.
+    #[rustfmt::skip]
+    fn castle_rook(&self, piece: &Piece, from: &Position, to: &Position) -> Result<Option<(Position, Position)>, CatchAllError> {
+        let (rook_from, rook_to, direction) = match (piece, Distance::new(from, to)) {
+            (Piece::King(_), Distance { file: 2, rank: 0 }) => (Position::new(7, from.rank), Position::new(5, from.rank), Direction::Right),
+            (Piece::King(_), Distance { file: -2, rank: 0 }) => (Position::new(0, from.rank), Position::new(3, from.rank), Direction::Left),
+            _ => return Ok(None),
         };
 
-        match self.pieces.remove(&from) {
-            rook @ Some(Piece::Rook(_, MoveCounter(0))) => self
-                .pieces
-                .insert(to, rook.unwrap())
-                .map_or(Ok(()), |_| Err(CatchAllError::BadCastle)),
+        // Chess960 castling is not played yet, only the king on the e-file castles with a rook in the corner.
+        let standard = from.file == 4 && self.castling.rook_file(&piece.color(), &direction) == Some(rook_from.file);
+
+        match self.pieces.get(&rook_from) {
+            Some(Piece::Rook(color)) if standard && color == &piece.color() => Ok(Some((rook_from, rook_to))),
             _ => Err(CatchAllError::BadCastle),
         }
     }
 
-    fn resolve_castle(
-        &mut self,
-        piece: &Piece,
-        from: &Position,
-        mv: &Move,
-    ) -> Result<(), CatchAllError> {
+    #[rustfmt::skip]
+    fn resolve_castle(&mut self, piece: &Piece, from: &Position, to: &Position, mv: &Move) -> Result<(), CatchAllError> {
         match (piece, mv) {
-            (Piece::King(color, MoveCounter(0)), Move::Straight(direction, 2, Action::Regular)) => {
-                let mut path = Path::new(&from, mv)?;
-                path.push(from.clone());
-                path.iter()
-                    .try_for_each(|to| self.resolve_check(from, to, color))?;
-                self.castle_rook(color, direction)
+            (Piece::King(color), Move::Straight(direction, 2, Action::Regular)) => {
+                // Check if neither the king nor the rook have moved yet.
+                self.castling
+                    .allowed(color, direction)
+                    .then_some(())
+                    .ok_or(CatchAllError::IllegalMove(IllegalMoveReason::CastlingRightsLost))?;
+
+                // Check if all squares between king and rook are empty.
+                let (rook, _) = self.castle_rook(piece, from, to)?.ok_or(CatchAllError::BadCastle)?;
+                self.assess_move(from, &Move::new(from, &rook, Action::Regular))
+                    .map_err(|_| CatchAllError::IllegalMove(IllegalMoveReason::CastlingPathBlocked))?;
+
+                // Check if the king is not in check.
+                self.is_attacked(from, &color.opposite())
+                    .eq(&false)
+                    .then_some(())
+                    .ok_or(CatchAllError::IllegalMove(IllegalMoveReason::CastlingInCheck))?;
+
+                // Check if the king does not pass through an attacked square.
+                Path::new(from, mv)?
+                    .iter()
+                    .any(|pos| self.is_attacked(pos, &color.opposite()))
+                    .eq(&false)
+                    .then_some(())
+                    .ok_or(CatchAllError::IllegalMove(IllegalMoveReason::CastlingThroughCheck))
             }
             _ => Ok(()),
         }
     }
 
-    pub fn resolve_nomoves(&mut self, color: &Color) -> Result<(), CatchAllError> {
+    pub fn legal_moves(&mut self, color: &Color) -> Vec<(Position, Position, MoveKind)> {
+        let pins = self.pinned_pieces(color);
+        let checked = self.in_check(color).unwrap_or(true);
+
         self.pieces
             .clone()
             .iter()
             .filter(|(_, piece)| &piece.color() == color)
-            .any(|(from, piece)| {
-                piece
-                    .all_moves(&from)
-                    .iter()
-                    // Assess if path is unobstructed aswell.
-                    .any(|to| {
-                        self.assess_move(from, &Move::new(from, to, Action::Regular))
-                            .is_ok_and(|_| {
-                                self.resolve_check(&from, to, color)
-                                    .is_ok_and(|_| self.action(to, color).is_ok())
-                            })
+            .flat_map(|(from, piece)| {
+                self.destinations(from, &pins, checked)
+                    .into_iter()
+                    .flat_map(|to| {
+                        self.kinds(piece, from, &to)
+                            .into_iter()
+                            .map(move |kind| (*from, to, kind))
                     })
+                    .collect::<Vec<_>>()
             })
-            .then(|| ())
-            .ok_or(CatchAllError::NoLegalMoves)
+            .collect()
     }
 
-    fn resolve_enpassant(&mut self, piece: &Piece, to: &Position) -> Result<(), CatchAllError> {
-        let prev_pos = self
-            .enpassant
-            .and_then(|pos| match Distance::new(&pos, &to) {
-                Distance { file: 0, rank: 1 } if piece.color() == Color::White => {
-                    Some(Position::new(to.file, to.rank - 1))
+    pub fn legal_destinations(&mut self, from: &Position) -> Vec<Position> {
+        let color = match self.pieces.get(from) {
+            Some(piece) => piece.color(),
+            None => return Vec::new(),
+        };
+
+        let pins = self.pinned_pieces(&color);
+        let checked = self.in_check(&color).unwrap_or(true);
+
+        self.destinations(from, &pins, checked)
+    }
+
+    pub fn pinned_pieces(&self, color: &Color) -> Vec<Pin> {
+        let king = match self.king(color) {
+            Ok((pos, _)) => *pos,
+            Err(_) => return Vec::new(),
+        };
+
+        self.pieces
+            .iter()
+            .filter(|(_, piece)| &piece.color() != color)
+            .filter(|(_, piece)| {
+                matches!(piece, Piece::Bishop(_) | Piece::Rook(_) | Piece::Queen(_))
+            })
+            .filter_map(|(pinner, piece)| {
+                let mv = Move::new(pinner, &king, Action::Capture);
+                piece.can_reach(&mv).ok()?;
+
+                let path = Path::new(pinner, &mv).ok()?;
+                let blockers: Vec<&Position> = path
+                    .iter()
+                    .filter(|pos| self.pieces.contains_key(pos))
+                    .collect();
+
+                // Exactly one piece of the king's color between pinner and king is pinned.
+                match blockers[..] {
+                    [pos] if self.pieces.get(pos).map(|p| p.color()) == Some(*color) => Some(Pin {
+                        position: *pos,
+                        pinner: *pinner,
+                        line: path.iter().chain([pinner]).cloned().collect(),
+                    }),
+                    _ => None,
                 }
-                Distance { file: 0, rank: -1 } if piece.color() == Color::Black => {
-                    Some(Position::new(to.file, to.rank + 1))
+            })
+            .collect()
+    }
+
+    fn destinations(&mut self, from: &Position, pins: &[Pin], checked: bool) -> Vec<Position> {
+        let piece = match self.pieces.get(from) {
+            Some(piece) => piece.clone(),
+            None => return Vec::new(),
+        };
+
+        let color = piece.color();
+        let line = pins
+            .iter()
+            .find(|pin| &pin.position == from)
+            .map(|pin| pin.line.clone());
+
+        // Sliders look their targets up in the magic tables, which already respect blockers.
+        let sliding = matches!(piece, Piece::Bishop(_) | Piece::Rook(_) | Piece::Queen(_));
+        let targets = match sliding {
+            true => bitboard::positions(
+                Board::attacks(from, &piece, self.occupancy()) & !self.occupancy_of(&color),
+            )
+            .collect(),
+            false => piece.all_moves(from),
+        };
+
+        targets
+            .into_iter()
+            .filter(|to| {
+                // Only evasions, king moves and en passant captures need a trial move,
+                // any other piece leaves its king safe as long as it stays on its pin line.
+                let trial = checked
+                    || matches!(piece, Piece::King(_))
+                    || (matches!(piece, Piece::Pawn(_, _)) && self.enpassant == Some(*to));
+
+                match (trial, sliding, &line) {
+                    (true, true, _) => self.resolve_check(from, to, &color).is_ok(),
+                    (true, false, _) => self.assess_legal(&color, from, to).is_ok(),
+                    (false, true, Some(line)) => line.contains(to),
+                    (false, false, Some(line)) => {
+                        line.contains(to) && self.assess_reach(&color, from, to).is_ok()
+                    }
+                    (false, true, None) => true,
+                    (false, false, None) => self.assess_reach(&color, from, to).is_ok(),
                 }
-                _ => None,
-            });
+            })
+            .collect()
+    }
 
-        if let Some(pos) = prev_pos {
-            let enpassantable_piece = self.pieces.remove(&pos).ok_or(CatchAllError::EmptyField)?;
-            self.pieces.insert(to.clone(), enpassantable_piece);
+    // Counts the leaf nodes of the legal move tree, validating move generation against known totals.
+    pub fn perft(&mut self, color: &Color, depth: usize) -> Result<u64, CatchAllError> {
+        if depth == 0 {
+            return Ok(1);
         }
 
-        Ok(())
+        let moves = self.legal_moves(color);
+
+        if depth == 1 {
+            return Ok(moves.len() as u64);
+        }
+
+        moves.iter().try_fold(0, |nodes, (from, to, kind)| {
+            self.update(from, to, &kind.promotion())?;
+            let res = self.perft(&color.opposite(), depth - 1);
+            self.revert()?;
+
+            Ok(nodes + res?)
+        })
     }
 
-    fn enpassantable(&mut self, piece: &Piece, mv: &Move, to: &Position) {
-        if let (
-            Piece::Pawn(_, _),
-            Move::Straight(Direction::Up | Direction::Down, 2, Action::Regular),
-        ) = (piece, mv)
-        {
-            self.enpassant = Some(to.clone());
-        } else {
-            self.enpassant.take();
+    // Splits the perft count by root move to narrow down generation bugs.
+    pub fn perft_divide(
+        &mut self,
+        color: &Color,
+        depth: usize,
+    ) -> Result<Vec<(String, u64)>, CatchAllError> {
+        let mut divide = self
+            .legal_moves(color)
+            .iter()
+            .map(|(from, to, kind)| {
+                self.update(from, to, &kind.promotion())?;
+                let res = self.perft(&color.opposite(), depth.saturating_sub(1));
+                self.revert()?;
+
+                Ok((Lan::new(*from, *to, kind.promotion()).to_string(), res?))
+            })
+            .collect::<Result<Vec<_>, CatchAllError>>()?;
+
+        divide.sort();
+        Ok(divide)
+    }
+
+    pub fn resolve_nomoves(&mut self, color: &Color) -> Result<(), CatchAllError> {
+        self.legal_moves(color)
+            .is_empty()
+            .eq(&false)
+            .then_some(())
+            .ok_or(CatchAllError::NoLegalMoves)
+    }
+
+    // Classifies a legal move, yielding one kind per promotion piece for promoting pawns.
+    fn kinds(&self, piece: &Piece, from: &Position, to: &Position) -> Vec<MoveKind> {
+        let capture = self.pieces.contains_key(to);
+        let promotions = [
+            Promotion::Queen,
+            Promotion::Rook,
+            Promotion::Bishop,
+            Promotion::Knight,
+        ];
+
+        match (piece, Distance::new(from, to)) {
+            (Piece::Pawn(_, _), _) if self.enpassant == Some(*to) => {
+                vec![MoveKind::EnPassant]
+            }
+            (Piece::Pawn(_, _), _) if to.rank == 0 || to.rank == 7 => promotions
+                .into_iter()
+                .map(|p| match capture {
+                    true => MoveKind::PromotionCapture(p),
+                    false => MoveKind::Promotion(p),
+                })
+                .collect(),
+            (Piece::Pawn(_, _), Distance { rank: 2 | -2, .. }) => vec![MoveKind::DoublePush],
+            (Piece::King(_), Distance { file: 2, .. }) => vec![MoveKind::Castle(Direction::Right)],
+            (Piece::King(_), Distance { file: -2, .. }) => vec![MoveKind::Castle(Direction::Left)],
+            _ if capture => vec![MoveKind::Capture],
+            _ => vec![MoveKind::Quiet],
+        }
+    }
+
+    // Whether the given side could mate with some series of legal moves. A lone minor piece only
+    // manages with the help of the opponent's own men boxing in their king.
+    pub fn can_mate(&self, color: &Color) -> bool {
+        let (mut minors, mut others) = (0, 0);
+        for (_, piece) in self.pieces() {
+            match piece {
+                Piece::King(_) => (),
+                piece if piece.color() != *color => others += 1,
+                Piece::Knight(_) | Piece::Bishop(_) => minors += 1,
+                _ => return true,
+            }
+        }
+
+        minors > 1 || minors == 1 && others > 0
+    }
+
+    pub fn resolve_outcome(&mut self, color: &Color) -> Result<Option<Outcome>, CatchAllError> {
+        match self.resolve_nomoves(color) {
+            Err(CatchAllError::NoLegalMoves) => match self.in_check(color)? {
+                true => Ok(Some(Outcome::Checkmate(color.opposite()))),
+                false => Ok(Some(Outcome::Stalemate)),
+            },
+            res => res.map(|_| None),
         }
     }
 
     #[rustfmt::skip]
-    fn assess_turn(&mut self, color: &Color, from: &Position, to: &Position) -> Result<(), CatchAllError> {
+    fn assess_turn(&mut self, color: &Color, from: &Position, to: &Position, promotion: &Option<Promotion>) -> Result<(), CatchAllError> {
         // Check if piece of correct color is at from position.
         let piece = self.piece_at(from, color)?.clone();
 
-        self.resolve_enpassant(&piece, &to)?;
+        // Check if a requested promotion is made by a pawn reaching the last rank.
+        Board::assess_promotion(&piece, to, promotion)?;
+
+        self.assess_legal(color, from, to)?;
+
+        Ok(())
+    }
+
+    #[rustfmt::skip]
+    fn assess_legal(&mut self, color: &Color, from: &Position, to: &Position) -> Result<Move, CatchAllError> {
+        let mv = self.assess_reach(color, from, to)?;
+
+        // Check if the king would be in check after the move.
+        self.resolve_check(from, to, color)?;
+
+        Ok(mv)
+    }
+
+    #[rustfmt::skip]
+    fn assess_reach(&mut self, color: &Color, from: &Position, to: &Position) -> Result<Move, CatchAllError> {
+        let piece = self.piece_at(from, color)?.clone();
 
         // Check if piece is at to.
         // If piece of opposite color, the action will be capture.
         // If piece of same color, the path is blocked.
-        let action = self.action(to, color)?;
+        // A pawn moving onto the en passant square captures as well.
+        let action = match (&piece, self.enpassant) {
+            (Piece::Pawn(_, _), Some(target)) if &target == to => Action::Capture,
+            _ => self.action(to, color)?,
+        };
         let mv = Move::new(from, to, action);
 
         // Check if piece can reach the to position from the from position.
@@ -304,13 +931,147 @@ impl Board {
         // Check if the path taken by move from to is unobstructed.
         self.assess_move(from, &mv)?;
 
-        // Check if the king would be in check after the move.
-        self.resolve_check(from, to, color)?;
+        // Check if castling rights, empty squares and unattacked squares permit castling.
+        self.resolve_castle(&piece, from, to, &mv)?;
 
-        self.resolve_castle(&piece.clone(), from, &mv)?;
+        Ok(mv)
+    }
+}
 
-        self.enpassantable(&piece, &mv, &to);
+impl Default for Board {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
-        Ok(())
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "  +-----------------+")?;
+
+        for rank in (0..8).rev() {
+            write!(f, "{} |", rank + 1)?;
+            for file in 0..8 {
+                let symbol = self
+                    .pieces
+                    .get(&Position::new(file, rank))
+                    .map_or('.', |piece| piece.symbol());
+                write!(f, " {}", symbol)?;
+            }
+            writeln!(f, " |")?;
+        }
+
+        writeln!(f, "  +-----------------+")?;
+        write!(f, "    a b c d e f g h")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fen::Fen;
+    use crate::piece::Promotion;
+    use crate::position::Position;
+
+    // Published move generation counts, see https://www.chessprogramming.org/Perft_Results.
+    fn perft(fen: &str, counts: &[u64]) {
+        let Fen {
+            mut board, color, ..
+        } = Fen::parse(fen).unwrap();
+
+        for (depth, count) in counts.iter().enumerate() {
+            assert_eq!(
+                board.perft(&color, depth + 1).unwrap(),
+                *count,
+                "{} at depth {}",
+                fen,
+                depth + 1
+            );
+        }
+    }
+
+    #[test]
+    fn perft_initial_position() {
+        perft(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8_902, 197_281],
+        );
+    }
+
+    #[test]
+    fn perft_kiwipete() {
+        perft(
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2_039, 97_862],
+        );
+    }
+
+    #[test]
+    fn perft_position_3() {
+        perft(
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2_812, 43_238],
+        );
+    }
+
+    #[test]
+    fn perft_position_4() {
+        perft(
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9_467],
+        );
+    }
+
+    #[test]
+    fn perft_position_5() {
+        perft(
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1_486, 62_379],
+        );
+    }
+
+    #[test]
+    fn perft_position_6() {
+        perft(
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2_079, 89_890],
+        );
+    }
+
+    // The running key must match the key of the same position set up from scratch.
+    #[test]
+    fn incremental_key() {
+        let Fen {
+            mut board,
+            mut color,
+            ..
+        } = Fen::parse("r3k2r/1Pp5/8/3P4/8/8/6P1/R3K2R w KQkq - 0 1").unwrap();
+        let initial = board.hash(&color);
+
+        // A double step, en passant, castling, a capturing promotion and a captured rook.
+        let moves = [
+            ("g2", "g4", None),
+            ("c7", "c5", None),
+            ("d5", "c6", None),
+            ("e8", "g8", None),
+            ("b7", "a8", Some(Promotion::Queen)),
+            ("f8", "a8", None),
+            ("e1", "g1", None),
+            ("g8", "h8", None),
+        ];
+
+        for (from, to, promotion) in moves {
+            let (from, to): (Position, Position) = (from.parse().unwrap(), to.parse().unwrap());
+            board.advance(&color, &from, &to, promotion).unwrap();
+            color = color.opposite();
+
+            let fen = board.to_fen(&color, 1);
+            let scratch = Fen::parse(&fen).unwrap().board;
+            assert_eq!(board.hash(&color), scratch.hash(&color), "{}", fen);
+        }
+
+        for _ in moves {
+            board.revert().unwrap();
+            color = color.opposite();
+        }
+        assert_eq!(board.hash(&color), initial);
     }
 }
@@ -0,0 +1,168 @@
+use crate::piece::{Color, Piece};
+use crate::position::Position;
+use crate::r#move::Direction;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CastlingRights {
+    pub white_kingside: bool,
+    pub white_queenside: bool,
+    pub black_kingside: bool,
+    pub black_queenside: bool,
+    // Files of the rooks each right castles with, in the order of the rights. Chess960 places
+    // them anywhere on either side of the king.
+    rook_files: [usize; 4],
+}
+
+// The side and back rank of each right, in the order of the rights.
+const SIDES: [(Color, Direction, usize); 4] = [
+    (Color::White, Direction::Right, 0),
+    (Color::White, Direction::Left, 0),
+    (Color::Black, Direction::Right, 7),
+    (Color::Black, Direction::Left, 7),
+];
+
+impl Default for CastlingRights {
+    fn default() -> Self {
+        Self {
+            white_kingside: true,
+            white_queenside: true,
+            black_kingside: true,
+            black_queenside: true,
+            rook_files: [7, 0, 7, 0],
+        }
+    }
+}
+
+impl CastlingRights {
+    pub fn none() -> Self {
+        Self {
+            white_kingside: false,
+            white_queenside: false,
+            black_kingside: false,
+            black_queenside: false,
+            rook_files: [7, 0, 7, 0],
+        }
+    }
+
+    pub fn allowed(&self, color: &Color, direction: &Direction) -> bool {
+        match (color, direction) {
+            (Color::White, Direction::Right) => self.white_kingside,
+            (Color::White, Direction::Left) => self.white_queenside,
+            (Color::Black, Direction::Right) => self.black_kingside,
+            (Color::Black, Direction::Left) => self.black_queenside,
+            _ => false,
+        }
+    }
+
+    pub fn set(&mut self, color: &Color, direction: &Direction, allowed: bool) {
+        let right = match (color, direction) {
+            (Color::White, Direction::Right) => &mut self.white_kingside,
+            (Color::White, Direction::Left) => &mut self.white_queenside,
+            (Color::Black, Direction::Right) => &mut self.black_kingside,
+            (Color::Black, Direction::Left) => &mut self.black_queenside,
+            _ => return,
+        };
+        *right = allowed;
+    }
+
+    pub fn rook_file(&self, color: &Color, direction: &Direction) -> Option<usize> {
+        CastlingRights::index(color, direction).map(|index| self.rook_files[index])
+    }
+
+    pub fn set_rook_file(&mut self, color: &Color, direction: &Direction, file: usize) {
+        if let Some(index) = CastlingRights::index(color, direction) {
+            self.rook_files[index] = file;
+        }
+    }
+
+    fn index(color: &Color, direction: &Direction) -> Option<usize> {
+        SIDES
+            .iter()
+            .position(|(c, d, _)| c == color && d == direction)
+    }
+
+    // Rights are lost as soon as a king moves, or a rook leaves its home square or is captured on it.
+    pub fn update(&mut self, piece: &Piece, from: &Position, to: &Position) {
+        if let Piece::King(color) = piece {
+            self.set(color, &Direction::Right, false);
+            self.set(color, &Direction::Left, false);
+        }
+
+        for (index, (color, direction, rank)) in SIDES.iter().enumerate() {
+            let home = Position::new(self.rook_files[index], *rank);
+            if from == &home || to == &home {
+                self.set(color, direction, false);
+            }
+        }
+    }
+
+    // Rights ordered as white kingside, white queenside, black kingside, black queenside.
+    pub fn rights(&self) -> [bool; 4] {
+        [
+            self.white_kingside,
+            self.white_queenside,
+            self.black_kingside,
+            self.black_queenside,
+        ]
+    }
+
+    // Shredder-FEN names each right by the file of its rook, e.g. "HAha".
+    pub fn to_shredder(&self) -> String {
+        let rights: String = self
+            .rights()
+            .iter()
+            .zip(self.symbols(|_, _| false))
+            .filter(|(right, _)| **right)
+            .map(|(_, symbol)| symbol)
+            .collect();
+
+        match rights.is_empty() {
+            true => "-".to_string(),
+            false => rights,
+        }
+    }
+
+    // The symbol of each right, either the side's letter if the rook's file is implied or the file.
+    fn symbols(&self, implied: impl Fn(&Direction, usize) -> bool) -> Vec<char> {
+        SIDES
+            .iter()
+            .zip(self.rook_files)
+            .map(|((color, direction, _), file)| {
+                let symbol = match (implied(direction, file), direction) {
+                    (true, Direction::Right) => 'K',
+                    (true, _) => 'Q',
+                    (false, _) => (b'A' + file as u8) as char,
+                };
+
+                match color {
+                    Color::White => symbol,
+                    Color::Black => symbol.to_ascii_lowercase(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for CastlingRights {
+    // X-FEN keeps the usual letters for rooks in the corners and names any other rook by its file.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rights: String = self
+            .rights()
+            .iter()
+            .zip(self.symbols(|direction, file| match direction {
+                Direction::Right => file == 7,
+                _ => file == 0,
+            }))
+            .filter(|(right, _)| **right)
+            .map(|(_, symbol)| symbol)
+            .collect();
+
+        match rights.is_empty() {
+            true => write!(f, "-"),
+            false => write!(f, "{}", rights),
+        }
+    }
+}
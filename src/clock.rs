@@ -0,0 +1,282 @@
+use crate::piece::Color;
+use crate::timing::Instant;
+
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TimeControl {
+    Unlimited,
+    Bullet,
+    Blitz,
+    Rapid,
+    Classical,
+}
+
+impl TimeControl {
+    pub const ALL: [TimeControl; 5] = [
+        TimeControl::Unlimited,
+        TimeControl::Bullet,
+        TimeControl::Blitz,
+        TimeControl::Rapid,
+        TimeControl::Classical,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        TimeControl::ALL
+            .into_iter()
+            .find(|control| format!("{:?}", control).eq_ignore_ascii_case(name))
+    }
+
+    // Time each side has for the whole game, if the game is timed at all.
+    pub fn base(&self) -> Option<Duration> {
+        let minutes = match self {
+            TimeControl::Unlimited => return None,
+            TimeControl::Bullet => 1,
+            TimeControl::Blitz => 5,
+            TimeControl::Rapid => 15,
+            TimeControl::Classical => 90,
+        };
+
+        Some(Duration::from_secs(minutes * 60))
+    }
+}
+
+impl fmt::Display for TimeControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.base() {
+            Some(base) => write!(f, "{:?} ({} min)", self, base.as_secs() / 60),
+            None => write!(f, "Unlimited"),
+        }
+    }
+}
+
+// Choices of bonus time, in seconds.
+const BONUS_SECONDS: [u64; 5] = [2, 3, 5, 10, 30];
+
+// Time given back to a player for each move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Bonus {
+    None,
+    // Fischer: added after each move.
+    Increment(Duration),
+    // Bronstein: what the move took is given back, up to the delay.
+    Bronstein(Duration),
+    // Simple delay: the clock only starts running once the delay has passed.
+    Delay(Duration),
+}
+
+impl Bonus {
+    pub fn choices() -> Vec<Bonus> {
+        let durations = BONUS_SECONDS.map(Duration::from_secs);
+
+        std::iter::once(Bonus::None)
+            .chain(durations.map(Bonus::Increment))
+            .chain(durations.map(Bonus::Bronstein))
+            .chain(durations.map(Bonus::Delay))
+            .collect()
+    }
+}
+
+impl fmt::Display for Bonus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Bonus::None => write!(f, "No bonus"),
+            Bonus::Increment(time) => write!(f, "{} s increment", time.as_secs()),
+            Bonus::Bronstein(time) => write!(f, "{} s Bronstein delay", time.as_secs()),
+            Bonus::Delay(time) => write!(f, "{} s delay", time.as_secs()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Clock {
+    // Time left to white and black, not counting the running period.
+    white: Duration,
+    black: Duration,
+    bonus: Bonus,
+    // Side whose time is running and since when.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    running: Option<(Color, Instant)>,
+}
+
+impl Clock {
+    pub fn new(base: Duration, bonus: Bonus) -> Self {
+        Self {
+            white: base,
+            black: base,
+            bonus,
+            running: None,
+        }
+    }
+
+    pub fn bonus(&self) -> Bonus {
+        self.bonus
+    }
+
+    pub fn remaining(&self, color: &Color) -> Duration {
+        let left = match color {
+            Color::White => self.white,
+            Color::Black => self.black,
+        };
+
+        match self.running {
+            Some((running, since)) if running == *color => left.saturating_sub(self.charge(since)),
+            _ => left,
+        }
+    }
+
+    pub fn running(&self) -> Option<Color> {
+        self.running.map(|(color, _)| color)
+    }
+
+    // Time left to white and black, not counting the running period.
+    pub fn times(&self) -> (Duration, Duration) {
+        (self.white, self.black)
+    }
+
+    // Sets the time of both sides back to what they had, as when taking back a move, and starts
+    // the given side's time.
+    pub fn restore(&mut self, (white, black): (Duration, Duration), color: &Color) {
+        self.running = None;
+        self.white = white;
+        self.black = black;
+        self.start(color);
+    }
+
+    // Starts the time of the given side, stopping the other's.
+    pub fn start(&mut self, color: &Color) {
+        self.stop();
+        self.running = Some((*color, Instant::now()));
+    }
+
+    // Charges the running side for the time it used.
+    pub fn stop(&mut self) {
+        if let Some((color, _)) = self.running {
+            let left = self.remaining(&color);
+            *self.time_mut(&color) = left;
+            self.running = None;
+        }
+    }
+
+    // Completes the move of the running side, crediting its bonus unless its time ran out, and
+    // starts the other side's time.
+    pub fn press(&mut self) {
+        let (color, since) = match self.running {
+            Some(running) => running,
+            None => return,
+        };
+        let used = since.elapsed();
+        self.stop();
+
+        let credit = match self.bonus {
+            Bonus::Increment(increment) => increment,
+            Bonus::Bronstein(delay) => used.min(delay),
+            Bonus::None | Bonus::Delay(_) => Duration::ZERO,
+        };
+        let time = self.time_mut(&color);
+        if !time.is_zero() {
+            *time += credit;
+        }
+
+        self.start(&color.opposite());
+    }
+
+    // Time the running period counts for, which a simple delay shortens.
+    fn charge(&self, since: Instant) -> Duration {
+        match self.bonus {
+            Bonus::Delay(delay) => since.elapsed().saturating_sub(delay),
+            _ => since.elapsed(),
+        }
+    }
+
+    fn time_mut(&mut self, color: &Color) -> &mut Duration {
+        match color {
+            Color::White => &mut self.white,
+            Color::Black => &mut self.black,
+        }
+    }
+}
+
+// Minutes and seconds, with tenths once less than ten seconds are left.
+pub fn format_time(time: Duration) -> String {
+    let seconds = time.as_secs();
+    match seconds < 10 {
+        true => format!("0:{:02}.{}", seconds, time.subsec_millis() / 100),
+        false => format!("{}:{:02}", seconds / 60, seconds % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Bonus, Clock};
+    use crate::piece::Color;
+    use crate::timing::Instant;
+
+    use std::time::Duration;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    // Plays a move of white that took the given time, leaving black's time running.
+    fn move_taking(bonus: Bonus, seconds: u64) -> Clock {
+        let mut clock = Clock::new(MINUTE, bonus);
+        clock.running = Some((Color::White, Instant::now() - Duration::from_secs(seconds)));
+        clock.press();
+        clock
+    }
+
+    // Whether the time is the expected one, give or take the time the test itself takes.
+    fn about(time: Duration, seconds: u64) -> bool {
+        time.abs_diff(Duration::from_secs(seconds)) < Duration::from_millis(500)
+    }
+
+    #[test]
+    fn increment() {
+        let clock = move_taking(Bonus::Increment(Duration::from_secs(5)), 2);
+
+        assert!(about(clock.remaining(&Color::White), 63));
+        assert_eq!(clock.times().1, MINUTE);
+        assert_eq!(clock.running(), Some(Color::Black));
+    }
+
+    #[test]
+    fn bronstein() {
+        // Up to the delay, the time a move took is given back.
+        let clock = move_taking(Bonus::Bronstein(Duration::from_secs(5)), 2);
+        assert!(about(clock.remaining(&Color::White), 60));
+
+        let clock = move_taking(Bonus::Bronstein(Duration::from_secs(5)), 8);
+        assert!(about(clock.remaining(&Color::White), 57));
+    }
+
+    #[test]
+    fn delay() {
+        // The time only runs once the delay has passed.
+        let clock = move_taking(Bonus::Delay(Duration::from_secs(5)), 2);
+        assert_eq!(clock.remaining(&Color::White), MINUTE);
+
+        let clock = move_taking(Bonus::Delay(Duration::from_secs(5)), 8);
+        assert!(about(clock.remaining(&Color::White), 57));
+    }
+
+    #[test]
+    fn no_bonus_after_flag() {
+        let mut clock = Clock::new(Duration::from_secs(1), Bonus::Increment(MINUTE));
+        clock.running = Some((Color::White, Instant::now() - Duration::from_secs(2)));
+        clock.press();
+
+        assert!(clock.remaining(&Color::White).is_zero());
+    }
+
+    #[test]
+    fn restore() {
+        let mut clock = move_taking(Bonus::Increment(Duration::from_secs(5)), 2);
+        clock.restore((MINUTE, MINUTE), &Color::White);
+
+        assert_eq!(clock.times(), (MINUTE, MINUTE));
+        assert_eq!(clock.running(), Some(Color::White));
+    }
+}
@@ -0,0 +1,148 @@
+use crate::accuracy;
+use crate::annotation;
+use crate::error::PgnError;
+use crate::game::Game;
+use crate::pgn::Pgn;
+use crate::piece::Color;
+use crate::timing::{SystemTime, UNIX_EPOCH};
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+
+pub const DEFAULT_PATH: &str = "games.pgn";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Summary {
+    pub white: String,
+    pub black: String,
+    pub result: String,
+    pub date: String,
+    pub eco: String,
+    // Accuracy of white and black in percent, for games annotated with evaluations.
+    pub accuracy: Option<(f64, f64)>,
+}
+
+impl Summary {
+    pub fn new(pgn: &Pgn) -> Self {
+        let tag = |name: &str| pgn.tag(name).unwrap_or("?").to_string();
+        let accuracy = annotation::evaluations(pgn).and_then(|evals| {
+            Some((
+                accuracy::accuracy(&evals, &Color::White)?,
+                accuracy::accuracy(&evals, &Color::Black)?,
+            ))
+        });
+
+        Self {
+            white: tag("White"),
+            black: tag("Black"),
+            result: tag("Result"),
+            date: tag("Date"),
+            eco: tag("ECO"),
+            accuracy,
+        }
+    }
+}
+
+// A PGN file that finished games are appended to.
+pub struct Database {
+    path: PathBuf,
+}
+
+impl Database {
+    pub fn new(path: &str) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn record(&self, game: &Game) -> io::Result<()> {
+        let tags = [
+            ("Event", "Casual game".to_string()),
+            ("Site", "?".to_string()),
+            ("Date", today()),
+            ("Round", "-".to_string()),
+            ("White", "?".to_string()),
+            ("Black", "?".to_string()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+        self.append(&Pgn::from_game(game, tags))
+    }
+
+    pub fn append(&self, pgn: &Pgn) -> io::Result<()> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+
+        writeln!(file, "{}", pgn)
+    }
+
+    pub fn summaries(&self) -> Result<Vec<Result<Summary, PgnError>>, PgnError> {
+        self.pgns().map(|pgns| {
+            pgns.into_iter()
+                .map(|pgn| pgn.map(|pgn| Summary::new(&pgn)))
+                .collect()
+        })
+    }
+
+    // Each game is parsed on its own, so that one broken game leaves the others readable.
+    pub fn pgns(&self) -> Result<Vec<Result<Pgn, PgnError>>, PgnError> {
+        Ok(self.games()?.iter().map(|game| Pgn::parse(game)).collect())
+    }
+
+    pub fn load(&self, index: usize) -> Result<Pgn, PgnError> {
+        self.games()?
+            .get(index)
+            .ok_or(PgnError::Io(io::ErrorKind::NotFound))
+            .and_then(|game| Pgn::parse(game))
+    }
+
+    // Splits the file into games, each starting with a tag pair after the previous movetext.
+    fn games(&self) -> Result<Vec<String>, PgnError> {
+        let text = match fs::read_to_string(&self.path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(PgnError::Io(e.kind())),
+        };
+
+        let mut games: Vec<String> = Vec::new();
+        let mut movetext = true;
+
+        for line in text.lines() {
+            let tag = line.trim_start().starts_with('[');
+
+            if tag && movetext {
+                games.push(String::new());
+            }
+            if let Some(game) = games.last_mut() {
+                game.push_str(line);
+                game.push('\n');
+            }
+
+            movetext = !tag && (movetext || !line.trim().is_empty());
+        }
+
+        Ok(games)
+    }
+}
+
+// Current UTC date in PGN format, converted from days since the epoch with Hinnant's algorithm.
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs() / 86400) as i64;
+
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!("{:04}.{:02}.{:02}", year, month, day)
+}
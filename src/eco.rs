@@ -0,0 +1,78 @@
+use crate::board::Board;
+use crate::game::Game;
+use crate::piece::Color;
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+// Tab-separated ECO code, opening name and moves in SAN from the initial position.
+const TABLE: &str = include_str!("eco.tsv");
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Opening {
+    pub eco: String,
+    pub name: String,
+}
+
+struct Table {
+    // Openings keyed by the Zobrist hash of the position their moves lead to.
+    positions: HashMap<u64, Opening>,
+    // Length of the longest line in the table, in plies.
+    plies: usize,
+}
+
+static OPENINGS: OnceLock<Table> = OnceLock::new();
+
+fn openings() -> &'static Table {
+    OPENINGS.get_or_init(|| {
+        let mut table = Table {
+            positions: HashMap::new(),
+            plies: 0,
+        };
+
+        for line in TABLE.lines() {
+            let (eco, name, moves) = match line.split('\t').collect::<Vec<_>>()[..] {
+                [eco, name, moves] => (eco, name, moves),
+                _ => continue,
+            };
+
+            let mut game = Game::new();
+            let moves: Vec<&str> = moves.split_whitespace().collect();
+            if moves.iter().any(|san| game.advance_san(san).is_err()) {
+                continue;
+            }
+
+            table.plies = table.plies.max(moves.len());
+            table.positions.insert(
+                game.board().hash(&game.color()),
+                Opening {
+                    eco: eco.to_string(),
+                    name: name.to_string(),
+                },
+            );
+        }
+
+        table
+    })
+}
+
+// Names the opening of a game after the last of its positions found in the table, so that
+// transpositions are recognised. Games not starting from the initial position have none.
+pub fn classify(game: &Game) -> Option<Opening> {
+    if game.initial_fen() != Board::new().to_fen(&Color::White, 1) {
+        return None;
+    }
+
+    let table = openings();
+    let mut replay = Game::new();
+    let mut opening = None;
+
+    for san in game.moves().iter().take(table.plies) {
+        replay.advance_san(san).ok()?;
+        if let Some(found) = table.positions.get(&replay.board().hash(&replay.color())) {
+            opening = Some(found);
+        }
+    }
+
+    opening.cloned()
+}
@@ -0,0 +1,273 @@
+use crate::error::EngineError;
+use crate::lan::Lan;
+use crate::search::MATE;
+
+use std::fmt;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+
+// Time an engine gets to answer the handshake before it is given up on.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(5);
+// Depth searched when neither a depth nor a time is asked for.
+const DEFAULT_DEPTH: usize = 18;
+// How often a running analysis looks whether it has been asked to stop.
+const STOP_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Score {
+    Centipawns(i32),
+    // Moves until mate, negative when the side to move gets mated.
+    Mate(i32),
+}
+
+impl Score {
+    // Reads a score of the built-in search limited to the given depth, where mates count plies.
+    pub fn from_search(score: i32, depth: usize) -> Self {
+        match score {
+            score if score.abs() >= MATE - depth as i32 => {
+                let plies = MATE - score.abs();
+                Score::Mate(score.signum() * (plies + 1) / 2)
+            }
+            score => Score::Centipawns(score),
+        }
+    }
+
+    // The same score from the opponent's point of view.
+    pub fn flipped(&self) -> Self {
+        match self {
+            Score::Centipawns(cp) => Score::Centipawns(-cp),
+            Score::Mate(moves) => Score::Mate(-moves),
+        }
+    }
+}
+
+impl fmt::Display for Score {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Score::Centipawns(cp) => write!(f, "{:+.2}", *cp as f64 / 100.0),
+            Score::Mate(moves) if *moves < 0 => write!(f, "-M{}", -moves),
+            Score::Mate(moves) => write!(f, "M{}", moves),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Info {
+    pub depth: usize,
+    // Rank of the line when the engine reports several.
+    pub multipv: usize,
+    pub score: Option<Score>,
+    pub nodes: Option<u64>,
+    pub pv: Vec<Lan>,
+}
+
+impl Info {
+    // Reads the fields of an info line the analysis cares about, ignoring everything else.
+    pub fn parse(line: &str) -> Option<Self> {
+        let mut tokens = line.split_whitespace();
+        if tokens.next() != Some("info") {
+            return None;
+        }
+
+        let mut info = Info {
+            depth: 0,
+            multipv: 1,
+            score: None,
+            nodes: None,
+            pv: Vec::new(),
+        };
+
+        while let Some(token) = tokens.next() {
+            match token {
+                "depth" => info.depth = tokens.next()?.parse().ok()?,
+                "multipv" => info.multipv = tokens.next()?.parse().ok()?,
+                "nodes" => info.nodes = tokens.next()?.parse().ok(),
+                "score" => {
+                    info.score = match (tokens.next()?, tokens.next()?.parse().ok()?) {
+                        ("cp", cp) => Some(Score::Centipawns(cp)),
+                        ("mate", moves) => Some(Score::Mate(moves)),
+                        _ => None,
+                    }
+                }
+                // The principal variation runs until the end of the line.
+                "pv" => {
+                    info.pv = tokens
+                        .by_ref()
+                        .map_while(|lan| Lan::parse(lan).ok())
+                        .collect();
+                }
+                "string" => return None,
+                _ => (),
+            }
+        }
+
+        // Lines without a score only report progress such as the current move.
+        info.score.map(|_| info)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Analysis {
+    pub best_move: Option<Lan>,
+    // Latest report for each line, ordered by rank.
+    pub lines: Vec<Info>,
+}
+
+pub struct Engine {
+    name: String,
+    process: Child,
+    stdin: ChildStdin,
+    // Lines printed by the engine, read on a separate thread so that waiting can time out.
+    output: Receiver<String>,
+}
+
+impl Engine {
+    // Starts the engine executable at the given path and performs the UCI handshake.
+    pub fn spawn(path: &str) -> Result<Self, EngineError> {
+        let mut process = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| EngineError::Spawn(e.kind()))?;
+
+        let stdin = process.stdin.take().ok_or(EngineError::Handshake)?;
+        let stdout = process.stdout.take().ok_or(EngineError::Handshake)?;
+
+        let (sender, output) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut engine = Self {
+            name: path.to_string(),
+            process,
+            stdin,
+            output,
+        };
+
+        engine.send("uci")?;
+        while let Some(line) = engine.receive(HANDSHAKE_TIMEOUT)? {
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.to_string();
+            }
+            if line == "uciok" {
+                engine.ready()?;
+                return Ok(engine);
+            }
+        }
+
+        Err(EngineError::Handshake)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_option(&mut self, name: &str, value: &str) -> Result<(), EngineError> {
+        self.send(&format!("setoption name {} value {}", name, value))?;
+        self.ready()
+    }
+
+    pub fn new_game(&mut self) -> Result<(), EngineError> {
+        self.send("ucinewgame")?;
+        self.ready()
+    }
+
+    // Analyses the position for the given depth or time, passing every report on as it arrives.
+    // Raising the stop flag ends the analysis early with the engine's results so far.
+    #[rustfmt::skip]
+    pub fn analyse(&mut self, fen: &str, depth: Option<usize>, time: Option<Duration>, stop: &AtomicBool, mut report: impl FnMut(&Info)) -> Result<Analysis, EngineError> {
+        self.send(&format!("position fen {}", fen))?;
+
+        let mut go = String::from("go");
+        if let Some(depth) = depth {
+            go.push_str(&format!(" depth {}", depth));
+        }
+        if let Some(time) = time {
+            go.push_str(&format!(" movetime {}", time.as_millis()));
+        }
+        if depth.is_none() && time.is_none() {
+            go.push_str(&format!(" depth {}", DEFAULT_DEPTH));
+        }
+        self.send(&go)?;
+
+        let mut lines: Vec<Info> = Vec::new();
+        let mut stopping = false;
+
+        loop {
+            if !stopping && stop.load(Ordering::Relaxed) {
+                self.send("stop")?;
+                stopping = true;
+            }
+
+            let line = match self.receive(STOP_INTERVAL)? {
+                Some(line) => line,
+                None => continue,
+            };
+
+            if let Some(best_move) = line.strip_prefix("bestmove") {
+                lines.sort_by_key(|info| info.multipv);
+
+                return Ok(Analysis {
+                    best_move: best_move
+                        .split_whitespace()
+                        .next()
+                        .and_then(|lan| Lan::parse(lan).ok()),
+                    lines,
+                });
+            }
+
+            if let Some(info) = Info::parse(&line) {
+                report(&info);
+                match lines.iter_mut().find(|line| line.multipv == info.multipv) {
+                    Some(line) => *line = info,
+                    None => lines.push(info),
+                }
+            }
+        }
+    }
+
+    fn ready(&mut self) -> Result<(), EngineError> {
+        self.send("isready")?;
+        while let Some(line) = self.receive(HANDSHAKE_TIMEOUT)? {
+            if line == "readyok" {
+                return Ok(());
+            }
+        }
+
+        Err(EngineError::Handshake)
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), EngineError> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| EngineError::Io(e.kind()))
+    }
+
+    // Waits for the next line, yielding None once the timeout passes.
+    fn receive(&self, timeout: Duration) -> Result<Option<String>, EngineError> {
+        match self.output.recv_timeout(timeout) {
+            Ok(line) => Ok(Some(line)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(EngineError::Terminated),
+        }
+    }
+}
+
+impl Drop for Engine {
+    fn drop(&mut self) {
+        // An engine ignoring the request to quit must not outlive its owner.
+        let _ = self.send("quit");
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
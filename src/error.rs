@@ -1,32 +1,247 @@
+use crate::i18n::{Language, Translate};
+
 use std::fmt;
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IllegalMoveReason {
+    EmptySquare,
+    WrongTurn,
+    OwnPieceOnTarget,
+    Unreachable,
+    PathBlocked,
+    LeavesKingInCheck,
+    CastlingRightsLost,
+    CastlingPathBlocked,
+    CastlingInCheck,
+    CastlingThroughCheck,
+    InvalidPromotion,
+}
+
 #[derive(Debug, PartialEq)]
 pub enum CatchAllError {
     NoLegalMoves,
     BadCastle,
     EmptyMoveCache,
     NoKing,
-    InCheck,
     InvalidPath,
-    BlockedPath,
     EmptyField,
-    UnreachableField,
     InvalidTurn,
+    IllegalMove(IllegalMoveReason),
+    GameOver,
+    NoDrawOffer,
+    NoClaimableDraw,
+    KingCount,
+    PawnOnBackRank,
+    OpponentInCheck,
+    TooManyPieces,
+    BadEnPassant,
+    BadSan,
+    AmbiguousSan,
+    NoMatchingMove,
+    BadLan,
+    BadSquare,
+    ImageExport,
+    // Holds the path of the drawing that could not be read.
+    BadPieceSet(String),
 }
 
-impl fmt::Display for CatchAllError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+#[derive(Debug, PartialEq)]
+pub enum FenError {
+    FieldCount,
+    Placement,
+    SideToMove,
+    Castling,
+    EnPassant,
+    HalfmoveClock,
+    FullmoveNumber,
+    InvalidPosition(CatchAllError),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PgnError {
+    Io(std::io::ErrorKind),
+    Tag,
+    Fen(FenError),
+    IllegalMove(String, CatchAllError),
+    Download(String),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PuzzleError {
+    Io(std::io::ErrorKind),
+    Format,
+    Fen(FenError),
+    IllegalMove(String, CatchAllError),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EngineError {
+    Spawn(std::io::ErrorKind),
+    Io(std::io::ErrorKind),
+    Handshake,
+    Terminated,
+}
+
+impl Translate for IllegalMoveReason {
+    fn tr(&self, language: Language) -> String {
+        let text = match self {
+            IllegalMoveReason::EmptySquare => "there is no piece to move",
+            IllegalMoveReason::WrongTurn => "it is not this side's turn",
+            IllegalMoveReason::OwnPieceOnTarget => "the destination is occupied by an own piece",
+            IllegalMoveReason::Unreachable => "the piece cannot move that way",
+            IllegalMoveReason::PathBlocked => "the path is blocked",
+            IllegalMoveReason::LeavesKingInCheck => "the king would be in check",
+            IllegalMoveReason::CastlingRightsLost => "the king or rook has already moved",
+            IllegalMoveReason::CastlingPathBlocked => {
+                "the squares between king and rook are occupied"
+            }
+            IllegalMoveReason::CastlingInCheck => "the king cannot castle out of check",
+            IllegalMoveReason::CastlingThroughCheck => {
+                "the king cannot castle through an attacked square"
+            }
+            IllegalMoveReason::InvalidPromotion => "the promotion is invalid",
+        };
+
+        language.tr(text).to_string()
+    }
+}
+
+impl Translate for CatchAllError {
+    fn tr(&self, language: Language) -> String {
+        let text = match self {
+            CatchAllError::NoLegalMoves => "no legal moves possible",
+            CatchAllError::BadCastle => "invalid castle",
+            CatchAllError::EmptyMoveCache => "the move cache is empty",
+            CatchAllError::NoKing => "the king does not exist",
+            CatchAllError::InvalidPath => "the path is invalid",
+            CatchAllError::EmptyField => "the field is empty",
+            CatchAllError::InvalidTurn => "the turn is invalid",
+            CatchAllError::IllegalMove(reason) => {
+                return language.fill("illegal move: {}", &[&reason.tr(language)])
+            }
+            CatchAllError::GameOver => "the game is over",
+            CatchAllError::NoDrawOffer => "no draw has been offered",
+            CatchAllError::NoClaimableDraw => "no draw can be claimed",
+            CatchAllError::KingCount => "each side needs exactly one king",
+            CatchAllError::PawnOnBackRank => "a pawn is on the first or last rank",
+            CatchAllError::OpponentInCheck => "the side not to move is in check",
+            CatchAllError::TooManyPieces => "too many pieces for one side",
+            CatchAllError::BadEnPassant => "invalid en passant square",
+            CatchAllError::BadSan => "the move notation is malformed",
+            CatchAllError::AmbiguousSan => "the move notation is ambiguous",
+            CatchAllError::NoMatchingMove => "no legal move matches the notation",
+            CatchAllError::BadLan => "the coordinate notation is malformed",
+            CatchAllError::BadSquare => "the square is not on the board",
+            CatchAllError::ImageExport => "the image could not be exported",
+            CatchAllError::BadPieceSet(path) => {
+                return language.fill("the piece drawing {} could not be read", &[path])
+            }
+        };
+
+        language.tr(text).to_string()
+    }
+}
+
+impl Translate for FenError {
+    fn tr(&self, language: Language) -> String {
+        let text = match self {
+            FenError::FieldCount => "the FEN has the wrong number of fields",
+            FenError::Placement => "the piece placement is malformed",
+            FenError::SideToMove => "the side to move is malformed",
+            FenError::Castling => "the castling rights are malformed",
+            FenError::EnPassant => "the en passant square is malformed",
+            FenError::HalfmoveClock => "the half-move clock is malformed",
+            FenError::FullmoveNumber => "the full-move number is malformed or too large",
+            FenError::InvalidPosition(e) => {
+                return language.fill("the position is invalid: {}", &[&e.tr(language)])
+            }
+        };
+
+        language.tr(text).to_string()
+    }
+}
+
+impl Translate for PgnError {
+    fn tr(&self, language: Language) -> String {
+        match self {
+            PgnError::Io(kind) => language.fill("the PGN could not be read: {}", &[kind]),
+            PgnError::Tag => language.tr("a tag pair is malformed").to_string(),
+            PgnError::Fen(e) => language.fill("the FEN tag is invalid: {}", &[&e.tr(language)]),
+            PgnError::IllegalMove(san, e) => language.fill(
+                "the move {} cannot be played: {}",
+                &[&language.san(san), &e.tr(language)],
+            ),
+            PgnError::Download(e) => language.fill("the game could not be downloaded: {}", &[e]),
+        }
+    }
+}
+
+impl Translate for PuzzleError {
+    fn tr(&self, language: Language) -> String {
+        match self {
+            PuzzleError::Io(kind) => language.fill("the puzzles could not be read: {}", &[kind]),
+            PuzzleError::Format => language.tr("a puzzle line is malformed").to_string(),
+            PuzzleError::Fen(e) => {
+                language.fill("the puzzle position is invalid: {}", &[&e.tr(language)])
+            }
+            PuzzleError::IllegalMove(lan, e) => language.fill(
+                "the puzzle move {} cannot be played: {}",
+                &[lan, &e.tr(language)],
+            ),
+        }
+    }
+}
+
+impl Translate for EngineError {
+    fn tr(&self, language: Language) -> String {
         match self {
-            CatchAllError::NoLegalMoves => write!(f, "no legal moves possible"),
-            CatchAllError::BadCastle => write!(f, "invalid castle"),
-            CatchAllError::EmptyMoveCache => write!(f, "the move cache is empty"),
-            CatchAllError::NoKing => write!(f, "the king does not exist"),
-            CatchAllError::InCheck => write!(f, "the king is in check"),
-            CatchAllError::InvalidPath => write!(f, "the path is invalid"),
-            CatchAllError::BlockedPath => write!(f, "the path is blocked"),
-            CatchAllError::EmptyField => write!(f, "the field is empty"),
-            CatchAllError::UnreachableField => write!(f, "the field is unreachable"),
-            CatchAllError::InvalidTurn => write!(f, "the turn is invalid"),
+            EngineError::Spawn(kind) => {
+                language.fill("the engine could not be started: {}", &[kind])
+            }
+            EngineError::Io(kind) => language.fill("the engine could not be reached: {}", &[kind]),
+            EngineError::Handshake => language
+                .tr("the engine did not answer the UCI handshake")
+                .to_string(),
+            EngineError::Terminated => language
+                .tr("the engine terminated unexpectedly")
+                .to_string(),
         }
     }
 }
+
+// Outside the app's windows, as in the command-line tools, errors read in English.
+impl fmt::Display for IllegalMoveReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
+
+impl fmt::Display for CatchAllError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
+
+impl fmt::Display for FenError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
+
+impl fmt::Display for PgnError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
+
+impl fmt::Display for PuzzleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
+
+impl fmt::Display for EngineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
@@ -0,0 +1,251 @@
+use crate::bitboard::{self, Bitboard};
+use crate::board::Board;
+use crate::piece::{Color, Piece};
+use crate::position::Position;
+
+// Piece-square tables from white's point of view, laid out like a diagram with rank 8 on top.
+#[rustfmt::skip]
+const PAWN: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+    50, 50, 50, 50, 50, 50, 50, 50,
+    10, 10, 20, 30, 30, 20, 10, 10,
+     5,  5, 10, 25, 25, 10,  5,  5,
+     0,  0,  0, 20, 20,  0,  0,  0,
+     5, -5,-10,  0,  0,-10, -5,  5,
+     5, 10, 10,-20,-20, 10, 10,  5,
+     0,  0,  0,  0,  0,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const KNIGHT: [i32; 64] = [
+    -50,-40,-30,-30,-30,-30,-40,-50,
+    -40,-20,  0,  0,  0,  0,-20,-40,
+    -30,  0, 10, 15, 15, 10,  0,-30,
+    -30,  5, 15, 20, 20, 15,  5,-30,
+    -30,  0, 15, 20, 20, 15,  0,-30,
+    -30,  5, 10, 15, 15, 10,  5,-30,
+    -40,-20,  0,  5,  5,  0,-20,-40,
+    -50,-40,-30,-30,-30,-30,-40,-50,
+];
+
+#[rustfmt::skip]
+const BISHOP: [i32; 64] = [
+    -20,-10,-10,-10,-10,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5, 10, 10,  5,  0,-10,
+    -10,  5,  5, 10, 10,  5,  5,-10,
+    -10,  0, 10, 10, 10, 10,  0,-10,
+    -10, 10, 10, 10, 10, 10, 10,-10,
+    -10,  5,  0,  0,  0,  0,  5,-10,
+    -20,-10,-10,-10,-10,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const ROOK: [i32; 64] = [
+     0,  0,  0,  0,  0,  0,  0,  0,
+     5, 10, 10, 10, 10, 10, 10,  5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+    -5,  0,  0,  0,  0,  0,  0, -5,
+     0,  0,  0,  5,  5,  0,  0,  0,
+];
+
+#[rustfmt::skip]
+const QUEEN: [i32; 64] = [
+    -20,-10,-10, -5, -5,-10,-10,-20,
+    -10,  0,  0,  0,  0,  0,  0,-10,
+    -10,  0,  5,  5,  5,  5,  0,-10,
+     -5,  0,  5,  5,  5,  5,  0, -5,
+      0,  0,  5,  5,  5,  5,  0, -5,
+    -10,  5,  5,  5,  5,  5,  0,-10,
+    -10,  0,  5,  0,  0,  0,  0,-10,
+    -20,-10,-10, -5, -5,-10,-10,-20,
+];
+
+#[rustfmt::skip]
+const KING_MIDDLEGAME: [i32; 64] = [
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -30,-40,-40,-50,-50,-40,-40,-30,
+    -20,-30,-30,-40,-40,-30,-30,-20,
+    -10,-20,-20,-20,-20,-20,-20,-10,
+     20, 20,  0,  0,  0,  0, 20, 20,
+     20, 30, 10,  0,  0, 10, 30, 20,
+];
+
+#[rustfmt::skip]
+const KING_ENDGAME: [i32; 64] = [
+    -50,-40,-30,-20,-20,-30,-40,-50,
+    -30,-20,-10,  0,  0,-10,-20,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 30, 40, 40, 30,-10,-30,
+    -30,-10, 20, 30, 30, 20,-10,-30,
+    -30,-30,  0,  0,  0,  0,-30,-30,
+    -50,-30,-30,-30,-30,-30,-30,-50,
+];
+
+// Bonus of a passed pawn by the number of ranks it has advanced.
+const PASSED_PAWN: [i32; 8] = [0, 5, 10, 20, 35, 60, 100, 0];
+const DOUBLED_PAWN: i32 = -10;
+const ISOLATED_PAWN: i32 = -15;
+const PAWN_SHIELD: i32 = 10;
+const KING_ZONE_ATTACK: i32 = -8;
+
+// Phase contributed by the pieces of the starting position, 24 in total.
+const TOTAL_PHASE: i32 = 24;
+
+// Centipawn score of the position from the perspective of the given side to move.
+pub fn evaluate(board: &Board, color: &Color) -> i32 {
+    let phase = phase(board);
+
+    let score = side(board, &Color::White, phase) - side(board, &Color::Black, phase);
+
+    match color {
+        Color::White => score,
+        Color::Black => -score,
+    }
+}
+
+fn side(board: &Board, color: &Color, phase: i32) -> i32 {
+    let occupancy = board.occupancy();
+    let own = board.occupancy_of(color);
+
+    let pieces = board
+        .pieces()
+        .filter(|(_, piece)| &piece.color() == color)
+        .map(|(pos, piece)| {
+            let mobility = (Board::attacks(pos, piece, occupancy) & !own).count_ones() as i32;
+
+            piece.value()
+                + match piece {
+                    Piece::Pawn(_, _) => PAWN[index(pos, color)],
+                    Piece::Knight(_) => KNIGHT[index(pos, color)] + 4 * mobility,
+                    Piece::Bishop(_) => BISHOP[index(pos, color)] + 5 * mobility,
+                    Piece::Rook(_) => ROOK[index(pos, color)] + 2 * mobility,
+                    Piece::Queen(_) => QUEEN[index(pos, color)] + mobility,
+                    Piece::King(_) => taper(
+                        KING_MIDDLEGAME[index(pos, color)] + king_safety(board, pos, color),
+                        KING_ENDGAME[index(pos, color)],
+                        phase,
+                    ),
+                }
+        })
+        .sum::<i32>();
+
+    pieces + pawn_structure(board, color)
+}
+
+fn pawn_structure(board: &Board, color: &Color) -> i32 {
+    let own = pawns(board, color);
+    let enemy = pawns(board, &color.opposite());
+
+    bitboard::positions(own)
+        .map(|pos| {
+            let file = file_mask(pos.file);
+            let neighbours = adjacent_files(pos.file);
+
+            let doubled = match (own & file & ahead(&pos, color)) != 0 {
+                true => DOUBLED_PAWN,
+                false => 0,
+            };
+
+            let isolated = match own & neighbours == 0 {
+                true => ISOLATED_PAWN,
+                false => 0,
+            };
+
+            let passed = match enemy & (file | neighbours) & ahead(&pos, color) == 0 {
+                true => PASSED_PAWN[advancement(&pos, color)],
+                false => 0,
+            };
+
+            doubled + isolated + passed
+        })
+        .sum()
+}
+
+// Rewards pawns sheltering the king and punishes enemy attacks next to it.
+fn king_safety(board: &Board, king: &Position, color: &Color) -> i32 {
+    let zone = bitboard::king_attacks(king);
+
+    let shield = (zone & ahead(king, color) & pawns(board, color)).count_ones() as i32;
+
+    let occupancy = board.occupancy();
+    let attacks = board
+        .pieces()
+        .filter(|(_, piece)| piece.color() != *color)
+        .fold(0, |attacks, (pos, piece)| {
+            attacks | Board::attacks(pos, piece, occupancy)
+        });
+
+    PAWN_SHIELD * shield + KING_ZONE_ATTACK * (zone & attacks).count_ones() as i32
+}
+
+// Blends middlegame and endgame scores by the material left on the board.
+fn taper(middlegame: i32, endgame: i32, phase: i32) -> i32 {
+    (middlegame * phase + endgame * (TOTAL_PHASE - phase)) / TOTAL_PHASE
+}
+
+fn phase(board: &Board) -> i32 {
+    board
+        .pieces()
+        .map(|(_, piece)| match piece {
+            Piece::Knight(_) | Piece::Bishop(_) => 1,
+            Piece::Rook(_) => 2,
+            Piece::Queen(_) => 4,
+            _ => 0,
+        })
+        .sum::<i32>()
+        .min(TOTAL_PHASE)
+}
+
+fn pawns(board: &Board, color: &Color) -> Bitboard {
+    board
+        .pieces()
+        .filter(|(_, piece)| matches!(piece, Piece::Pawn(c, _) if c == color))
+        .fold(0, |pawns, (pos, _)| pawns | bitboard::bit(pos))
+}
+
+fn index(pos: &Position, color: &Color) -> usize {
+    match color {
+        Color::White => (7 - pos.rank) * 8 + pos.file,
+        Color::Black => pos.rank * 8 + pos.file,
+    }
+}
+
+fn advancement(pos: &Position, color: &Color) -> usize {
+    match color {
+        Color::White => pos.rank,
+        Color::Black => 7 - pos.rank,
+    }
+}
+
+fn file_mask(file: usize) -> Bitboard {
+    0x0101_0101_0101_0101 << file
+}
+
+fn adjacent_files(file: usize) -> Bitboard {
+    let left = match file {
+        0 => 0,
+        _ => file_mask(file - 1),
+    };
+    let right = match file {
+        7 => 0,
+        _ => file_mask(file + 1),
+    };
+
+    left | right
+}
+
+// Every square on the ranks in front of the given one, as seen by the given side.
+fn ahead(pos: &Position, color: &Color) -> Bitboard {
+    match (color, pos.rank) {
+        (Color::White, 7) | (Color::Black, 0) => 0,
+        (Color::White, rank) => !0 << ((rank + 1) * 8),
+        (Color::Black, rank) => !0 >> ((8 - rank) * 8),
+    }
+}
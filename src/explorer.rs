@@ -0,0 +1,152 @@
+use crate::database::{Database, Summary};
+use crate::error::PgnError;
+use crate::game::Game;
+use crate::pgn::Pgn;
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    pub white: usize,
+    pub draws: usize,
+    pub black: usize,
+    // Games still in progress or abandoned, counted but without a result.
+    pub unfinished: usize,
+}
+
+impl Stats {
+    pub fn games(&self) -> usize {
+        self.white + self.draws + self.black + self.unfinished
+    }
+
+    // Shares of white wins, draws and black wins among the finished games, in percent.
+    pub fn percentages(&self) -> (f64, f64, f64) {
+        let finished = (self.white + self.draws + self.black).max(1) as f64;
+        let percent = |count: usize| 100.0 * count as f64 / finished;
+
+        (
+            percent(self.white),
+            percent(self.draws),
+            percent(self.black),
+        )
+    }
+
+    fn add(&mut self, result: &str) {
+        match result {
+            "1-0" => self.white += 1,
+            "1/2-1/2" => self.draws += 1,
+            "0-1" => self.black += 1,
+            _ => self.unfinished += 1,
+        }
+    }
+}
+
+// Moves played from each position of a collection of games, keyed by the position's hash.
+#[derive(Debug, Clone, Default)]
+pub struct Explorer {
+    positions: HashMap<u64, Vec<(String, Stats)>>,
+    // Games reaching each position, by their index in the collection and the number of moves
+    // played when they first did.
+    references: HashMap<u64, Vec<(usize, usize)>>,
+    // Summaries of the games in the order of the collection, with none for unreadable games.
+    summaries: Vec<Option<Summary>>,
+}
+
+impl Explorer {
+    pub fn from_database(database: &Database) -> Result<Self, PgnError> {
+        database.pgns().map(|pgns| Self::from_pgns(&pgns))
+    }
+
+    pub fn from_pgns(pgns: &[Result<Pgn, PgnError>]) -> Self {
+        let mut explorer = Self::default();
+
+        // Unreadable games are skipped but keep their place, so that indices match the collection.
+        for pgn in pgns {
+            match pgn {
+                Ok(pgn) => explorer.add(pgn),
+                Err(_) => explorer.summaries.push(None),
+            }
+        }
+
+        // Most popular moves first.
+        for moves in explorer.positions.values_mut() {
+            moves.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.games()));
+        }
+
+        explorer
+    }
+
+    // Number of games indexed.
+    pub fn games(&self) -> usize {
+        self.summaries.iter().flatten().count()
+    }
+
+    // Number of games skipped for being unreadable.
+    pub fn unreadable(&self) -> usize {
+        self.summaries.len() - self.games()
+    }
+
+    // The moves played in the position with the given hash, most popular first.
+    pub fn moves(&self, hash: u64) -> &[(String, Stats)] {
+        self.positions
+            .get(&hash)
+            .map_or(&[], |moves| moves.as_slice())
+    }
+
+    // The games reaching the position with the given hash, in the order of the collection, each
+    // with the number of moves played when it got there.
+    pub fn references(&self, hash: u64) -> Vec<(usize, usize, &Summary)> {
+        self.references.get(&hash).map_or(Vec::new(), |games| {
+            games
+                .iter()
+                .filter_map(|&(index, moves)| Some((index, moves, self.summaries[index].as_ref()?)))
+                .collect()
+        })
+    }
+
+    // Walks the game back from its end, counting each move for the position it was played in.
+    fn add(&mut self, pgn: &Pgn) {
+        let result = pgn.tag("Result").unwrap_or("*");
+        let index = self.summaries.len();
+        let mut game = pgn.game.clone();
+        self.reference(&game, index);
+
+        while let Some(san) = game.moves().last().cloned() {
+            // A parsed game can always be taken back, move by move.
+            if game.revert().is_err() {
+                break;
+            }
+            self.reference(&game, index);
+
+            let moves = self
+                .positions
+                .entry(game.board().hash(&game.color()))
+                .or_default();
+            match moves.iter_mut().find(|(played, _)| *played == san) {
+                Some((_, stats)) => stats.add(result),
+                None => {
+                    let mut stats = Stats::default();
+                    stats.add(result);
+                    moves.push((san, stats));
+                }
+            }
+        }
+
+        self.summaries.push(Some(Summary::new(pgn)));
+    }
+
+    // Notes that the game reached its current position. As games are walked back, an earlier
+    // visit of the same position replaces the later one.
+    fn reference(&mut self, game: &Game, index: usize) {
+        let games = self
+            .references
+            .entry(game.board().hash(&game.color()))
+            .or_default();
+        let moves = game.moves().len();
+
+        match games.last_mut() {
+            Some((last, reached)) if *last == index => *reached = moves,
+            _ => games.push((index, moves)),
+        }
+    }
+}
@@ -0,0 +1,201 @@
+use crate::board::Board;
+use crate::castling::CastlingRights;
+use crate::error::FenError;
+use crate::piece::{Color, Piece};
+use crate::position::Position;
+use crate::r#move::Direction;
+
+// Far more moves than the seventy-five-move rule lets any game last, and small enough that the
+// ply count derived from it cannot overflow.
+const MAX_FULLMOVE_NUMBER: usize = 10_000;
+
+pub struct Fen {
+    pub board: Board,
+    pub color: Color,
+    pub fullmove_number: usize,
+}
+
+impl Fen {
+    pub fn parse(fen: &str) -> Result<Self, FenError> {
+        // The clocks may be omitted, as is common for EPD-style positions.
+        let fields: Vec<&str> = fen.split_whitespace().collect();
+        let (placement, side, castling, enpassant, halfmove, fullmove) = match fields[..] {
+            [p, s, c, e, h, f] => (p, s, c, e, h, f),
+            [p, s, c, e] => (p, s, c, e, "0", "1"),
+            _ => return Err(FenError::FieldCount),
+        };
+
+        let mut board = Fen::placement(placement)?;
+
+        let color = match side {
+            "w" => Color::White,
+            "b" => Color::Black,
+            _ => return Err(FenError::SideToMove),
+        };
+
+        board.set_castling(Fen::castling(castling, &board)?);
+
+        board.set_enpassant(match enpassant {
+            "-" => None,
+            square => Some(square.parse().map_err(|_| FenError::EnPassant)?),
+        });
+
+        board.set_halfmove_clock(halfmove.parse().map_err(|_| FenError::HalfmoveClock)?);
+
+        let fullmove_number = fullmove
+            .parse()
+            .ok()
+            .filter(|n| (1..=MAX_FULLMOVE_NUMBER).contains(n))
+            .ok_or(FenError::FullmoveNumber)?;
+
+        board.validate(&color).map_err(FenError::InvalidPosition)?;
+
+        Ok(Self {
+            board,
+            color,
+            fullmove_number,
+        })
+    }
+
+    fn placement(placement: &str) -> Result<Board, FenError> {
+        let rows: Vec<&str> = placement.split('/').collect();
+
+        if rows.len() != 8 {
+            return Err(FenError::Placement);
+        }
+
+        let mut board = Board::empty();
+
+        for (i, row) in rows.iter().enumerate() {
+            let rank = 7 - i;
+            let mut file = 0;
+
+            for symbol in row.chars() {
+                match symbol.to_digit(10) {
+                    Some(empty @ 1..=8) => file += empty as usize,
+                    _ => {
+                        let piece = Piece::from_symbol(symbol).ok_or(FenError::Placement)?;
+                        let pos = Position::try_new(file, rank).ok_or(FenError::Placement)?;
+                        board.place(&pos, piece);
+                        file += 1;
+                    }
+                }
+            }
+
+            if file != 8 {
+                return Err(FenError::Placement);
+            }
+        }
+
+        Ok(board)
+    }
+
+    fn castling(castling: &str, board: &Board) -> Result<CastlingRights, FenError> {
+        if castling == "-" {
+            return Ok(CastlingRights::none());
+        }
+
+        castling
+            .chars()
+            .try_fold(CastlingRights::none(), |mut rights, symbol| {
+                let (color, rank) = match symbol.is_ascii_uppercase() {
+                    true => (Color::White, 0),
+                    false => (Color::Black, 7),
+                };
+
+                // Without its king on the back rank, validation rejects the right anyway.
+                let king = board
+                    .king(&color)
+                    .ok()
+                    .filter(|(pos, _)| pos.rank == rank)
+                    .map_or(4, |(pos, _)| pos.file);
+                let rook = |file: &usize| {
+                    board
+                        .at(&Position::new(*file, rank))
+                        .is_ok_and(|piece| piece == &Piece::Rook(color))
+                };
+
+                // X-FEN letters stand for the outermost rook on their side of the king, while
+                // Shredder-FEN names the rook's file.
+                let (direction, file) = match symbol.to_ascii_lowercase() {
+                    'k' => (
+                        Direction::Right,
+                        (king + 1..8).rev().find(rook).unwrap_or(7),
+                    ),
+                    'q' => (Direction::Left, (0..king).find(rook).unwrap_or(0)),
+                    file @ 'a'..='h' => match (file as u8 - b'a') as usize {
+                        file if file > king => (Direction::Right, file),
+                        file => (Direction::Left, file),
+                    },
+                    _ => return Err(FenError::Castling),
+                };
+
+                rights.set(&color, &direction, true);
+                rights.set_rook_file(&color, &direction, file);
+                Ok(rights)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fen;
+    use crate::piece::Color;
+    use crate::r#move::Direction;
+
+    fn round_trip(fen: &str) {
+        let parsed = Fen::parse(fen).unwrap();
+        assert_eq!(
+            parsed.board.to_fen(&parsed.color, parsed.fullmove_number),
+            fen
+        );
+    }
+
+    #[test]
+    fn round_trip_positions() {
+        round_trip("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1");
+        round_trip("rnbqkbnr/pp1ppppp/8/2p5/4P3/8/PPPP1PPP/RNBQKBNR w KQkq c6 0 2");
+        round_trip("r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R b Kq - 3 17");
+        round_trip("8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1");
+    }
+
+    #[test]
+    fn omitted_clocks() {
+        let parsed = Fen::parse("8/8/8/4k3/8/8/8/4K2R w K -").unwrap();
+        assert_eq!(
+            parsed.board.to_fen(&parsed.color, parsed.fullmove_number),
+            "8/8/8/4k3/8/8/8/4K2R w K - 0 1"
+        );
+    }
+
+    #[test]
+    fn shredder_castling() {
+        // Rooks on their usual files are written with the usual letters.
+        let parsed = Fen::parse("r3k2r/8/8/8/8/8/8/R3K2R w HAha - 0 1").unwrap();
+        assert_eq!(
+            parsed.board.to_fen(&parsed.color, 1),
+            "r3k2r/8/8/8/8/8/8/R3K2R w KQkq - 0 1"
+        );
+
+        round_trip("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1");
+    }
+
+    #[test]
+    fn chess960_rook_files() {
+        let parsed = Fen::parse("1r2k1r1/8/8/8/8/8/8/1R2K1R1 w GBgb - 0 1").unwrap();
+        let castling = parsed.board.castling();
+
+        for color in [Color::White, Color::Black] {
+            assert_eq!(castling.rook_file(&color, &Direction::Left), Some(1));
+            assert_eq!(castling.rook_file(&color, &Direction::Right), Some(6));
+        }
+    }
+
+    #[test]
+    fn invalid_fields() {
+        assert!(Fen::parse("8/8/8/8/8/8/8/8 w - - 0").is_err());
+        assert!(Fen::parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR x KQkq - 0 1").is_err());
+        assert!(Fen::parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBN w KQkq - 0 1").is_err());
+        assert!(Fen::parse("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 0").is_err());
+    }
+}
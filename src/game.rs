@@ -1,89 +1,543 @@
 use crate::board::Board;
-use crate::error::CatchAllError;
+use crate::clock::{Bonus, Clock, TimeControl};
+use crate::error::{CatchAllError, FenError, IllegalMoveReason};
+use crate::fen::Fen;
+use crate::json;
+use crate::lan::Lan;
+use crate::outcome::{DrawReason, GameStatus, Outcome};
 use crate::piece::Color;
-use crate::piece::Piece;
+use crate::piece::{Piece, Promotion};
 use crate::position::Position;
+use crate::r#move::MoveKind;
+use crate::san::San;
+
+use std::collections::HashMap;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Turn {
     New(Color),
     Select(Color, Position),
+    Over(Outcome),
 }
 
+#[derive(Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Game {
     board: Board,
+    // Position the game started from.
+    initial_fen: String,
     turn: Turn,
+    // Number of occurrences of each position, keyed by its Zobrist hash.
+    history: HashMap<u64, usize>,
+    // Color of the side with a pending draw offer.
+    draw_offer: Option<Color>,
+    // Indices of the moves a draw was offered with, whether or not it was accepted.
+    #[cfg_attr(feature = "serde", serde(default))]
+    draw_offers: Vec<usize>,
+    // Standard algebraic notation of each move played.
+    moves: Vec<String>,
+    // Half-moves played since the start of the game, counting from white's first move.
+    ply: usize,
+    // Time left to both sides, in timed games.
+    clock: Option<Clock>,
+    // Time left to white and black before each move, given back when the move is taken back.
+    #[cfg_attr(feature = "serde", serde(default))]
+    clock_history: Vec<Option<(Duration, Duration)>>,
+}
+
+// One position of a game, before the move played in it.
+pub struct Ply {
+    pub fen: String,
+    pub board: Board,
+    pub color: Color,
+    pub san: String,
+    pub lan: Lan,
 }
 
 impl Game {
     pub fn new() -> Self {
+        let board = Board::new();
+        let history = HashMap::from([(board.hash(&Color::White), 1)]);
+
         Self {
-            board: Board::new(),
+            initial_fen: board.to_fen(&Color::White, 1),
+            board,
             turn: Turn::New(Color::White),
+            history,
+            draw_offer: None,
+            draw_offers: Vec::new(),
+            moves: Vec::new(),
+            ply: 0,
+            clock: None,
+            clock_history: Vec::new(),
         }
     }
 
+    pub fn from_fen(fen: &str) -> Result<Self, FenError> {
+        let Fen {
+            board,
+            color,
+            fullmove_number,
+        } = Fen::parse(fen)?;
+
+        let history = HashMap::from([(board.hash(&color), 1)]);
+        let ply = (fullmove_number - 1) * 2
+            + match color {
+                Color::White => 0,
+                Color::Black => 1,
+            };
+
+        Ok(Self {
+            initial_fen: board.to_fen(&color, fullmove_number),
+            board,
+            turn: Turn::New(color),
+            history,
+            draw_offer: None,
+            draw_offers: Vec::new(),
+            moves: Vec::new(),
+            ply,
+            clock: None,
+            clock_history: Vec::new(),
+        })
+    }
+
     pub fn reset(&mut self) {
-        self.turn = Turn::New(Color::White);
-        self.board = Board::new();
+        *self = Self::new();
+    }
+
+    // Times the rest of the game, starting the clock of the side to move.
+    pub fn set_clock(&mut self, control: TimeControl, bonus: Bonus) {
+        let color = self.color();
+        self.clock = control.base().map(|base| Clock::new(base, bonus));
+
+        if let (Some(clock), Turn::New(_) | Turn::Select(_, _)) = (&mut self.clock, self.turn) {
+            clock.start(&color);
+        }
+    }
+
+    pub fn clock(&self) -> Option<&Clock> {
+        self.clock.as_ref()
+    }
+
+    // Ends the game once the side to move has run out of time. Their opponent wins unless they
+    // could not have mated, in which case the game is drawn.
+    pub fn adjudicate_flag(&mut self) -> Option<Outcome> {
+        let clock = self.clock.as_ref()?;
+        let color = clock.running()?;
+        if !clock.remaining(&color).is_zero() {
+            return None;
+        }
+
+        let outcome = match self.board.can_mate(&color.opposite()) {
+            true => Outcome::Timeout(color.opposite()),
+            false => Outcome::Draw(DrawReason::TimeoutVsInsufficientMaterial),
+        };
+        self.turn = Turn::Over(outcome);
+        self.draw_offer = None;
+        self.stop_clock();
+
+        Some(outcome)
     }
 
     pub fn reset_turn(&mut self) {
         self.turn = match self.turn {
             Turn::New(color) => Turn::New(color),
             Turn::Select(color, _) => Turn::New(color),
+            Turn::Over(outcome) => Turn::Over(outcome),
         }
     }
 
-    pub fn advance(&mut self, pos: &Position) -> Result<(), CatchAllError> {
+    #[rustfmt::skip]
+    pub fn advance(&mut self, pos: &Position, promotion: Option<Promotion>) -> Result<GameStatus, CatchAllError> {
+        // A move made after the flag fell does not count.
+        self.adjudicate_flag();
+
         self.turn = match self.turn {
             Turn::New(_) => self.select(pos)?,
-            Turn::Select(_, _) => self.play(pos)?,
+            Turn::Select(_, _) => self.play(pos, promotion)?,
+            Turn::Over(_) => Err(CatchAllError::GameOver)?,
         };
 
+        self.status()
+    }
+
+    // Plays a complete move, undoing the selection if the move is rejected.
+    #[rustfmt::skip]
+    pub fn play_move(&mut self, from: &Position, to: &Position, promotion: Option<Promotion>) -> Result<GameStatus, CatchAllError> {
+        self.reset_turn();
+        self.advance(from, None)?;
+
+        let status = self.advance(to, promotion);
+        if status.is_err() {
+            self.reset_turn();
+        }
+
+        status
+    }
+
+    pub fn advance_san(&mut self, san: &str) -> Result<GameStatus, CatchAllError> {
+        let color = self.color();
+        let (from, to, promotion) = San::parse(san)?.resolve(&mut self.board, &color)?;
+        self.play_move(&from, &to, promotion)
+    }
+
+    pub fn advance_lan(&mut self, lan: &str) -> Result<GameStatus, CatchAllError> {
+        let Lan {
+            from,
+            to,
+            promotion,
+        } = Lan::parse(lan)?;
+        self.play_move(&from, &to, promotion)
+    }
+
+    pub fn status(&self) -> Result<GameStatus, CatchAllError> {
+        match self.turn {
+            Turn::New(color) | Turn::Select(color, _) => {
+                match (self.board.in_check(&color)?, self.claimable_draw()) {
+                    (true, _) => Ok(GameStatus::Check),
+                    (false, Some(reason)) => Ok(GameStatus::DrawClaimable(reason)),
+                    (false, None) => Ok(GameStatus::Ongoing),
+                }
+            }
+            Turn::Over(outcome) => Ok(GameStatus::from(outcome)),
+        }
+    }
+
+    pub fn revert(&mut self) -> Result<(), CatchAllError> {
+        let color = self
+            .board
+            .last_move()
+            .ok_or(CatchAllError::EmptyMoveCache)?
+            .piece
+            .color();
+
+        if let Some(repetitions) = self.history.get_mut(&self.board.hash(&color.opposite())) {
+            *repetitions -= 1;
+        }
+
+        self.board.revert()?;
+        self.moves.pop();
+        self.ply -= 1;
+        self.turn = Turn::New(color);
+        self.draw_offer = None;
+        let moves = self.moves.len();
+        self.draw_offers.retain(|index| *index < moves);
+
+        // The time used on the move and on the reply so far is given back, along with any bonus.
+        match (&mut self.clock, self.clock_history.pop().flatten()) {
+            (Some(clock), Some(times)) => clock.restore(times, &color),
+            (Some(clock), None) => clock.start(&color),
+            (None, _) => (),
+        }
+
+        Ok(())
+    }
+
+    pub fn resign(&mut self, color: &Color) -> Result<(), CatchAllError> {
+        self.assess_ongoing()?;
+        self.turn = Turn::Over(Outcome::Resignation(color.opposite()));
+        self.draw_offer = None;
+        self.stop_clock();
+
+        Ok(())
+    }
+
+    pub fn offer_draw(&mut self, color: &Color) -> Result<(), CatchAllError> {
+        self.assess_ongoing()?;
+        self.draw_offer = Some(*color);
+
+        // An offer made on move goes with the move that follows it, otherwise with the one just made.
+        let index = match self.color() == *color {
+            true => self.moves.len(),
+            false => self.moves.len().saturating_sub(1),
+        };
+        if !self.draw_offers.contains(&index) {
+            self.draw_offers.push(index);
+        }
+
         Ok(())
     }
 
+    pub fn accept_draw(&mut self) -> Result<(), CatchAllError> {
+        self.assess_ongoing()?;
+        self.draw_offer.take().ok_or(CatchAllError::NoDrawOffer)?;
+        self.turn = Turn::Over(Outcome::Draw(DrawReason::Agreement));
+        self.stop_clock();
+
+        Ok(())
+    }
+
+    pub fn claimable_draw(&self) -> Option<DrawReason> {
+        let color = match self.turn {
+            Turn::New(color) | Turn::Select(color, _) => color,
+            Turn::Over(_) => return None,
+        };
+
+        let repetitions = self.history.get(&self.board.hash(&color)).unwrap_or(&0);
+
+        match (repetitions, self.board.halfmove_clock()) {
+            (3.., _) => Some(DrawReason::ThreefoldRepetition),
+            (_, 100..) => Some(DrawReason::FiftyMoveRule),
+            _ => None,
+        }
+    }
+
+    pub fn claim_draw(&mut self) -> Result<(), CatchAllError> {
+        let reason = self
+            .claimable_draw()
+            .ok_or(CatchAllError::NoClaimableDraw)?;
+        self.turn = Turn::Over(Outcome::Draw(reason));
+        self.draw_offer = None;
+        self.stop_clock();
+
+        Ok(())
+    }
+
+    pub fn decline_draw(&mut self) {
+        self.draw_offer = None;
+    }
+
+    pub fn draw_offer(&self) -> Option<Color> {
+        self.draw_offer
+    }
+
+    pub fn draw_offers(&self) -> &[usize] {
+        &self.draw_offers
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
     pub fn at(&self, pos: &Position) -> Result<&Piece, CatchAllError> {
         self.board.at(pos)
     }
 
+    pub fn legal_destinations(&mut self, pos: &Position) -> Vec<Position> {
+        self.board.legal_destinations(pos)
+    }
+
+    pub fn legal_moves(&mut self) -> Vec<(Position, Position, MoveKind)> {
+        match self.turn {
+            Turn::New(color) | Turn::Select(color, _) => self.board.legal_moves(&color),
+            Turn::Over(_) => Vec::new(),
+        }
+    }
+
+    pub fn outcome(&self) -> Option<Outcome> {
+        match self.turn {
+            Turn::Over(outcome) => Some(outcome),
+            _ => None,
+        }
+    }
+
+    pub fn initial_fen(&self) -> &str {
+        &self.initial_fen
+    }
+
     pub fn turn(&self) -> Turn {
         self.turn
     }
 
+    pub fn halfmove_clock(&self) -> u32 {
+        self.board.halfmove_clock()
+    }
+
+    pub fn to_fen(&self) -> String {
+        self.board.to_fen(&self.color(), self.fullmove_number())
+    }
+
+    // Color of the side to move, or that would be to move if the game had not ended.
+    pub fn color(&self) -> Color {
+        match self.turn {
+            Turn::New(color) | Turn::Select(color, _) => color,
+            Turn::Over(_) => self
+                .board
+                .last_move()
+                .map_or(Color::White, |mv| mv.piece.color().opposite()),
+        }
+    }
+
+    pub fn to_json_state(&mut self) -> Result<String, CatchAllError> {
+        json::state(self)
+    }
+
+    pub fn moves(&self) -> &[String] {
+        &self.moves
+    }
+
+    // Every position of the game together with the move played in it, found by taking the moves back.
+    pub fn plies(&self) -> Result<Vec<Ply>, CatchAllError> {
+        let mut game = self.clone();
+        let mut plies = Vec::new();
+
+        while let Some(san) = game.moves.last().cloned() {
+            game.revert()?;
+
+            let mut board = game.board.clone();
+            let color = game.color();
+            let (from, to, promotion) = San::parse(&san)?.resolve(&mut board, &color)?;
+
+            plies.push(Ply {
+                fen: game.to_fen(),
+                board,
+                color,
+                san,
+                lan: Lan::new(from, to, promotion),
+            });
+        }
+
+        plies.reverse();
+        Ok(plies)
+    }
+
+    pub fn ply(&self) -> usize {
+        self.ply
+    }
+
+    pub fn fullmove_number(&self) -> usize {
+        self.ply / 2 + 1
+    }
+
+    fn assess_ongoing(&self) -> Result<(), CatchAllError> {
+        match self.turn {
+            Turn::Over(_) => Err(CatchAllError::GameOver),
+            _ => Ok(()),
+        }
+    }
+
+    fn record(&mut self, color: &Color) -> usize {
+        let repetitions = self.history.entry(self.board.hash(color)).or_insert(0);
+        *repetitions += 1;
+        *repetitions
+    }
+
+    fn stop_clock(&mut self) {
+        if let Some(clock) = &mut self.clock {
+            clock.stop();
+        }
+    }
+
     fn select(&mut self, pos: &Position) -> Result<Turn, CatchAllError> {
         match self.turn {
             Turn::New(color) => self
                 .board
-                .at(&pos)?
+                .at(pos)
+                .map_err(|_| CatchAllError::IllegalMove(IllegalMoveReason::EmptySquare))?
                 .color()
                 .eq(&color)
-                .then(|| ())
-                .map_or(Err(CatchAllError::InvalidTurn), |_| {
-                    Ok(Turn::Select(color, pos.clone()))
-                }),
+                .then_some(())
+                .map_or(
+                    Err(CatchAllError::IllegalMove(IllegalMoveReason::WrongTurn)),
+                    |_| Ok(Turn::Select(color, *pos)),
+                ),
             _ => Err(CatchAllError::InvalidTurn),
         }
     }
 
-    fn play(&mut self, pos: &Position) -> Result<Turn, CatchAllError> {
+    #[rustfmt::skip]
+    fn play(&mut self, pos: &Position, promotion: Option<Promotion>) -> Result<Turn, CatchAllError> {
         match self.turn {
             Turn::Select(color, from) => {
-                self.board.advance(&color, &from, pos)?;
-                self.board.resolve_nomoves(&color)?;
-                match color {
-                    Color::White => {
-                        self.board.resolve_nomoves(&Color::Black)?;
-                        Ok(Turn::New(Color::Black))
+                // An illegal move fails to format as well, but advancing reports why.
+                let san = San::format(&mut self.board, &color, &from, pos, promotion);
+                self.board.advance(&color, &from, pos, promotion)?;
+                self.moves.push(san?);
+                self.clock_history.push(self.clock.as_ref().map(Clock::times));
+                self.ply += 1;
+
+                let opponent = color.opposite();
+
+                // Playing a move instead of accepting declines the opponent's draw offer.
+                if self.draw_offer == Some(opponent) {
+                    self.draw_offer = None;
+                }
+
+                let repetitions = self.record(&opponent);
+
+                let turn = match self.board.resolve_outcome(&opponent)? {
+                    Some(outcome) => Turn::Over(outcome),
+                    None if repetitions >= 5 => {
+                        Turn::Over(Outcome::Draw(DrawReason::FivefoldRepetition))
                     }
-                    Color::Black => {
-                        self.board.resolve_nomoves(&Color::White)?;
-                        Ok(Turn::New(Color::White))
+                    None if self.board.halfmove_clock() >= 150 => {
+                        Turn::Over(Outcome::Draw(DrawReason::SeventyFiveMoveRule))
                     }
+                    None => Turn::New(opponent),
+                };
+
+                match (&mut self.clock, turn) {
+                    (Some(clock), Turn::New(_)) => clock.press(),
+                    (Some(clock), _) => clock.stop(),
+                    (None, _) => (),
                 }
+
+                Ok(turn)
             }
             _ => Err(CatchAllError::InvalidTurn),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::Game;
+    use crate::clock::{Bonus, TimeControl};
+    use crate::piece::Color;
+    use crate::r#move::Direction;
+
+    use std::time::Duration;
+
+    #[test]
+    fn takeback_restores_position() {
+        let mut game = Game::from_fen("r3k2r/2p5/8/3P4/8/8/8/R3K2R b KQkq - 0 1").unwrap();
+        let mut fens = vec![game.to_fen()];
+
+        for san in ["c5", "dxc6", "O-O-O", "Ke2"] {
+            game.advance_san(san).unwrap();
+            fens.push(game.to_fen());
+        }
+        let castling = game.board().castling();
+        assert!(!castling.allowed(&Color::White, &Direction::Right));
+
+        while let Some(fen) = fens.pop() {
+            assert_eq!(game.to_fen(), fen);
+            if !fens.is_empty() {
+                game.revert().unwrap();
+            }
+        }
+
+        // Castling and the en passant capture are possible again.
+        let castling = game.board().castling();
+        assert!(castling.allowed(&Color::White, &Direction::Right));
+        assert!(castling.allowed(&Color::Black, &Direction::Left));
+        game.advance_san("c5").unwrap();
+        assert_eq!(game.board().enpassant(), Some("c6".parse().unwrap()));
+        assert!(game.revert().is_ok());
+        assert!(game.revert().is_err());
+    }
+
+    #[test]
+    fn takeback_restores_clocks() {
+        let mut game = Game::new();
+        game.set_clock(TimeControl::Blitz, Bonus::Increment(Duration::from_secs(2)));
+        let base = Duration::from_secs(300);
+
+        game.advance_san("e4").unwrap();
+        let times = game.clock().unwrap().times();
+        game.advance_san("e5").unwrap();
+
+        // The time of each move, and its increment, goes back with it.
+        game.revert().unwrap();
+        let clock = game.clock().unwrap();
+        assert_eq!(clock.running(), Some(Color::Black));
+        assert_eq!(clock.times(), times);
+
+        game.revert().unwrap();
+        let clock = game.clock().unwrap();
+        assert_eq!(clock.running(), Some(Color::White));
+        assert_eq!(clock.times(), (base, base));
+    }
+}
@@ -0,0 +1,129 @@
+use crate::error::CatchAllError;
+use crate::game::Game;
+use crate::lan::Lan;
+use crate::piece::Color;
+
+// Points for a guess matching the move played in the game, and for one matching the engine's
+// choice. A guess matching both earns both.
+pub const GAME_POINTS: usize = 2;
+pub const ENGINE_POINTS: usize = 1;
+
+// How a guess compares to the move played in the game, given in SAN, and to the engine's choice.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Verdict {
+    pub played: String,
+    pub game: bool,
+    pub engine: bool,
+}
+
+impl Verdict {
+    pub fn points(&self) -> usize {
+        GAME_POINTS * self.game as usize + ENGINE_POINTS * self.engine as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Score {
+    pub points: usize,
+    pub guesses: usize,
+    // Guesses matching the move played in the game.
+    pub matches: usize,
+}
+
+// Replays a recorded game with the moves of one side hidden, for the user to guess one by one.
+// The game goes on with the move actually played after every guess.
+pub struct GuessTheMove {
+    // The position the recorded game started from and its moves, in SAN and as coordinates.
+    start: Game,
+    moves: Vec<(String, Lan)>,
+    color: Color,
+    // Number of moves of the recorded game played on the board so far.
+    progress: usize,
+    verdict: Option<Verdict>,
+    score: Score,
+}
+
+impl GuessTheMove {
+    pub fn new(game: &Game, color: Color) -> Result<Self, CatchAllError> {
+        let moves = game
+            .plies()?
+            .into_iter()
+            .map(|ply| (ply.san, ply.lan))
+            .collect();
+
+        let mut start = game.clone();
+        while !start.moves().is_empty() {
+            start.revert()?;
+        }
+
+        Ok(Self {
+            start,
+            moves,
+            color,
+            progress: 0,
+            verdict: None,
+            score: Score::default(),
+        })
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    pub fn verdict(&self) -> Option<&Verdict> {
+        self.verdict.as_ref()
+    }
+
+    pub fn score(&self) -> Score {
+        self.score
+    }
+
+    // Sets up the game up to the guessing side's first move.
+    pub fn start(&mut self) -> Result<Game, CatchAllError> {
+        self.progress = 0;
+        self.verdict = None;
+        self.score = Score::default();
+
+        let mut game = self.start.clone();
+        self.skip(&mut game)?;
+        Ok(game)
+    }
+
+    // Whether the guessing side has no moves left in the recorded game.
+    pub fn finished(&self) -> bool {
+        self.progress >= self.moves.len()
+    }
+
+    // Judges a guess in the position after the given game's moves, then plays the move actually
+    // made there and the other side's moves up to the next guess.
+    #[rustfmt::skip]
+    pub fn guess(&mut self, game: &mut Game, guess: Lan, engine: Option<Lan>) -> Result<&Verdict, CatchAllError> {
+        let (san, lan) = self.moves[self.progress].clone();
+        let verdict = Verdict {
+            played: san,
+            game: guess == lan,
+            engine: Some(guess) == engine,
+        };
+
+        self.score.points += verdict.points();
+        self.score.guesses += 1;
+        self.score.matches += verdict.game as usize;
+
+        game.play_move(&lan.from, &lan.to, lan.promotion)?;
+        self.progress += 1;
+        self.skip(game)?;
+
+        Ok(self.verdict.insert(verdict))
+    }
+
+    // Plays the recorded moves of the other side, as far as the guessing side's next move.
+    fn skip(&mut self, game: &mut Game) -> Result<(), CatchAllError> {
+        while game.color() != self.color && !self.finished() {
+            let (_, lan) = self.moves[self.progress];
+            game.play_move(&lan.from, &lan.to, lan.promotion)?;
+            self.progress += 1;
+        }
+
+        Ok(())
+    }
+}
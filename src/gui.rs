@@ -1,12 +1,58 @@
-use crate::error::CatchAllError;
+use crate::accuracy::{self, Judgement};
+use crate::ai::{self, Difficulty};
+use crate::annotation;
+use crate::board::Board;
+use crate::book::Book;
+use crate::clock::{self, Bonus, TimeControl};
+use crate::database::{self, Database};
+use crate::eco::{self, Opening};
+use crate::engine::{Analysis, Engine, Info, Score};
+use crate::error::{CatchAllError, IllegalMoveReason, PuzzleError};
+use crate::explorer::Explorer;
 use crate::game::Game;
 use crate::game::Turn;
-use crate::piece::Color;
+use crate::guess::GuessTheMove;
+use crate::heatmap::Heatmap;
+use crate::i18n::{Language, Translate};
+use crate::lan::Lan;
+#[cfg(feature = "lichess")]
+use crate::lichess;
+use crate::material::{self, Captures};
+use crate::outcome::{GameStatus, Outcome};
+use crate::pgn::Pgn;
+use crate::piece::{Color, Piece};
+use crate::png;
 use crate::position::Position;
+use crate::puzzle::{self, Training, Verdict};
+use crate::r#move::Direction;
+use crate::san::San;
+use crate::settings::Settings;
+use crate::sprite::{self, PieceSet, Segment, Shape, Sprites};
+use crate::tactics;
+use crate::timing::Instant;
 
-use iced::alignment::{Horizontal, Vertical};
-use iced::widget::{button, Button, Column, Container, Row, Text};
-use iced::{theme, Alignment, Element, Length, Renderer, Sandbox, Theme};
+#[cfg(not(target_arch = "wasm32"))]
+use iced::futures::channel::oneshot;
+use iced::widget::canvas::{self, event, Canvas, Frame, Geometry, LineCap, LineJoin, Path, Stroke};
+use iced::widget::{
+    button, checkbox, container, horizontal_space, pick_list, scrollable, slider, text_input,
+    Column, Container, Row, Text,
+};
+use iced::{
+    alignment, clipboard, executor, keyboard, mouse, subscription, theme, time, window, Alignment,
+    Application, Command, Element, Event, Length, Point, Rectangle, Renderer, Size, Subscription,
+    Theme,
+};
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
+use std::thread;
+use std::time::Duration;
 
 macro_rules! rgb {
     ($r:expr, $g:expr, $b:expr) => {
@@ -14,33 +60,101 @@ macro_rules! rgb {
     };
 }
 
-const LIGHT_SQUARE: iced::Color = rgb!(240, 217, 181);
-const DARK_SQUARE: iced::Color = rgb!(181, 136, 99);
 const HIGHLIGHTED_SQUARE: iced::Color = rgb!(255, 0, 0);
+const STANDARD: Highlights = Highlights {
+    selected: HIGHLIGHTED_SQUARE,
+    last_move: rgb!(205, 210, 106),
+    check: rgb!(235, 120, 60),
+    hint: rgb!(106, 159, 181),
+    destination: rgb!(130, 151, 105),
+    dot: rgb!(60, 80, 50),
+};
+// Taken from the Okabe-Ito palette, whose colors also differ in lightness and so stay apart under
+// red-green and blue-yellow color blindness.
+const COLOR_BLIND: Highlights = Highlights {
+    selected: rgb!(240, 228, 66),
+    last_move: rgb!(86, 180, 233),
+    check: rgb!(213, 94, 0),
+    hint: rgb!(204, 121, 167),
+    destination: rgb!(0, 158, 115),
+    dot: rgb!(0, 114, 178),
+};
+// Tints of squares controlled by either side, and of those both attack equally often.
+const WHITE_CONTROL: iced::Color = rgb!(70, 140, 255);
+const BLACK_CONTROL: iced::Color = rgb!(255, 80, 60);
+const CONTESTED: iced::Color = rgb!(255, 210, 60);
+// How far the busiest square of the activity heatmap is tinted towards the side's color.
+const HEAT: f32 = 0.7;
+const BAR_WHITE: iced::Color = rgb!(240, 240, 240);
+const BAR_BLACK: iced::Color = rgb!(64, 64, 64);
+const CLOCK_STOPPED: iced::Color = rgb!(140, 140, 140);
+
+// Radius of the dots on destination squares, relative to the edge length of a square.
+const DOT_RADIUS: f32 = 0.12;
+// Coordinates are written into the corners of the edge squares, this far from their sides,
+// likewise relative to a square.
+const LABEL_SIZE: f32 = 0.16;
+const LABEL_MARGIN: f32 = 0.04;
+// Paint of the arrows and circles drawn with the right mouse button, the width of their lines and
+// the length of the arrow heads, relative to a square.
+const MARK: iced::Color = iced::Color::from_rgba(0.08, 0.47, 0.11, 0.8);
+const MARK_WIDTH: f32 = 0.15;
+const ARROW_HEAD: f32 = 0.45;
+// Arrows of the external engine's analysis: its best line for this many plies, fading with every
+// ply, and the first moves of the lines it ranks lower, fading with every rank.
+const BEST_LINE: iced::Color = iced::Color::from_rgba(0.12, 0.39, 0.86, 0.8);
+const OTHER_LINE: iced::Color = iced::Color::from_rgba(0.55, 0.35, 0.75, 0.6);
+const LINE_ARROWS: usize = 3;
+const ARROW_FADE: f32 = 0.6;
+// Frame around the square the keyboard cursor is on, and its width relative to a square.
+const CURSOR: iced::Color = rgb!(30, 100, 220);
+const CURSOR_WIDTH: f32 = 0.08;
+// Size of the captured pieces shown next to the board, which overlap a little.
+const TRAY_SIZE: f32 = 30.0;
+const TRAY_STEP: f32 = 22.0;
+// Height of the evaluation graph below the moves, the paint of its line and of the markers on
+// moves judged to be blunders, whose radius is in pixels.
+const GRAPH_HEIGHT: f32 = 120.0;
+const GRAPH_LINE: iced::Color = rgb!(106, 159, 181);
+const GRAPH_CURRENT: iced::Color = rgb!(205, 210, 106);
+const BLUNDER: iced::Color = rgb!(220, 40, 40);
+const BLUNDER_RADIUS: f32 = 4.0;
+
+// Time the engine's piece takes to glide to its destination.
+const ANIMATION: Duration = Duration::from_millis(300);
+const FRAME: Duration = Duration::from_millis(16);
+// Choices for the number of full moves the engine may take from its opening book.
+const BOOK_MOVES: [usize; 5] = [0, 5, 10, 15, 20];
+// The external engine searches each position until it is left or this depth is reached.
+const ANALYSIS_DEPTH: usize = 30;
+// Number of best lines the external engine is asked for.
+const ANALYSIS_LINES: usize = 3;
+// Games reaching the current position listed by the explorer.
+const REFERENCE_GAMES: usize = 10;
+// The engine accepts a draw unless it thinks itself ahead by more than this.
+const DRAW_ACCEPTANCE: i32 = 25;
+// Time between two looks at the external engine's progress.
+const POLL: Duration = Duration::from_millis(250);
+// Half-moves without a capture or pawn move after which a draw can be claimed.
+const FIFTY_MOVES: u32 = 100;
+// Choices of seconds between two moves during autoplay, and the default.
+const AUTOPLAY_DELAYS: std::ops::RangeInclusive<f32> = 0.5..=3.0;
+const AUTOPLAY_STEP: f32 = 0.25;
+const AUTOPLAY_DELAY: f32 = 1.0;
+// Interval the running clock is redrawn at, fine enough to show tenths of a second.
+const CLOCK_TICK: Duration = Duration::from_millis(100);
+// Time messages stay in the banner above the board, how often they are checked on, and how many
+// are shown at most.
+const NOTICE_DURATION: Duration = Duration::from_secs(5);
+const NOTICE_TICK: Duration = Duration::from_millis(500);
+const NOTICE_LIMIT: usize = 3;
+const NOTICE_BACKGROUND: iced::Color = rgb!(250, 225, 200);
 
 pub trait Decorate {
     type Output;
     fn decorate(self) -> Self::Output;
 }
 
-impl<'a> Decorate for Text<'a, Renderer> {
-    type Output = Text<'a, Renderer>;
-    fn decorate(self) -> Self::Output {
-        self.horizontal_alignment(Horizontal::Center)
-            .vertical_alignment(Vertical::Center)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .size(75)
-    }
-}
-
-impl<'a> Decorate for Button<'a, Message, Renderer> {
-    type Output = Button<'a, Message, Renderer>;
-    fn decorate(self) -> Self::Output {
-        self.height(100).width(100)
-    }
-}
-
 impl<'a> Decorate for Container<'a, Message, Renderer> {
     type Output = Container<'a, Message, Renderer>;
     fn decorate(self) -> Self::Output {
@@ -62,119 +176,4068 @@ impl<'a> Decorate for Column<'a, Message, Renderer> {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Message {
     Move(Position),
+    // Asks before a game in progress is abandoned for a new one.
+    AbandonGame,
+    NewGame,
+    OpponentSelected(Opponent),
+    DifficultySelected(Difficulty),
+    TimeControlSelected(TimeControl),
+    BoardThemeSelected(BoardTheme),
+    PaletteSelected(Palette),
+    AppThemeSelected(AppTheme),
+    LanguageSelected(Language),
+    PieceSetSelected(PieceSet),
+    // Asks for a directory of piece drawings.
+    LoadPieceSet,
+    BonusSelected(Bonus),
+    BookMovesSelected(usize),
+    StartGame,
+    CancelNewGame,
+    // Starts another game with the same settings and the colors reversed.
+    Rematch,
+    ClockTick,
+    NoticeTick,
+    DismissNotice(usize),
+    Resign,
+    OfferDraw,
+    AcceptDraw,
+    DeclineDraw,
+    Undo,
+    // Closes the popup at the end of the game to look back at its moves.
+    ReviewGame,
+    // Carries the hash of the position the engine was asked about.
+    EngineMoved(u64, Result<Option<Lan>, String>),
+    Tick(Instant),
+    Hint,
+    // Carries the hash of the position the hint was asked for.
+    HintReady(u64, Result<Option<Lan>, String>),
+    // Carries the hash of the evaluated position and the score for white.
+    Evaluated(u64, Result<Score, String>),
+    // Carries the number of puzzles found in the finished game.
+    Harvested(Result<usize, String>),
+    LoadPuzzles,
+    NextPuzzle,
+    RetryPuzzle,
+    StopTraining,
+    GuessMoves,
+    // Carries the hash of the position guessed in, the guess and the engine's choice there.
+    Guessed(u64, Lan, Result<Option<Lan>, String>),
+    ToggleReview,
+    // Carries the index and notation of the judged move.
+    Reviewed(usize, String, Result<Option<Judgement>, String>),
+    TakeBack,
+    ToggleAnalysis,
+    AnalysisFinished(Result<(), String>),
+    AnalysisTick,
+    TogglePanel,
+    ToggleOverlay,
+    // Shows or hides how active both sides were on each square over the finished game.
+    ToggleHeatmap,
+    Flip,
+    ToggleAutoFlip,
+    // Sets up the position shown in the board editor.
+    EditBoard,
+    BrushSelected(Brush),
+    SwapSideToMove,
+    CastlingToggled(Color, Direction, bool),
+    TargetSelected(Target),
+    ClearBoard,
+    ResetBoard,
+    // Leaves the editor to play the position against the chosen opponent, or to analyse it.
+    PlayPosition,
+    AnalysePosition,
+    CloseEditor,
+    // Draws the mark on the position shown, or erases it if it is there already.
+    Mark(Mark),
+    // Moves the keyboard cursor by files and ranks as seen on screen, rightwards and upwards.
+    MoveCursor(isize, isize),
+    ConfirmCursor,
+    ClearSelection,
+    MoveTyped(String),
+    SubmitMove,
+    ToggleFullscreen,
+    WindowResized(u32, u32),
+    WindowMoved(i32, i32),
+    // Remembers where the window was before it closes.
+    CloseRequested,
+    ToggleExplorer,
+    ImportDatabase,
+    // Index of the games in the given PGN file.
+    ExplorerBuilt(String, Result<Arc<Explorer>, String>),
+    // Replays the game with the given index in the explorer's file, from the given move on.
+    LoadReference(usize, usize),
+    // Shows the given line of the analysis on the board, or the game again if None.
+    Preview(Option<usize>),
+    // Shows the position after the given number of moves, or the current one.
+    Jump(Option<usize>),
+    StepBack,
+    StepForward,
+    // Steps through the moves of the game on a timer, animating each of them.
+    ToggleAutoplay,
+    AutoplayDelayChanged(f32),
+    AutoplayTick,
+    ClaimDraw,
+    AnalyseGame,
+    // Annotated PGN of the finished game.
+    GameAnalysed(Result<String, String>),
+    ExportImage,
+    CopyFen,
+    PasteFen,
+    FenPasted(Option<String>),
+    #[cfg(feature = "lichess")]
+    LichessUrl(String),
+    #[cfg(feature = "lichess")]
+    ImportLichess,
+    #[cfg(feature = "lichess")]
+    LichessImported(Result<String, String>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opponent {
+    Human,
+    Computer(Color),
+    // The engine plays both sides while the user watches.
+    Spectate,
+}
+
+impl Opponent {
+    const ALL: [Opponent; 4] = [
+        Opponent::Human,
+        Opponent::Computer(Color::Black),
+        Opponent::Computer(Color::White),
+        Opponent::Spectate,
+    ];
+
+    fn engine_plays(&self, color: &Color) -> bool {
+        match self {
+            Opponent::Human => false,
+            Opponent::Computer(engine) => engine == color,
+            Opponent::Spectate => true,
+        }
+    }
+}
+
+impl fmt::Display for Opponent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Opponent::Human => write!(f, "Human"),
+            Opponent::Computer(Color::White) => write!(f, "Computer plays white"),
+            Opponent::Computer(Color::Black) => write!(f, "Computer plays black"),
+            Opponent::Spectate => write!(f, "Computer vs computer"),
+        }
+    }
+}
+
+// Look of everything around the board, following the operating system unless chosen otherwise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AppTheme {
+    System,
+    Light,
+    Dark,
+}
+
+impl AppTheme {
+    const ALL: [AppTheme; 3] = [AppTheme::System, AppTheme::Light, AppTheme::Dark];
+
+    fn from_name(name: &str) -> Option<Self> {
+        AppTheme::ALL
+            .into_iter()
+            .find(|theme| theme.to_string().eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for AppTheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AppTheme::System => write!(f, "System theme"),
+            AppTheme::Light => write!(f, "Light theme"),
+            AppTheme::Dark => write!(f, "Dark theme"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardTheme {
+    Brown,
+    Blue,
+    Green,
+    HighContrast,
+}
+
+impl BoardTheme {
+    const ALL: [BoardTheme; 4] = [
+        BoardTheme::Brown,
+        BoardTheme::Blue,
+        BoardTheme::Green,
+        BoardTheme::HighContrast,
+    ];
+
+    // Colors of the light and the dark squares.
+    fn squares(&self) -> (iced::Color, iced::Color) {
+        match self {
+            BoardTheme::Brown => (rgb!(240, 217, 181), rgb!(181, 136, 99)),
+            BoardTheme::Blue => (rgb!(222, 227, 230), rgb!(140, 162, 173)),
+            BoardTheme::Green => (rgb!(238, 238, 210), rgb!(118, 150, 86)),
+            BoardTheme::HighContrast => (rgb!(255, 255, 255), rgb!(90, 90, 90)),
+        }
+    }
+}
+
+impl fmt::Display for BoardTheme {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BoardTheme::Brown => write!(f, "Brown"),
+            BoardTheme::Blue => write!(f, "Blue"),
+            BoardTheme::Green => write!(f, "Green"),
+            BoardTheme::HighContrast => write!(f, "High contrast"),
+        }
+    }
+}
+
+// Colors of the squares set apart from the others, and of the dots on empty destinations.
+struct Highlights {
+    selected: iced::Color,
+    last_move: iced::Color,
+    check: iced::Color,
+    hint: iced::Color,
+    destination: iced::Color,
+    dot: iced::Color,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Palette {
+    Standard,
+    ColorBlind,
+}
+
+impl Palette {
+    const ALL: [Palette; 2] = [Palette::Standard, Palette::ColorBlind];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Palette::ALL
+            .into_iter()
+            .find(|palette| palette.to_string().eq_ignore_ascii_case(name))
+    }
+
+    fn highlights(&self) -> &'static Highlights {
+        match self {
+            Palette::Standard => &STANDARD,
+            Palette::ColorBlind => &COLOR_BLIND,
+        }
+    }
+}
+
+impl fmt::Display for Palette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Palette::Standard => write!(f, "Standard highlights"),
+            Palette::ColorBlind => write!(f, "Color-blind highlights"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NewGame {
+    opponent: Opponent,
+    difficulty: Difficulty,
+    time_control: TimeControl,
+    bonus: Bonus,
+    book_moves: usize,
+}
+
+// Piece put on the squares clicked in the board editor, by its FEN letter, or none to clear them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Brush {
+    Piece(char),
+    Eraser,
+}
+
+impl Brush {
+    const ALL: [Brush; 13] = [
+        Brush::Piece('K'),
+        Brush::Piece('Q'),
+        Brush::Piece('R'),
+        Brush::Piece('B'),
+        Brush::Piece('N'),
+        Brush::Piece('P'),
+        Brush::Piece('k'),
+        Brush::Piece('q'),
+        Brush::Piece('r'),
+        Brush::Piece('b'),
+        Brush::Piece('n'),
+        Brush::Piece('p'),
+        Brush::Eraser,
+    ];
+
+    fn piece(&self) -> Option<Piece> {
+        match self {
+            Brush::Piece(symbol) => Piece::from_symbol(*symbol),
+            Brush::Eraser => None,
+        }
+    }
+}
+
+impl fmt::Display for Brush {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let piece = match self.piece() {
+            Some(piece) => piece,
+            None => return write!(f, "Eraser"),
+        };
+        let name = match piece {
+            Piece::Pawn(_, _) => "pawn",
+            Piece::Knight(_) => "knight",
+            Piece::Bishop(_) => "bishop",
+            Piece::Rook(_) => "rook",
+            Piece::Queen(_) => "queen",
+            Piece::King(_) => "king",
+        };
+
+        write!(f, "{:?} {}", piece.color(), name)
+    }
+}
+
+fn piece_name(piece: &Piece) -> &'static str {
+    match (piece, piece.color()) {
+        (Piece::Pawn(_, _), Color::White) => "white pawn",
+        (Piece::Knight(_), Color::White) => "white knight",
+        (Piece::Bishop(_), Color::White) => "white bishop",
+        (Piece::Rook(_), Color::White) => "white rook",
+        (Piece::Queen(_), Color::White) => "white queen",
+        (Piece::King(_), Color::White) => "white king",
+        (Piece::Pawn(_, _), Color::Black) => "black pawn",
+        (Piece::Knight(_), Color::Black) => "black knight",
+        (Piece::Bishop(_), Color::Black) => "black bishop",
+        (Piece::Rook(_), Color::Black) => "black rook",
+        (Piece::Queen(_), Color::Black) => "black queen",
+        (Piece::King(_), Color::Black) => "black king",
+    }
+}
+
+// En passant target offered in the board editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Target(Option<Position>);
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(pos) => write!(f, "{}", pos),
+            None => write!(f, "No en passant"),
+        }
+    }
+}
+
+// Position being set up square by square before it is played or analysed.
+#[derive(Clone)]
+struct Editor {
+    board: Board,
+    color: Color,
+    brush: Brush,
+}
+
+impl Editor {
+    // Clicking a square holding the piece of the brush already clears it.
+    fn paint(&mut self, pos: &Position) {
+        let held = self.board.at(pos).ok().map(Piece::symbol);
+
+        match self.brush.piece() {
+            Some(piece) if held != Some(piece.symbol()) => {
+                self.board.place(pos, piece);
+            }
+            _ => {
+                self.board.remove(pos);
+            }
+        }
+    }
+
+    // Squares a pawn of the other side may have just skipped, on the rank in front of its own.
+    fn targets(&self) -> Vec<Target> {
+        let rank = match self.color {
+            Color::White => 5,
+            Color::Black => 2,
+        };
+
+        std::iter::once(Target(None))
+            .chain((0..8).map(|file| Target(Some(Position::new(file, rank)))))
+            .collect()
+    }
+}
+
+struct Animation {
+    lan: Lan,
+    piece: Piece,
+    start: Instant,
+}
+
+impl Animation {
+    // Share of the way between the squares the piece has covered at the given moment.
+    fn progress(&self, now: Instant) -> f32 {
+        let progress = (now - self.start).as_secs_f32() / ANIMATION.as_secs_f32();
+        progress.min(1.0)
+    }
+
+    fn finished(&self, now: Instant) -> bool {
+        now - self.start >= ANIMATION
+    }
+}
+
+// Piece on its way from one square to another, drawn that share of the way between their centers.
+struct Moving {
+    piece: Piece,
+    from: Position,
+    to: Position,
+    progress: f32,
 }
 
 struct Square {
     position: Position,
+    piece: Option<Piece>,
     turn: Turn,
+    // Whether the square is part of the suggested move.
+    hinted: bool,
+    // Whether the selected piece may move to the square.
+    destination: bool,
+    // Whether the last move left or entered the square, and whether a king in check stands on it.
+    last_move: bool,
+    check: bool,
+    // Number of white and black attackers, when the overlay is shown.
+    attacks: Option<(usize, usize)>,
+    // Activity of white and black over the game, when the heatmap is shown.
+    heat: Option<(f32, f32)>,
 }
 
 impl Square {
-    fn new(position: Position, turn: Turn) -> Self {
-        Self { position, turn }
+    #[rustfmt::skip]
+    fn new(position: Position, piece: Option<Piece>, turn: Turn, hinted: bool, destination: bool, last_move: bool, check: bool, attacks: Option<(usize, usize)>) -> Self {
+        Self {
+            position,
+            piece,
+            turn,
+            hinted,
+            destination,
+            last_move,
+            check,
+            attacks,
+            heat: None,
+        }
+    }
+
+    // Tints the square towards the side attacking it more often, the stronger the larger the lead.
+    // The heatmap instead tints it towards both sides, each by how active it was there.
+    fn overlay(&self, color: iced::Color) -> iced::Color {
+        if let Some((white, black)) = self.heat {
+            let color = mix(color, WHITE_CONTROL, HEAT * white);
+            return mix(color, BLACK_CONTROL, HEAT * black);
+        }
+
+        let (tint, strength) = match self.attacks {
+            Some((white, black)) if white > black => (WHITE_CONTROL, (white - black).min(3)),
+            Some((white, black)) if black > white => (BLACK_CONTROL, (black - white).min(3)),
+            Some((white, _)) if white > 0 => (CONTESTED, 1),
+            _ => return color,
+        };
+
+        mix(color, tint, 0.2 * strength as f32)
+    }
+
+    fn color(&self, theme: BoardTheme, palette: Palette) -> iced::Color {
+        let (light, dark) = theme.squares();
+        let highlights = palette.highlights();
+
+        match self.turn {
+            Turn::Select(_, pos) if self.position.eq(&pos) => highlights.selected,
+            _ if self.check => highlights.check,
+            _ if self.destination => highlights.destination,
+            _ if self.hinted => highlights.hint,
+            _ if self.last_move => highlights.last_move,
+            _ => self.overlay(match Color::from(self.position) {
+                Color::White => light,
+                Color::Black => dark,
+            }),
+        }
     }
 }
 
-impl button::StyleSheet for Square {
-    type Style = Theme;
+// Color the given share of the way from one color to the other.
+fn mix(color: iced::Color, tint: iced::Color, share: f32) -> iced::Color {
+    iced::Color::from_rgb(
+        color.r + (tint.r - color.r) * share,
+        color.g + (tint.g - color.g) * share,
+        color.b + (tint.b - color.b) * share,
+    )
+}
 
-    fn active(&self, _: &Self::Style) -> button::Appearance {
-        let color = match self.turn {
-            Turn::Select(_, pos) if self.position.eq(&pos) => HIGHLIGHTED_SQUARE,
-            _ => match Color::from(self.position) {
-                Color::White => LIGHT_SQUARE,
-                Color::Black => DARK_SQUARE,
-            },
+// A piece being dragged across the board, drawn under the cursor instead of on its square.
+struct Drag {
+    from: Position,
+    cursor: Point,
+}
+
+#[derive(Default)]
+struct Pointer {
+    drag: Option<Drag>,
+    // Square a right-click drag started on, which becomes an arrow or a circle once released.
+    marking: Option<Position>,
+}
+
+// Marks drawn on the board with the right mouse button, as during analysis over the board.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    Circle(Position),
+    Arrow(Position, Position),
+}
+
+// The board drawn on a canvas, so that pieces can follow the cursor when dragged.
+// Pressing on a square counts as a click on it, and releasing on another one as a second click.
+struct BoardCanvas {
+    squares: Vec<Square>,
+    // Side whose pieces may be picked up, if any.
+    mover: Option<Color>,
+    // Side shown at the bottom of the board.
+    bottom: Color,
+    theme: BoardTheme,
+    palette: Palette,
+    sprites: Arc<Sprites>,
+    marks: Vec<Mark>,
+    // Moves of the external engine's analysis, drawn beneath the marks.
+    arrows: Vec<(Position, Position, iced::Color)>,
+    cursor: Option<Position>,
+    moving: Option<Moving>,
+}
+
+impl BoardCanvas {
+    fn square(&self, pos: &Position) -> Option<&Square> {
+        self.squares.iter().find(|square| square.position == *pos)
+    }
+
+    // Column and row of the square counted from the top left corner of the board.
+    fn cell(&self, pos: &Position) -> (usize, usize) {
+        match self.bottom {
+            Color::White => (pos.file, 7 - pos.rank),
+            Color::Black => (7 - pos.file, pos.rank),
+        }
+    }
+
+    // Edge length of the squares of the largest board fitting into the given space.
+    fn square_size(bounds: Size) -> f32 {
+        bounds.width.min(bounds.height) / 8.0
+    }
+
+    fn corner(&self, pos: &Position, size: f32) -> Point {
+        let (column, row) = self.cell(pos);
+        Point::new(column as f32 * size, row as f32 * size)
+    }
+
+    fn center(&self, pos: &Position, size: f32) -> Point {
+        let corner = self.corner(pos, size);
+        Point::new(corner.x + size / 2.0, corner.y + size / 2.0)
+    }
+
+    // Writes the file into the squares along the bottom edge and the rank into those along the
+    // left one, in the color of the squares of the other kind.
+    fn label(&self, frame: &mut Frame, pos: &Position, size: f32) {
+        let (column, row) = self.cell(pos);
+        let corner = self.corner(pos, size);
+        let margin = LABEL_MARGIN * size;
+        let (light, dark) = self.theme.squares();
+        let color = match Color::from(*pos) {
+            Color::White => dark,
+            Color::Black => light,
         };
 
-        button::Appearance {
-            background: Some(iced::Background::Color(color)),
-            ..Default::default()
+        if row == 7 {
+            frame.fill_text(canvas::Text {
+                content: ((b'a' + pos.file as u8) as char).to_string(),
+                position: Point::new(corner.x + size - margin, corner.y + size - margin),
+                color,
+                size: LABEL_SIZE * size,
+                horizontal_alignment: alignment::Horizontal::Right,
+                vertical_alignment: alignment::Vertical::Bottom,
+                ..canvas::Text::default()
+            });
+        }
+        if column == 0 {
+            frame.fill_text(canvas::Text {
+                content: (pos.rank + 1).to_string(),
+                position: Point::new(corner.x + margin, corner.y + margin),
+                color,
+                size: LABEL_SIZE * size,
+                horizontal_alignment: alignment::Horizontal::Left,
+                vertical_alignment: alignment::Vertical::Top,
+                ..canvas::Text::default()
+            });
         }
     }
 
-    fn pressed(&self, _: &Self::Style) -> button::Appearance {
-        button::Appearance {
-            background: Some(iced::Background::Color(HIGHLIGHTED_SQUARE)),
-            ..Default::default()
+    fn mark(&self, frame: &mut Frame, mark: &Mark, size: f32) {
+        let (from, to) = match *mark {
+            Mark::Circle(pos) => {
+                let ring = Path::circle(self.center(&pos, size), size * (0.5 - MARK_WIDTH / 2.0));
+                frame.stroke(
+                    &ring,
+                    Stroke::default()
+                        .with_color(MARK)
+                        .with_width(MARK_WIDTH * size),
+                );
+                return;
+            }
+            Mark::Arrow(from, to) => (from, to),
+        };
+        self.arrow(frame, &from, &to, MARK, size);
+    }
+
+    #[rustfmt::skip]
+    fn arrow(&self, frame: &mut Frame, from: &Position, to: &Position, color: iced::Color, size: f32) {
+        let (from, to) = (self.center(from, size), self.center(to, size));
+
+        // The shaft stops where the head begins, so that the two do not overlap in translucent paint.
+        let length = ((to.x - from.x).powi(2) + (to.y - from.y).powi(2)).sqrt();
+        let (dx, dy) = ((to.x - from.x) / length, (to.y - from.y) / length);
+        let head = ARROW_HEAD * size;
+        let base = Point::new(to.x - dx * head, to.y - dy * head);
+        let side = |sign: f32| {
+            Point::new(
+                base.x - sign * dy * head / 2.0,
+                base.y + sign * dx * head / 2.0,
+            )
+        };
+
+        let shaft = Path::line(from, base);
+        frame.stroke(
+            &shaft,
+            Stroke::default()
+                .with_color(color)
+                .with_width(MARK_WIDTH * size),
+        );
+        let tip = Path::new(|builder| {
+            builder.move_to(to);
+            builder.line_to(side(1.0));
+            builder.line_to(side(-1.0));
+            builder.close();
+        });
+        frame.fill(&tip, color);
+    }
+
+    // Square under the given point, if the point lies on the board rather than the space beside it.
+    fn square_at(&self, point: Point, size: f32) -> Option<Position> {
+        let (column, row) = ((point.x / size) as usize, (point.y / size) as usize);
+        if column > 7 || row > 7 {
+            return None;
         }
+
+        Some(match self.bottom {
+            Color::White => Position::new(column, 7 - row),
+            Color::Black => Position::new(7 - column, row),
+        })
     }
 }
 
-pub struct Gui {
-    game: Game,
+// Draws the piece's sprite scaled to fill a square of the given size around the given center.
+fn draw_piece(frame: &mut Frame, sprites: &Sprites, piece: &Piece, center: Point, size: f32) {
+    let point = |(x, y): sprite::Point| Point::new(center.x + x, center.y + y);
+    let color = |(r, g, b): sprite::Rgb| rgb!(r, g, b);
+
+    for layer in sprites.sprite(piece, size).iter() {
+        let path = match &layer.shape {
+            Shape::Circle(center, radius) => Path::circle(point(*center), *radius),
+            Shape::Path(segments) => Path::new(|builder| {
+                for segment in segments {
+                    match *segment {
+                        Segment::MoveTo(to) => builder.move_to(point(to)),
+                        Segment::LineTo(to) => builder.line_to(point(to)),
+                        Segment::CubicTo(first, second, to) => {
+                            builder.bezier_curve_to(point(first), point(second), point(to))
+                        }
+                        Segment::Close => builder.close(),
+                    }
+                }
+            }),
+        };
+
+        if let Some(fill) = layer.fill {
+            frame.fill(&path, color(fill));
+        }
+        if let Some((stroke, width)) = layer.stroke {
+            frame.stroke(
+                &path,
+                Stroke::default()
+                    .with_color(color(stroke))
+                    .with_width(width)
+                    .with_line_cap(LineCap::Round)
+                    .with_line_join(LineJoin::Round),
+            );
+        }
+    }
 }
 
-impl Gui {
-    fn square_view<'a>(&self, pos: Position) -> Button<'a, Message, Renderer> {
-        let turn = self.game.turn();
-        let theme = theme::Button::custom(Square::new(pos, turn));
+impl canvas::Program<Message> for BoardCanvas {
+    type State = Pointer;
 
-        let mut text = Text::new("");
+    #[rustfmt::skip]
+    fn update(&self, state: &mut Self::State, event: canvas::Event, bounds: Rectangle, cursor: mouse::Cursor) -> (event::Status, Option<Message>) {
+        let size = BoardCanvas::square_size(bounds.size());
+        let point = cursor.position_in(bounds);
 
-        let res = self.game.at(&pos);
+        match (event, point) {
+            (canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)), Some(point)) => {
+                let pos = match self.square_at(point, size) {
+                    Some(pos) => pos,
+                    None => return (event::Status::Ignored, None),
+                };
+                let movable = self
+                    .square(&pos)
+                    .and_then(|square| square.piece.as_ref())
+                    .is_some_and(|piece| Some(piece.color()) == self.mover);
 
-        if let Ok(piece) = res {
-            let color = match piece.color() {
-                Color::White => iced::Color::WHITE,
-                Color::Black => iced::Color::BLACK,
-            };
-            text = Text::new(piece.to_string()).style(color).decorate();
+                if movable {
+                    state.drag = Some(Drag { from: pos, cursor: point });
+                }
+                (event::Status::Captured, Some(Message::Move(pos)))
+            }
+            (canvas::Event::Mouse(mouse::Event::CursorMoved { .. }), Some(point)) => match &mut state.drag {
+                Some(drag) => {
+                    drag.cursor = point;
+                    (event::Status::Captured, None)
+                }
+                None => (event::Status::Ignored, None),
+            },
+            // A piece dropped on its own square or off the board stays where it was.
+            (canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)), point) => {
+                match (state.drag.take(), point.and_then(|point| self.square_at(point, size))) {
+                    (Some(drag), Some(to)) if to != drag.from => (event::Status::Captured, Some(Message::Move(to))),
+                    _ => (event::Status::Ignored, None),
+                }
+            }
+            (canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Right)), Some(point)) => {
+                state.marking = self.square_at(point, size);
+                (event::Status::Captured, None)
+            }
+            // Releasing on the square the drag started from circles it, anywhere else draws an arrow.
+            (canvas::Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Right)), point) => {
+                match (state.marking.take(), point.and_then(|point| self.square_at(point, size))) {
+                    (Some(from), Some(to)) if from == to => (event::Status::Captured, Some(Message::Mark(Mark::Circle(to)))),
+                    (Some(from), Some(to)) => (event::Status::Captured, Some(Message::Mark(Mark::Arrow(from, to)))),
+                    _ => (event::Status::Ignored, None),
+                }
+            }
+            _ => (event::Status::Ignored, None),
         }
+    }
 
-        button(text)
-            .style(theme)
-            .decorate()
-            .on_press(Message::Move(pos))
+    #[rustfmt::skip]
+    fn draw(&self, state: &Self::State, renderer: &Renderer, _: &Theme, bounds: Rectangle, _: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let size = BoardCanvas::square_size(bounds.size());
+        let dragged = state.drag.as_ref().map(|drag| drag.from);
+
+        for square in &self.squares {
+            frame.fill_rectangle(self.corner(&square.position, size), Size::new(size, size), square.color(self.theme, self.palette));
+            self.label(&mut frame, &square.position, size);
+
+            match &square.piece {
+                Some(piece) if dragged != Some(square.position) => draw_piece(&mut frame, &self.sprites, piece, self.center(&square.position, size), size),
+                None if square.destination => frame.fill(&Path::circle(self.center(&square.position, size), DOT_RADIUS * size), self.palette.highlights().dot),
+                _ => (),
+            }
+        }
+
+        if let Some(moving) = &self.moving {
+            let (from, to) = (self.center(&moving.from, size), self.center(&moving.to, size));
+            let center = Point::new(from.x + (to.x - from.x) * moving.progress, from.y + (to.y - from.y) * moving.progress);
+            draw_piece(&mut frame, &self.sprites, &moving.piece, center, size);
+        }
+
+        for (from, to, color) in &self.arrows {
+            self.arrow(&mut frame, from, to, *color, size);
+        }
+
+        for mark in &self.marks {
+            self.mark(&mut frame, mark, size);
+        }
+
+        if let Some(pos) = self.cursor {
+            let inset = CURSOR_WIDTH * size / 2.0;
+            let corner = self.corner(&pos, size);
+            let outline = Path::rectangle(
+                Point::new(corner.x + inset, corner.y + inset),
+                Size::new(size - 2.0 * inset, size - 2.0 * inset),
+            );
+            frame.stroke(&outline, Stroke::default().with_color(CURSOR).with_width(2.0 * inset));
+        }
+
+        let piece = state.drag.as_ref().and_then(|drag| {
+            let piece = self.square(&drag.from)?.piece.as_ref()?;
+            Some((piece, drag.cursor))
+        });
+        if let Some((piece, cursor)) = piece {
+            draw_piece(&mut frame, &self.sprites, piece, cursor, size);
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    #[rustfmt::skip]
+    fn mouse_interaction(&self, state: &Self::State, bounds: Rectangle, cursor: mouse::Cursor) -> mouse::Interaction {
+        let size = BoardCanvas::square_size(bounds.size());
+        let over = cursor.position_in(bounds).and_then(|point| self.square_at(point, size));
+
+        match (&state.drag, over) {
+            (Some(_), _) => mouse::Interaction::Grabbing,
+            (None, Some(_)) => mouse::Interaction::Pointer,
+            (None, None) => mouse::Interaction::default(),
+        }
     }
 }
 
-impl Default for Gui {
-    fn default() -> Self {
-        Self { game: Game::new() }
+// Row of the pieces one side has captured.
+struct Tray {
+    pieces: Vec<Piece>,
+    sprites: Arc<Sprites>,
+}
+
+impl canvas::Program<Message> for Tray {
+    type State = ();
+
+    #[rustfmt::skip]
+    fn draw(&self, _: &Self::State, renderer: &Renderer, _: &Theme, bounds: Rectangle, _: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+
+        for (i, piece) in self.pieces.iter().enumerate() {
+            let center = Point::new(TRAY_SIZE / 2.0 + i as f32 * TRAY_STEP, TRAY_SIZE / 2.0);
+            draw_piece(&mut frame, &self.sprites, piece, center, TRAY_SIZE);
+        }
+
+        vec![frame.into_geometry()]
     }
 }
 
-impl Sandbox for Gui {
-    type Message = Message;
+// White's winning chances over the course of an analysed game, one point per position.
+struct EvalGraph {
+    evals: Vec<i32>,
+    // Moves judged to be blunders, marked on the position they lead to.
+    blunders: Vec<usize>,
+    current: usize,
+}
 
-    fn new() -> Self {
-        Self::default()
+impl EvalGraph {
+    // Horizontal distance between two neighbouring positions.
+    fn step(&self, width: f32) -> f32 {
+        width / self.evals.len().saturating_sub(1).max(1) as f32
     }
 
-    fn title(&self) -> String {
-        "Chess".to_string()
+    fn point(&self, index: usize, bounds: Size) -> Point {
+        let chances = accuracy::win_percent(self.evals[index]) as f32 / 100.0;
+        Point::new(
+            index as f32 * self.step(bounds.width),
+            (1.0 - chances) * bounds.height,
+        )
     }
+}
 
-    fn update(&mut self, msg: Message) {
-        let pos = match msg {
-            Message::Move(pos) => pos,
-        };
+impl canvas::Program<Message> for EvalGraph {
+    type State = ();
 
-        if let Err(e) = self.game.advance(&pos) {
-            println!("{}", e);
-            self.game.reset_turn();
+    #[rustfmt::skip]
+    fn update(&self, _: &mut Self::State, event: canvas::Event, bounds: Rectangle, cursor: mouse::Cursor) -> (event::Status, Option<Message>) {
+        match (event, cursor.position_in(bounds)) {
+            (canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)), Some(point)) => {
+                let index = ((point.x / self.step(bounds.width)).round() as usize).min(self.evals.len() - 1);
+                (event::Status::Captured, Some(Message::Jump(Some(index))))
+            }
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    #[rustfmt::skip]
+    fn draw(&self, _: &Self::State, renderer: &Renderer, _: &Theme, bounds: Rectangle, _: mouse::Cursor) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let size = bounds.size();
+
+        // White's share of the graph lies below the line, black's above it.
+        frame.fill_rectangle(Point::ORIGIN, size, BAR_BLACK);
+        let area = Path::new(|builder| {
+            builder.move_to(Point::new(0.0, size.height));
+            for index in 0..self.evals.len() {
+                builder.line_to(self.point(index, size));
+            }
+            builder.line_to(Point::new(self.point(self.evals.len() - 1, size).x, size.height));
+            builder.close();
+        });
+        frame.fill(&area, BAR_WHITE);
 
-            if let CatchAllError::NoLegalMoves = e {
-                self.game.reset();
+        let middle = Path::line(Point::new(0.0, size.height / 2.0), Point::new(size.width, size.height / 2.0));
+        frame.stroke(&middle, Stroke::default().with_color(CLOCK_STOPPED).with_width(1.0));
+
+        let line = Path::new(|builder| {
+            builder.move_to(self.point(0, size));
+            for index in 1..self.evals.len() {
+                builder.line_to(self.point(index, size));
             }
+        });
+        frame.stroke(&line, Stroke::default().with_color(GRAPH_LINE).with_width(2.0).with_line_join(LineJoin::Round));
+
+        let x = self.point(self.current, size).x;
+        frame.stroke(&Path::line(Point::new(x, 0.0), Point::new(x, size.height)), Stroke::default().with_color(GRAPH_CURRENT).with_width(2.0));
+
+        for index in &self.blunders {
+            frame.fill(&Path::circle(self.point(index + 1, size), BLUNDER_RADIUS), BLUNDER);
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    #[rustfmt::skip]
+    fn mouse_interaction(&self, _: &Self::State, bounds: Rectangle, cursor: mouse::Cursor) -> mouse::Interaction {
+        match cursor.is_over(bounds) {
+            true => mouse::Interaction::Pointer,
+            false => mouse::Interaction::default(),
         }
     }
+}
 
-    fn view(&self) -> Element<Message> {
-        let mut column = Column::new().decorate();
-        for rank in (0..8).rev() {
-            let mut row = Row::new().decorate();
-            for file in 0..8 {
-                let button = self.square_view(Position::new(file, rank));
-                row = row.push(button);
+// Solid block of color, making up one side of the evaluation bar or the back of a notice.
+struct Fill(iced::Color);
+
+impl container::StyleSheet for Fill {
+    type Style = Theme;
+
+    fn appearance(&self, _: &Self::Style) -> container::Appearance {
+        container::Appearance {
+            background: Some(iced::Background::Color(self.0)),
+            ..Default::default()
+        }
+    }
+}
+
+pub struct Gui {
+    game: Game,
+    database: Database,
+    // Whether the current game has been written to the database.
+    recorded: bool,
+    #[cfg(feature = "lichess")]
+    lichess_url: String,
+    opponent: Opponent,
+    difficulty: Difficulty,
+    time_control: TimeControl,
+    bonus: Bonus,
+    // Opening book of the built-in engine and how many of its first moves come from it.
+    book: Option<Arc<Book>>,
+    book_moves: usize,
+    dialog: Option<NewGame>,
+    editor: Option<Editor>,
+    // Whether the moves are stepped through on their own, and the seconds between two of them.
+    autoplay: bool,
+    autoplay_delay: f32,
+    settings: Settings,
+    app_theme: AppTheme,
+    language: Language,
+    // Size and position of the window while not in fullscreen, once it has been resized or moved.
+    window_size: Option<(u32, u32)>,
+    window_position: Option<(i32, i32)>,
+    // Whether the operating system asked for dark colors when the app was started.
+    system_dark: bool,
+    // Move being typed, and why the last one submitted could not be played.
+    entry: String,
+    entry_error: Option<String>,
+    // Messages for the user, with the time each was posted.
+    notices: Vec<(String, Instant)>,
+    // Whether the user is asked to confirm abandoning the game in progress.
+    confirming: bool,
+    // Whether the popup announcing the end of the game has been closed.
+    dismissed: bool,
+    // Hash of the position the engine is currently thinking about.
+    thinking: Option<u64>,
+    animation: Option<Animation>,
+    // Path to an external UCI engine used for analysis, started on first use.
+    engine_path: Option<String>,
+    external: Arc<Mutex<Option<Engine>>>,
+    analysing: bool,
+    // Analysis of the position with the given hash by the named engine, updated as it runs.
+    analysis: Arc<Mutex<Option<(u64, String, Analysis)>>>,
+    // Raised to end the running analysis.
+    stop_analysis: Arc<AtomicBool>,
+    polling: bool,
+    panel_open: bool,
+    // Moves played in the games of a PGN file, indexed in the background when the panel opens.
+    exploring: bool,
+    explorer_path: String,
+    explorer: Option<Arc<Explorer>>,
+    // Number of moves of the game shown on the board when looking back at an earlier position.
+    viewing: Option<usize>,
+    // Line of the analysis shown on the board instead of the game.
+    preview: Option<(u64, Vec<Lan>)>,
+    // Suggested move together with the hash of the position it is meant for.
+    hint: Option<(u64, Lan)>,
+    // Latest evaluation of the built-in engine, kept until the next position has been evaluated.
+    evaluation: Option<(u64, Score)>,
+    // Opening reached by the moves played so far.
+    opening: Option<Opening>,
+    // Puzzle training, taking over the board while it lasts.
+    training: Option<Training>,
+    // Guess-the-move training on a recorded game, likewise taking over the board.
+    guessing: Option<GuessTheMove>,
+    // Collection that puzzles found in finished games are added to, and whether the current
+    // game has been searched for them.
+    puzzles_path: String,
+    harvested: bool,
+    // Whether the board is seen from the other side than usual, and whether it turns each move.
+    flipped: bool,
+    auto_flip: bool,
+    fullscreen: bool,
+    board_theme: BoardTheme,
+    palette: Palette,
+    piece_set: PieceSet,
+    sprites: Arc<Sprites>,
+    // Marks drawn on the positions of the game, keyed by their hash and erased by the next move.
+    marks: HashMap<u64, Vec<Mark>>,
+    // Square picked with the arrow keys, once they have been used.
+    cursor: Option<Position>,
+    // Whether squares are tinted by which side attacks them more often.
+    overlay: bool,
+    // Activity of both sides over the finished game, shown on the squares instead.
+    heatmap: Option<Heatmap>,
+    // Whether a finished game is being run through the engine for an annotated export, and the
+    // evaluations of its positions once it has been.
+    annotating: bool,
+    evaluations: Option<Vec<i32>>,
+    // Whether the player's moves are checked for blunders, the verdicts keyed by move index.
+    reviewing: bool,
+    judgements: HashMap<usize, Judgement>,
+    // Index of the flagged move a takeback is offered for.
+    takeback: Option<usize>,
+}
+
+impl Gui {
+    fn load(&mut self, game: Game) {
+        self.recorded = game.outcome().is_some();
+        self.harvested = game.outcome().is_some();
+        // Games loaded after they ended need no announcement.
+        self.dismissed = game.outcome().is_some();
+        self.opening = eco::classify(&game);
+        self.game = game;
+        self.evaluations = None;
+        self.judgements.clear();
+        self.takeback = None;
+        self.training = None;
+        self.guessing = None;
+        self.viewing = None;
+        self.heatmap = None;
+    }
+
+    // Whether either kind of training has taken over the board.
+    fn in_training(&self) -> bool {
+        self.training.is_some() || self.guessing.is_some()
+    }
+
+    // Reads the judgements and evaluations of an annotated game back from its glyphs and comments.
+    fn annotations(&mut self, pgn: &Pgn) {
+        for (i, node) in pgn.moves.iter().enumerate() {
+            let judgement = node.nags.iter().find_map(|nag| Judgement::from_nag(*nag));
+            if let Some(judgement) = judgement {
+                self.judgements.insert(i, judgement);
             }
-            column = column.push(row);
         }
+        self.evaluations = annotation::evaluations(pgn);
+    }
+
+    // Sets up the current puzzle of the training on the board.
+    fn start_puzzle(&mut self, game: Result<Game, PuzzleError>) {
+        let training = self.training.take();
+
+        match game {
+            Ok(game) => self.load(game),
+            Err(e) => self.notify(e),
+        }
+
+        self.training = training;
+        self.opponent = Opponent::Human;
+        self.thinking = None;
+        self.animation = None;
+        self.hint = None;
+        self.evaluation = None;
+    }
+
+    fn tr(&self, text: &'static str) -> &'static str {
+        self.language.tr(text)
+    }
+
+    fn fill(&self, template: &'static str, values: &[&dyn fmt::Display]) -> String {
+        self.language.fill(template, values)
+    }
+
+    // Shows a message in the banner above the board until it expires, dropping the oldest one
+    // once there are too many.
+    fn notify(&mut self, message: impl Translate) {
+        let message = message.tr(self.language);
+        let mut chars = message.chars();
+        let text = match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => return,
+        };
+
+        if self.notices.len() == NOTICE_LIMIT {
+            self.notices.remove(0);
+        }
+        self.notices.push((text, Instant::now()));
+    }
 
-        Container::new(column).decorate().into()
+    fn save_window(&mut self) -> std::io::Result<()> {
+        if let Some((width, height)) = self.window_size {
+            self.settings.set("window_width", width)?;
+            self.settings.set("window_height", height)?;
+        }
+        if let Some((x, y)) = self.window_position {
+            self.settings.set("window_x", x)?;
+            self.settings.set("window_y", y)?;
+        }
+        self.settings.set("fullscreen", self.fullscreen)
+    }
+
+    // The board is locked while the engine is on move or judging a guess, and once a puzzle is
+    // solved or the game to guess has run out of moves.
+    fn locked(&self) -> bool {
+        let solved = self
+            .training
+            .as_ref()
+            .is_some_and(|training| training.verdict() == Some(Verdict::Solved));
+        let guessed = self
+            .guessing
+            .as_ref()
+            .is_some_and(|guessing| guessing.finished() || self.thinking.is_some());
+
+        self.opponent.engine_plays(&self.game.color()) || solved || guessed
+    }
+
+    // Reads a typed move in coordinate notation, such as "g1f3", or else in SAN, such as "Nf3".
+    fn read_move(&self, text: &str) -> Result<Lan, CatchAllError> {
+        if let Ok(lan) = Lan::parse(text) {
+            return Ok(lan);
+        }
+
+        let mut board = self.game.board().clone();
+        let san = self.language.to_english(text);
+        let (from, to, promotion) = San::parse(&san)?.resolve(&mut board, &self.game.color())?;
+
+        Ok(Lan::new(from, to, promotion))
+    }
+
+    // Follows up on a move the user tried to make from the given position, if it was played.
+    fn user_moved(&mut self, played: usize, before: Board, color: Color) -> Command<Message> {
+        if self.game.moves().len() == played {
+            return Command::none();
+        }
+
+        if self.training.is_some() {
+            return self.puzzle_move();
+        }
+        if self.guessing.is_some() {
+            return self.guess_move(before, color);
+        }
+
+        // A new move withdraws the offer to take back an earlier one and erases the marks.
+        self.takeback = None;
+        self.marks.clear();
+
+        Command::batch([self.review(before, color), self.refresh()])
+    }
+
+    // Plays a move the user did not make, gliding the piece to its destination.
+    fn play_animated(&mut self, lan: Lan) -> Option<Command<Message>> {
+        let piece = self.game.at(&lan.from).ok().cloned();
+        if let Err(e) = self.game.play_move(&lan.from, &lan.to, lan.promotion) {
+            self.notify(e);
+            return None;
+        }
+        self.marks.clear();
+        self.record();
+
+        Some(match piece {
+            Some(piece) => {
+                self.animation = Some(Animation {
+                    lan,
+                    piece,
+                    start: Instant::now(),
+                });
+                frame()
+            }
+            None => Command::none(),
+        })
+    }
+
+    // Answers a move made in a puzzle, with the opponent's reply if the move was right.
+    fn puzzle_move(&mut self) -> Command<Message> {
+        let verdict = match self
+            .training
+            .as_mut()
+            .map(|training| training.check(&self.game))
+        {
+            Some(Ok(verdict)) => verdict,
+            Some(Err(e)) => {
+                self.notify(e);
+                return Command::none();
+            }
+            None => return Command::none(),
+        };
+
+        match verdict {
+            Verdict::Correct(reply) => self.play_animated(reply).unwrap_or(Command::none()),
+            Verdict::Solved => Command::none(),
+            // A wrong move is taken back for another try.
+            Verdict::Wrong => {
+                if let Err(e) = self.game.revert() {
+                    self.notify(e);
+                }
+                Command::none()
+            }
+        }
+    }
+
+    // Takes a guess back off the board and asks the engine for its own choice in the position, so
+    // that the guess can be judged against both that and the move played in the game.
+    fn guess_move(&mut self, before: Board, color: Color) -> Command<Message> {
+        let guess = match self.game.moves().last() {
+            Some(san) => San::parse(san).and_then(|san| san.resolve(&mut before.clone(), &color)),
+            None => return Command::none(),
+        };
+        let guess = match guess.and_then(|guess| self.game.revert().map(|_| guess)) {
+            Ok((from, to, promotion)) => Lan::new(from, to, promotion),
+            Err(e) => {
+                self.notify(e);
+                return Command::none();
+            }
+        };
+
+        let hash = before.hash(&color);
+        self.thinking = Some(hash);
+        let language = self.language;
+
+        Command::perform(
+            background(move || ai::hint(before, color).map_err(|e| e.tr(language))),
+            move |reply| {
+                Message::Guessed(
+                    hash,
+                    guess,
+                    reply.unwrap_or(Err("Engine stopped".to_string())),
+                )
+            },
+        )
+    }
+
+    // Asks the engine for a reply on a background thread if it is its move.
+    fn engine_turn(&mut self) -> Command<Message> {
+        let color = self.game.color();
+
+        if !self.opponent.engine_plays(&color) || self.game.outcome().is_some() {
+            return Command::none();
+        }
+
+        let board = self.game.board().clone();
+        let difficulty = self.difficulty;
+        let hash = board.hash(&color);
+        self.thinking = Some(hash);
+
+        let book = match self.game.fullmove_number() <= self.book_moves {
+            true => self.book.clone(),
+            false => None,
+        };
+        let language = self.language;
+
+        Command::perform(
+            background(move || {
+                ai::think(board, color, difficulty, book.as_deref()).map_err(|e| e.tr(language))
+            }),
+            move |reply| {
+                Message::EngineMoved(hash, reply.unwrap_or(Err("Engine stopped".to_string())))
+            },
+        )
+    }
+
+    // Asks the external engine about the current position if analysis is switched on.
+    // An analysis still running on an earlier position is stopped first.
+    fn analyse(&mut self) -> Command<Message> {
+        self.stop_analysis.store(true, Ordering::Relaxed);
+
+        let path = match (&self.engine_path, self.analysing) {
+            (Some(path), true) if self.game.outcome().is_none() => path.clone(),
+            _ => return Command::none(),
+        };
+
+        let fen = self.game.to_fen();
+        let hash = self.game.board().hash(&self.game.color());
+        let external = Arc::clone(&self.external);
+        let analysis = Arc::clone(&self.analysis);
+        let stop = Arc::new(AtomicBool::new(false));
+        self.stop_analysis = Arc::clone(&stop);
+        let language = self.language;
+
+        let work = move || -> Result<(), String> {
+            let mut external = external
+                .lock()
+                .map_err(|_| "The engine is unavailable".to_string())?;
+
+            if stop.load(Ordering::Relaxed) {
+                return Ok(());
+            }
+
+            if external.is_none() {
+                let mut engine = Engine::spawn(&path).map_err(|e| e.tr(language))?;
+                engine
+                    .set_option("MultiPV", &ANALYSIS_LINES.to_string())
+                    .map_err(|e| e.tr(language))?;
+                *external = Some(engine);
+            }
+
+            let engine = external.as_mut().ok_or("The engine is unavailable")?;
+            let name = engine.name().to_string();
+
+            let publish = |best_move: Option<Lan>, lines: Vec<Info>| {
+                if let Ok(mut analysis) = analysis.lock() {
+                    *analysis = Some((hash, name.clone(), Analysis { best_move, lines }));
+                }
+            };
+
+            let mut lines: Vec<Info> = Vec::new();
+            let report = |info: &Info| {
+                match lines.iter_mut().find(|line| line.multipv == info.multipv) {
+                    Some(line) => *line = info.clone(),
+                    None => lines.push(info.clone()),
+                }
+                lines.sort_by_key(|info| info.multipv);
+                publish(None, lines.clone());
+            };
+
+            match engine.analyse(&fen, Some(ANALYSIS_DEPTH), None, &stop, report) {
+                Ok(result) => {
+                    publish(result.best_move, result.lines);
+                    Ok(())
+                }
+                Err(e) => {
+                    // A broken engine is restarted on the next request.
+                    *external = None;
+                    Err(e.tr(language))
+                }
+            }
+        };
+
+        let analysis = Command::perform(background(work), |reply| {
+            Message::AnalysisFinished(reply.unwrap_or(Err("Engine stopped".to_string())))
+        });
+
+        match self.polling {
+            true => analysis,
+            false => {
+                self.polling = true;
+                Command::batch([analysis, poll()])
+            }
+        }
+    }
+
+    // The game as it stood after the given number of its moves.
+    fn game_at(&self, moves: usize) -> Game {
+        let mut game = self.game.clone();
+        while game.moves().len() > moves && game.revert().is_ok() {}
+        game
+    }
+
+    // Drops the moves after the given number of them to play on from the position reached.
+    fn branch(&mut self, moves: usize) {
+        while self.game.moves().len() > moves {
+            if let Err(e) = self.game.revert() {
+                self.notify(e);
+                break;
+            }
+        }
+
+        self.judgements.retain(|i, _| *i < moves);
+        self.takeback = None;
+        self.thinking = None;
+        self.animation = None;
+        self.hint = None;
+        self.opening = eco::classify(&self.game);
+    }
+
+    // Hash of the position of the game shown, which may be an earlier one.
+    fn shown_hash(&self) -> u64 {
+        match self.viewing {
+            Some(moves) => {
+                let game = self.game_at(moves);
+                game.board().hash(&game.color())
+            }
+            None => self.game.board().hash(&self.game.color()),
+        }
+    }
+
+    // Arrows of the external engine's lines on the position it analyses, unless another position
+    // is shown.
+    fn analysis_arrows(&self) -> Vec<(Position, Position, iced::Color)> {
+        let analysis = self
+            .analysis
+            .lock()
+            .ok()
+            .and_then(|analysis| analysis.clone());
+        let lines = match analysis {
+            Some((analysed, _, analysis))
+                if self.analysing && self.preview.is_none() && analysed == self.shown_hash() =>
+            {
+                analysis.lines
+            }
+            _ => return Vec::new(),
+        };
+
+        let fade = |color: iced::Color, steps: usize| iced::Color {
+            a: color.a * ARROW_FADE.powi(steps as i32),
+            ..color
+        };
+        let mut arrows = Vec::new();
+        for (rank, line) in lines.iter().enumerate() {
+            let plies = match rank {
+                0 => LINE_ARROWS,
+                _ => 1,
+            };
+            for (ply, lan) in line.pv.iter().take(plies).enumerate() {
+                let color = match rank {
+                    0 => fade(BEST_LINE, ply),
+                    _ => fade(OTHER_LINE, rank - 1),
+                };
+                arrows.push((lan.from, lan.to, color));
+            }
+        }
+
+        // The fainter arrows go underneath, so that the best move stays on top.
+        arrows.reverse();
+        arrows
+    }
+
+    // Board to draw: an earlier position looked back at, the game's, or the position at the end
+    // of the previewed line.
+    fn shown_board(&self) -> Board {
+        if let Some(moves) = self.viewing {
+            return self.game_at(moves).board().clone();
+        }
+
+        let mut board = self.game.board().clone();
+        let hash = board.hash(&self.game.color());
+
+        if let Some((previewed, line)) = &self.preview {
+            if *previewed == hash {
+                for lan in line {
+                    if board.make(&lan.from, &lan.to, lan.promotion).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+
+        board
+    }
+
+    // Judges the move just played from the given position if blunder checking is switched on.
+    fn review(&self, before: Board, color: Color) -> Command<Message> {
+        if !self.reviewing || self.in_training() {
+            return Command::none();
+        }
+
+        let index = self.game.moves().len() - 1;
+        let san = self.game.moves()[index].clone();
+        let after = self.game.board().clone();
+        let language = self.language;
+
+        Command::perform(
+            background(move || ai::review(before, color, after).map_err(|e| e.tr(language))),
+            move |reply| {
+                Message::Reviewed(
+                    index,
+                    san,
+                    reply.unwrap_or(Err("Engine stopped".to_string())),
+                )
+            },
+        )
+    }
+
+    // Estimates the current position for the evaluation bar.
+    fn evaluate(&self) -> Command<Message> {
+        // The bar would give puzzles away.
+        if self.game.outcome().is_some() || self.in_training() {
+            return Command::none();
+        }
+
+        let board = self.game.board().clone();
+        let color = self.game.color();
+        let hash = board.hash(&color);
+        let language = self.language;
+
+        let work = move || {
+            let score = ai::evaluate(board, color).map_err(|e| e.tr(language))?;
+            Ok(match color {
+                Color::White => score,
+                Color::Black => score.flipped(),
+            })
+        };
+
+        Command::perform(background(work), move |reply| {
+            Message::Evaluated(hash, reply.unwrap_or(Err("Engine stopped".to_string())))
+        })
+    }
+
+    // Lets the built-in engine reply and evaluate the new position, and the external one analyse it.
+    fn refresh(&mut self) -> Command<Message> {
+        self.opening = eco::classify(&self.game);
+        self.heatmap = None;
+        // A game taken back from its end is announced again once it ends anew.
+        if self.game.outcome().is_none() {
+            self.dismissed = false;
+        }
+
+        Command::batch([
+            self.engine_turn(),
+            self.evaluate(),
+            self.analyse(),
+            self.harvest(),
+        ])
+    }
+
+    // The side the user plays, or the side to move when both sides are played at the board.
+    fn player(&self) -> Color {
+        match self.opponent {
+            Opponent::Computer(engine) => engine.opposite(),
+            Opponent::Human | Opponent::Spectate => self.game.color(),
+        }
+    }
+
+    // Whether the engine playing the given side would rather draw, judged by its latest evaluation.
+    fn accepts_draw(&self, engine: &Color) -> bool {
+        let hash = self.game.board().hash(&self.game.color());
+        let score = match self.evaluation {
+            Some((evaluated, score)) if evaluated == hash => score,
+            _ => return false,
+        };
+        let score = match engine {
+            Color::White => score,
+            Color::Black => score.flipped(),
+        };
+
+        match score {
+            Score::Centipawns(cp) => cp <= DRAW_ACCEPTANCE,
+            Score::Mate(moves) => moves < 0,
+        }
+    }
+
+    // Searches a game that has just ended for tactics and saves them as puzzles.
+    fn harvest(&mut self) -> Command<Message> {
+        if self.harvested || self.game.outcome().is_none() || self.in_training() {
+            return Command::none();
+        }
+        self.harvested = true;
+
+        let game = self.game.clone();
+        let path = self.puzzles_path.clone();
+        let language = self.language;
+
+        let work = move || -> Result<usize, String> {
+            let puzzles = tactics::find_puzzles(&game).map_err(|e| e.tr(language))?;
+            puzzle::append(&path, &puzzles).map_err(|e| e.tr(language))?;
+            Ok(puzzles.len())
+        };
+
+        Command::perform(background(work), |reply| {
+            Message::Harvested(reply.unwrap_or(Err("Engine stopped".to_string())))
+        })
+    }
+
+    // Indexes the games of the explorer's PGN file, which may have grown since the last time.
+    fn explore(&mut self) -> Command<Message> {
+        self.explorer = None;
+        if !self.exploring {
+            return Command::none();
+        }
+
+        let path = self.explorer_path.clone();
+        let language = self.language;
+        let work = {
+            let path = path.clone();
+            move || {
+                Explorer::from_database(&Database::new(&path))
+                    .map(Arc::new)
+                    .map_err(|e| e.tr(language))
+            }
+        };
+
+        Command::perform(background(work), move |reply| {
+            Message::ExplorerBuilt(path, reply.unwrap_or(Err("Indexing stopped".to_string())))
+        })
+    }
+
+    fn record(&mut self) {
+        // Puzzles are not games worth keeping.
+        if self.recorded || self.game.outcome().is_none() || self.in_training() {
+            return;
+        }
+
+        match self.database.record(&self.game) {
+            Ok(()) => self.recorded = true,
+            Err(e) => self.notify(e),
+        }
+    }
+
+    #[rustfmt::skip]
+    fn square_view(&self, board: &Board, pos: Position, destination: bool, check: bool, attacks: Option<(usize, usize)>) -> Square {
+        let turn = self.game.turn();
+        let hash = self.game.board().hash(&self.game.color());
+        let hinted = match self.hint {
+            Some((hinted, lan)) if hinted == hash => lan.from == pos || lan.to == pos,
+            _ => false,
+        };
+
+        // Earlier positions are shown without the move being animated, unless played back. The
+        // moving piece is drawn on its own between the squares.
+        let piece = match &self.animation {
+            _ if self.viewing.is_some() && !self.autoplay => board.at(&pos).ok(),
+            Some(animation) if animation.lan.to == pos => None,
+            _ => board.at(&pos).ok(),
+        };
+
+        let last_move = board.last_move().is_some_and(|mv| mv.from == pos || mv.to == pos);
+
+        Square::new(pos, piece.cloned(), turn, hinted, destination, last_move, check, attacks)
+    }
+
+    // The piece of the move being animated and how far along it is.
+    fn moving(&self) -> Option<Moving> {
+        let animation = self.animation.as_ref()?;
+        if self.viewing.is_some() && !self.autoplay {
+            return None;
+        }
+
+        Some(Moving {
+            piece: animation.piece.clone(),
+            from: animation.lan.from,
+            to: animation.lan.to,
+            progress: animation.progress(Instant::now()),
+        })
+    }
+
+    // Side seen from below, which follows the side to move when the board turns with each move.
+    fn bottom(&self) -> Color {
+        let bottom = match self.auto_flip {
+            true => self.game.color(),
+            false => Color::White,
+        };
+
+        match self.flipped {
+            true => bottom.opposite(),
+            false => bottom,
+        }
+    }
+
+    // Square the keyboard cursor appears on.
+    fn cursor_start(&self) -> Position {
+        match self.game.turn() {
+            Turn::Select(_, from) => from,
+            _ => self
+                .shown_board()
+                .king(&self.game.color())
+                .map_or(Position::new(4, 0), |(pos, _)| *pos),
+        }
+    }
+
+    // Description of a square for those who cannot see the board, such as "e4, white knight".
+    fn square_label(&self, pos: &Position) -> String {
+        match self.shown_board().at(pos) {
+            Ok(piece) => format!("{}, {}", pos, self.tr(piece_name(piece))),
+            Err(_) => self.fill("{}, empty", &[pos]),
+        }
+    }
+
+    fn board_view(&self) -> Canvas<BoardCanvas, Message> {
+        let shown = self.shown_board();
+        // Destinations of the selected piece, left out while a line is previewed.
+        let destinations = match self.game.turn() {
+            Turn::Select(_, from) if self.preview.is_none() && self.viewing.is_none() => {
+                self.game.board().clone().legal_destinations(&from)
+            }
+            _ => Vec::new(),
+        };
+        // Attackers are counted once for the whole board rather than square by square.
+        let counts = self.overlay.then(|| {
+            (
+                shown.attack_counts(&Color::White),
+                shown.attack_counts(&Color::Black),
+            )
+        });
+        let checked: Vec<Position> = [Color::White, Color::Black]
+            .iter()
+            .filter(|color| shown.in_check(color).unwrap_or(false))
+            .filter_map(|color| shown.king(color).ok().map(|(pos, _)| *pos))
+            .collect();
+
+        let mut squares = Vec::new();
+        for rank in 0..8 {
+            for file in 0..8 {
+                let pos = Position::new(file, rank);
+                let attacks = counts.as_ref().map(|(white, black)| {
+                    (
+                        white.get(&pos).copied().unwrap_or(0),
+                        black.get(&pos).copied().unwrap_or(0),
+                    )
+                });
+                let destination = destinations.contains(&pos);
+                let check = checked.contains(&pos);
+                let mut square = self.square_view(&shown, pos, destination, check, attacks);
+                square.heat = self.heatmap.as_ref().map(|heatmap| heatmap.heat(&pos));
+                squares.push(square);
+            }
+        }
+
+        // Pieces can only be picked up by a human on move in the game itself, or in an earlier
+        // position analysis branches off from.
+        let mover = match (&self.preview, self.viewing) {
+            (None, None) if self.game.outcome().is_none() => Some(self.game.color()),
+            (None, Some(moves)) if self.analysing => Some(self.game_at(moves).color()),
+            _ => None,
+        }
+        .filter(|color| !self.opponent.engine_plays(color));
+
+        Canvas::new(BoardCanvas {
+            squares,
+            mover,
+            bottom: self.bottom(),
+            theme: self.board_theme,
+            palette: self.palette,
+            sprites: self.sprites.clone(),
+            marks: self
+                .marks
+                .get(&self.shown_hash())
+                .cloned()
+                .unwrap_or_default(),
+            arrows: self.analysis_arrows(),
+            cursor: self.cursor,
+            moving: self.moving(),
+        })
+        .width(Length::Fill)
+        .height(Length::Fill)
+    }
+
+    // Pieces the given side has captured, with the material it is ahead by if any.
+    fn captures_view(&self, captures: &Captures, color: Color) -> Row<'_, Message, Renderer> {
+        let pieces = captures.by(&color).to_vec();
+        let lead = match color {
+            Color::White => captures.balance,
+            Color::Black => -captures.balance,
+        };
+
+        let row = Row::new().decorate().spacing(10).height(TRAY_SIZE).push(
+            Canvas::new(Tray {
+                pieces,
+                sprites: self.sprites.clone(),
+            })
+            .width(TRAY_SIZE + 15.0 * TRAY_STEP)
+            .height(TRAY_SIZE),
+        );
+
+        match lead > 0 {
+            true => row.push(Text::new(format!("+{}", lead)).size(20)),
+            false => row,
+        }
+    }
+
+    // Captures and clock of one side, shown on the edge of the board the side plays from.
+    fn player_view(&self, captures: &Captures, color: Color) -> Row<'_, Message, Renderer> {
+        let row = Row::new()
+            .decorate()
+            .width(Length::Fill)
+            .push(self.captures_view(captures, color))
+            .push(horizontal_space(Length::Fill));
+
+        match self.clock_view(color) {
+            Some(clock) => row.push(clock),
+            None => row,
+        }
+    }
+
+    // Time left to the given side in a timed game.
+    fn clock_view(&self, color: Color) -> Option<Text<'_, Renderer>> {
+        let clock = self.game.clock()?;
+        let text = Text::new(clock::format_time(clock.remaining(&color))).size(24);
+
+        // The running clock stands out from the stopped one in the usual text color.
+        Some(match clock.running() == Some(color) {
+            true => text,
+            false => text.style(theme::Text::Color(CLOCK_STOPPED)),
+        })
+    }
+
+    fn evaluation_view(&self) -> Column<'_, Message, Renderer> {
+        // Finished games show their result, running ones the latest estimate.
+        let (white, label) = match (self.game.outcome(), &self.evaluation) {
+            (Some(outcome), _) => match outcome.winner() {
+                Some(Color::White) => (100.0, outcome.result().to_string()),
+                Some(Color::Black) => (0.0, outcome.result().to_string()),
+                None => (50.0, outcome.result().to_string()),
+            },
+            (None, Some((_, score))) => match score {
+                Score::Centipawns(cp) => (accuracy::win_percent(*cp), score.to_string()),
+                Score::Mate(moves) if *moves < 0 => (0.0, score.to_string()),
+                Score::Mate(_) => (100.0, score.to_string()),
+            },
+            (None, None) => (50.0, String::new()),
+        };
+
+        // Either side keeps a sliver of the bar even when lost.
+        let white = (white * 10.0).round().clamp(10.0, 990.0) as u16;
+        let side = |portion: u16, color: iced::Color| {
+            Container::new(Text::new(""))
+                .width(Length::Fill)
+                .height(Length::FillPortion(portion))
+                .style(theme::Container::Custom(Box::new(Fill(color))))
+        };
+
+        // The bar is turned along with the board.
+        let (top, bottom) = match self.bottom() {
+            Color::White => (side(1000 - white, BAR_BLACK), side(white, BAR_WHITE)),
+            Color::Black => (side(white, BAR_WHITE), side(1000 - white, BAR_BLACK)),
+        };
+        let bar = Column::new()
+            .width(30)
+            .height(Length::Fill)
+            .push(top)
+            .push(bottom);
+
+        Column::new()
+            .decorate()
+            .spacing(5)
+            .height(Length::Fill)
+            .push(bar)
+            .push(Text::new(label).size(16))
+    }
+
+    // The bundled piece sets, and the user's own if one is in use.
+    fn piece_sets(&self) -> Vec<PieceSet> {
+        let mut sets = PieceSet::BUNDLED.to_vec();
+        if let PieceSet::Custom(_) = self.piece_set {
+            sets.push(self.piece_set.clone());
+        }
+        sets
+    }
+
+    fn toolbar_view(&self) -> Row<'_, Message, Renderer> {
+        // Resigning and offering a draw are up to the user, not to the engine watched playing itself.
+        let playing = self.game.outcome().is_none()
+            && !self.in_training()
+            && self.opponent != Opponent::Spectate;
+        let undoable = !self.game.moves().is_empty()
+            && !self.in_training()
+            && self.opponent != Opponent::Spectate;
+
+        Row::new()
+            .decorate()
+            .spacing(20)
+            .padding(10)
+            .push(button(self.tr("New game")).on_press(Message::AbandonGame))
+            .push(match undoable {
+                true => button(self.tr("Undo")).on_press(Message::Undo),
+                false => button(self.tr("Undo")),
+            })
+            .push(match playing {
+                true => button(self.tr("Resign")).on_press(Message::Resign),
+                false => button(self.tr("Resign")),
+            })
+            .push(match self.game.draw_offer() {
+                Some(color) => button(Text::new(
+                    self.fill("Draw offered by {}", &[&self.language.color(&color)]),
+                )),
+                None if playing => button(self.tr("Offer draw")).on_press(Message::OfferDraw),
+                None => button(self.tr("Offer draw")),
+            })
+            .push(
+                text_input(self.tr("Type a move, e.g. Nf3"), &self.entry)
+                    .on_input(Message::MoveTyped)
+                    .on_submit(Message::SubmitMove)
+                    .width(200),
+            )
+            .push(
+                Text::new(self.entry_error.clone().unwrap_or_default())
+                    .style(theme::Text::Color(HIGHLIGHTED_SQUARE)),
+            )
+    }
+
+    // Side to move, move number, state of the game, progress toward the fifty-move rule and the
+    // time left to either side, kept along the bottom of the window.
+    fn status_bar_view(&self) -> Row<'_, Message, Renderer> {
+        let state = match self.game.status() {
+            Ok(status) => status.tr(self.language),
+            Err(e) => e.tr(self.language),
+        };
+        let mut items = Vec::from_iter(self.cursor.map(|pos| self.square_label(&pos)));
+        items.extend([
+            self.fill("{} to move", &[&self.language.color(&self.game.color())]),
+            self.fill("Move {}", &[&self.game.fullmove_number()]),
+            self.fill("State: {}", &[&state]),
+            self.fill(
+                "Fifty-move rule: {}/{}",
+                &[&self.game.halfmove_clock().min(FIFTY_MOVES), &FIFTY_MOVES],
+            ),
+        ]);
+        if let Some(clock) = self.game.clock() {
+            items.extend([Color::White, Color::Black].map(|color| {
+                format!(
+                    "{} {}",
+                    self.language.color(&color),
+                    clock::format_time(clock.remaining(&color))
+                )
+            }));
+        }
+
+        items
+            .into_iter()
+            .fold(Row::new().decorate().spacing(30).padding(5), |row, item| {
+                row.push(Text::new(item).size(16))
+            })
+    }
+
+    // Lets a human opponent answer a pending draw offer, which the engine answers by itself.
+    fn draw_offer_view(&self) -> Option<Row<'_, Message, Renderer>> {
+        let offerer = self.game.draw_offer()?;
+        if self.opponent.engine_plays(&offerer.opposite()) {
+            return None;
+        }
+
+        Some(
+            Row::new()
+                .decorate()
+                .spacing(20)
+                .padding(10)
+                .push(
+                    Text::new(self.fill("{} offers a draw", &[&self.language.color(&offerer)]))
+                        .size(24),
+                )
+                .push(button(self.tr("Accept")).on_press(Message::AcceptDraw))
+                .push(button(self.tr("Decline")).on_press(Message::DeclineDraw)),
+        )
+    }
+
+    fn notices_view(&self) -> Option<Column<'_, Message, Renderer>> {
+        if self.notices.is_empty() {
+            return None;
+        }
+
+        let column = self.notices.iter().enumerate().fold(
+            Column::new().spacing(5).padding(5),
+            |column, (i, (text, _))| {
+                let row = Row::new()
+                    .decorate()
+                    .spacing(20)
+                    .push(Text::new(text.as_str()).style(theme::Text::Color(HIGHLIGHTED_SQUARE)))
+                    .push(button(self.tr("Dismiss")).on_press(Message::DismissNotice(i)));
+                column.push(
+                    Container::new(row)
+                        .padding(5)
+                        .width(Length::Fill)
+                        .style(theme::Container::Custom(Box::new(Fill(NOTICE_BACKGROUND)))),
+                )
+            },
+        );
+
+        Some(column)
+    }
+
+    fn confirm_view(&self) -> Column<'_, Message, Renderer> {
+        let buttons = Row::new()
+            .decorate()
+            .spacing(20)
+            .push(button(self.tr("Abandon game")).on_press(Message::NewGame))
+            .push(button(self.tr("Keep playing")).on_press(Message::CancelNewGame));
+
+        Column::new()
+            .decorate()
+            .spacing(20)
+            .padding(40)
+            .push(Text::new(self.tr("New game")).size(40))
+            .push(Text::new(self.tr("The game in progress will be lost.")).size(20))
+            .push(buttons)
+    }
+
+    fn actions_view(&self) -> Row<'_, Message, Renderer> {
+        let row = Row::new()
+            .decorate()
+            .spacing(20)
+            .padding(10)
+            .push(button(self.tr("Hint")).on_press(Message::Hint))
+            .push(button(self.tr("Puzzles")).on_press(Message::LoadPuzzles))
+            .push(button(self.tr("Guess the move")).on_press(Message::GuessMoves))
+            .push(button(self.tr("Explorer")).on_press(Message::ToggleExplorer))
+            .push(button(self.tr("Flip board")).on_press(Message::Flip))
+            .push(match self.fullscreen {
+                true => button(self.tr("Leave fullscreen")).on_press(Message::ToggleFullscreen),
+                false => button(self.tr("Fullscreen")).on_press(Message::ToggleFullscreen),
+            })
+            .push(match self.auto_flip {
+                true => button(self.tr("Fixed board")).on_press(Message::ToggleAutoFlip),
+                false => button(self.tr("Turn board each move")).on_press(Message::ToggleAutoFlip),
+            })
+            .push(match self.overlay {
+                true => button(self.tr("Hide attacks")).on_press(Message::ToggleOverlay),
+                false => button(self.tr("Show attacks")).on_press(Message::ToggleOverlay),
+            })
+            .push(pick_list(
+                Language::ALL.to_vec(),
+                Some(self.language),
+                Message::LanguageSelected,
+            ))
+            .push(pick_list(
+                AppTheme::ALL.to_vec(),
+                Some(self.app_theme),
+                Message::AppThemeSelected,
+            ))
+            .push(pick_list(
+                BoardTheme::ALL.to_vec(),
+                Some(self.board_theme),
+                Message::BoardThemeSelected,
+            ))
+            .push(pick_list(
+                Palette::ALL.to_vec(),
+                Some(self.palette),
+                Message::PaletteSelected,
+            ))
+            .push(pick_list(
+                self.piece_sets(),
+                Some(self.piece_set.clone()),
+                Message::PieceSetSelected,
+            ))
+            .push(button(self.tr("Load pieces")).on_press(Message::LoadPieceSet))
+            .push(button(self.tr("Export image")).on_press(Message::ExportImage))
+            .push(button(self.tr("Edit board")).on_press(Message::EditBoard))
+            .push(button(self.tr("Copy FEN")).on_press(Message::CopyFen))
+            .push(button(self.tr("Paste FEN")).on_press(Message::PasteFen));
+
+        let row = match self.reviewing {
+            true => row.push(button(self.tr("Stop blunder check")).on_press(Message::ToggleReview)),
+            false => row.push(button(self.tr("Check blunders")).on_press(Message::ToggleReview)),
+        };
+
+        // Activity is only told once the game is over.
+        let row = match (&self.heatmap, self.game.outcome()) {
+            (Some(_), _) => {
+                row.push(button(self.tr("Hide activity")).on_press(Message::ToggleHeatmap))
+            }
+            (None, Some(_)) => {
+                row.push(button(self.tr("Show activity")).on_press(Message::ToggleHeatmap))
+            }
+            (None, None) => row,
+        };
+
+        let row = match &self.engine_path {
+            Some(_) if self.analysing => {
+                row.push(button(self.tr("Stop analysis")).on_press(Message::ToggleAnalysis))
+            }
+            Some(_) => row.push(button(self.tr("Analyse")).on_press(Message::ToggleAnalysis)),
+            None => row,
+        };
+
+        #[cfg(feature = "lichess")]
+        let row = row
+            .push(
+                text_input(self.tr("lichess game URL"), &self.lichess_url)
+                    .on_input(Message::LichessUrl)
+                    .width(200),
+            )
+            .push(button(self.tr("Import")).on_press(Message::ImportLichess));
+
+        row
+    }
+
+    fn analysis_view(&self) -> Option<Column<'_, Message, Renderer>> {
+        if !self.analysing {
+            return None;
+        }
+
+        let header = match self.panel_open {
+            true => "Analysis \u{25be}",
+            false => "Analysis \u{25b8}",
+        };
+        let column = Column::new()
+            .spacing(10)
+            .padding(10)
+            .width(400)
+            .push(button(header).on_press(Message::TogglePanel));
+
+        if !self.panel_open {
+            return Some(column);
+        }
+
+        let color = self.game.color();
+        let hash = self.game.board().hash(&color);
+        let analysis = self
+            .analysis
+            .lock()
+            .ok()
+            .and_then(|analysis| analysis.clone());
+
+        let (name, lines) = match analysis {
+            Some((analysed, name, analysis)) if analysed == hash => (name, analysis.lines),
+            _ => return Some(column.push(Text::new(self.tr("Analysing...")).size(20))),
+        };
+
+        let mut column = column.push(Text::new(name).size(20));
+
+        for (i, line) in lines.iter().enumerate() {
+            // Engines score for the side to move, the panel always for white.
+            let score = line.score.map(|score| match color {
+                Color::White => score,
+                Color::Black => score.flipped(),
+            });
+            let moves = San::format_line(self.game.board(), &color, &line.pv).unwrap_or_default();
+
+            let moves = moves.iter().map(|san| self.language.san(san)).collect();
+            let text = self.fill(
+                "{} (depth {}) {}",
+                &[
+                    &score.map_or("?".to_string(), |score| score.to_string()),
+                    &line.depth,
+                    &numbered(self.game.ply(), moves),
+                ],
+            );
+
+            column = column.push(
+                button(Text::new(text).size(16))
+                    .width(Length::Fill)
+                    .on_press(Message::Preview(Some(i))),
+            );
+        }
+
+        Some(match &self.preview {
+            Some((previewed, _)) if *previewed == hash => {
+                column.push(button(self.tr("Back to game")).on_press(Message::Preview(None)))
+            }
+            _ => column,
+        })
+    }
+
+    fn explorer_view(&self) -> Option<Column<'_, Message, Renderer>> {
+        if !self.exploring {
+            return None;
+        }
+
+        let column = Column::new().spacing(10).padding(10).width(400).push(
+            Row::new()
+                .decorate()
+                .spacing(10)
+                .push(Text::new(self.tr("Explorer")).size(20).width(Length::Fill))
+                .push(button(self.tr("Import PGN")).on_press(Message::ImportDatabase)),
+        );
+
+        let explorer = match &self.explorer {
+            Some(explorer) => explorer,
+            None => return Some(column.push(Text::new(self.tr("Indexing games...")).size(16))),
+        };
+
+        let mut column = column.push(
+            Text::new(self.fill("{} games in {}", &[&explorer.games(), &self.explorer_path]))
+                .size(16),
+        );
+        if explorer.unreadable() > 0 {
+            column = column.push(
+                Text::new(self.fill("{} unreadable games skipped", &[&explorer.unreadable()]))
+                    .size(14),
+            );
+        }
+
+        let hash = self.game.board().hash(&self.game.color());
+        let (moves, references) = (explorer.moves(hash), explorer.references(hash));
+        if references.is_empty() {
+            return Some(
+                column.push(Text::new(self.tr("No games reached this position")).size(16)),
+            );
+        }
+
+        for (san, stats) in moves {
+            let (white, draws, black) = stats.percentages();
+            column = column.push(
+                Row::new()
+                    .spacing(10)
+                    .push(Text::new(self.language.san(san)).size(16).width(60))
+                    .push(Text::new(stats.games().to_string()).size(16).width(60))
+                    .push(
+                        Text::new(format!("{:.0}% / {:.0}% / {:.0}%", white, draws, black))
+                            .size(16),
+                    ),
+            );
+        }
+
+        // The games themselves, to be replayed from the position on.
+        column = column.push(Text::new(self.tr("Games reaching this position")).size(20));
+        for (index, moves, summary) in references.into_iter().take(REFERENCE_GAMES) {
+            let accuracy = summary.accuracy.map_or(String::new(), |(white, black)| {
+                format!("{:.0}% / {:.0}%", white, black)
+            });
+            column = column.push(
+                button(
+                    Column::new()
+                        .push(
+                            Text::new(format!(
+                                "{} - {} {}",
+                                summary.white, summary.black, summary.result
+                            ))
+                            .size(16),
+                        )
+                        .push(
+                            Text::new(format!("{} {} {}", summary.date, summary.eco, accuracy))
+                                .size(14),
+                        ),
+                )
+                .width(Length::Fill)
+                .style(theme::Button::Text)
+                .on_press(Message::LoadReference(index, moves)),
+            );
+        }
+
+        Some(column)
+    }
+
+    // Moves of the game in numbered pairs, the one leading to the shown position highlighted.
+    fn moves_view(&self) -> Option<Column<'_, Message, Renderer>> {
+        let moves = self.game.moves();
+        if moves.is_empty() {
+            return None;
+        }
+
+        // Games set up from a FEN need not start with white's first move.
+        let first = self.game.ply() - moves.len();
+        let current = self.viewing.unwrap_or(moves.len());
+
+        let mut cells = Vec::new();
+        if first % 2 == 1 {
+            cells.push(None);
+        }
+        cells.extend((0..moves.len()).map(Some));
+
+        let mut list = Column::new().spacing(5);
+        for (n, pair) in cells.chunks(2).enumerate() {
+            let mut row = Row::new().decorate().spacing(5).push(
+                Text::new(format!("{}.", first / 2 + n + 1))
+                    .size(16)
+                    .width(40),
+            );
+
+            for cell in pair {
+                let cell: Element<'_, Message, Renderer> = match cell {
+                    Some(i) => {
+                        let label = match self.judgements.get(i) {
+                            Some(judgement) => {
+                                format!("{}{}", self.language.san(&moves[*i]), judgement.symbol())
+                            }
+                            None => self.language.san(&moves[*i]),
+                        };
+                        let style = match i + 1 == current {
+                            true => theme::Button::Primary,
+                            false => theme::Button::Text,
+                        };
+                        button(Text::new(label).size(16))
+                            .width(100)
+                            .style(style)
+                            .on_press(Message::Jump(Some(i + 1)))
+                            .into()
+                    }
+                    None => Text::new("...").size(16).width(100).into(),
+                };
+                row = row.push(cell);
+            }
+
+            list = list.push(row);
+        }
+
+        // Stepping back is possible from anywhere but the start, forward only from the past.
+        let back = current > 0;
+        let forward = self.viewing.is_some();
+        let step = |label, enabled, message| match enabled {
+            true => button(label).on_press(message),
+            false => button(label),
+        };
+        let navigation = Row::new()
+            .decorate()
+            .spacing(5)
+            .push(step("|<", back, Message::Jump(Some(0))))
+            .push(step("<", back, Message::StepBack))
+            .push(step(">", forward, Message::StepForward))
+            .push(step(">|", forward, Message::Jump(None)));
+
+        let playback = Row::new()
+            .decorate()
+            .spacing(10)
+            .push(match self.autoplay {
+                true => button(self.tr("Pause")).on_press(Message::ToggleAutoplay),
+                false => button(self.tr("Play")).on_press(Message::ToggleAutoplay),
+            })
+            .push(
+                slider(
+                    AUTOPLAY_DELAYS,
+                    self.autoplay_delay,
+                    Message::AutoplayDelayChanged,
+                )
+                .step(AUTOPLAY_STEP)
+                .width(120),
+            )
+            .push(Text::new(format!("{:.2} s", self.autoplay_delay)).size(16));
+
+        let column = Column::new()
+            .spacing(10)
+            .padding(10)
+            .width(300)
+            .height(Length::Fill)
+            .push(Text::new(self.tr("Moves")).size(20))
+            .push(navigation)
+            .push(playback)
+            .push(scrollable(list).height(Length::Fill));
+
+        // Once the game has been analysed, its evaluations are plotted below the moves.
+        let graph = self.evaluations().map(|evals| {
+            let mut blunders: Vec<usize> = self
+                .judgements
+                .iter()
+                .filter(|(_, judgement)| **judgement == Judgement::Blunder)
+                .map(|(i, _)| *i)
+                .collect();
+            blunders.sort_unstable();
+
+            Canvas::new(EvalGraph {
+                evals: evals.to_vec(),
+                blunders,
+                current,
+            })
+            .width(Length::Fill)
+            .height(GRAPH_HEIGHT)
+        });
+
+        Some(match graph {
+            Some(graph) => column.push(graph),
+            None => column,
+        })
+    }
+
+    // Opening reached so far and the offer to take back a flagged move.
+    fn opening_view(&self) -> Option<Column<'_, Message, Renderer>> {
+        let opening = self
+            .opening
+            .as_ref()
+            .map(|opening| Text::new(format!("{} {}", opening.eco, opening.name)).size(20));
+
+        let offer = self.takeback.and_then(|index| {
+            let san = self.game.moves().get(index)?;
+            let judgement = self.judgements.get(&index)?;
+            Some(
+                Row::new()
+                    .decorate()
+                    .spacing(20)
+                    .push(Text::new(format!("{}: {}", judgement, self.language.san(san))).size(20))
+                    .push(button(self.tr("Take back")).on_press(Message::TakeBack)),
+            )
+        });
+
+        if opening.is_none() && offer.is_none() {
+            return None;
+        }
+
+        let mut column = Column::new().decorate().spacing(10).padding(10);
+        if let Some(opening) = opening {
+            column = column.push(opening);
+        }
+        if let Some(offer) = offer {
+            column = column.push(offer);
+        }
+        Some(column)
+    }
+
+    fn editor_view(&self, editor: &Editor) -> Row<'_, Message, Renderer> {
+        let mut squares = Vec::new();
+        for rank in 0..8 {
+            for file in 0..8 {
+                let pos = Position::new(file, rank);
+                let piece = editor.board.at(&pos).ok().cloned();
+                squares.push(Square::new(
+                    pos,
+                    piece,
+                    Turn::New(editor.color),
+                    false,
+                    false,
+                    false,
+                    false,
+                    None,
+                ));
+            }
+        }
+        let board = Canvas::new(BoardCanvas {
+            squares,
+            mover: None,
+            bottom: self.bottom(),
+            theme: self.board_theme,
+            palette: self.palette,
+            sprites: self.sprites.clone(),
+            marks: Vec::new(),
+            arrows: Vec::new(),
+            cursor: self.cursor,
+            moving: None,
+        })
+        .width(Length::Fill)
+        .height(Length::Fill);
+
+        let castling = editor.board.castling();
+        let rights = [
+            (Color::White, Direction::Right, "White O-O"),
+            (Color::White, Direction::Left, "White O-O-O"),
+            (Color::Black, Direction::Right, "Black O-O"),
+            (Color::Black, Direction::Left, "Black O-O-O"),
+        ]
+        .into_iter()
+        .fold(
+            Column::new().spacing(5),
+            |column, (color, direction, label)| {
+                column.push(checkbox(
+                    label,
+                    castling.allowed(&color, &direction),
+                    move |allowed| Message::CastlingToggled(color, direction, allowed),
+                ))
+            },
+        );
+
+        // The position is checked as it is set up, and can only be left for a game once valid.
+        let validity = editor.board.validate(&editor.color);
+        let status = match &validity {
+            Ok(()) => Text::new(self.tr("Valid position")),
+            Err(e) => Text::new(e.tr(self.language)).style(theme::Text::Color(HIGHLIGHTED_SQUARE)),
+        };
+        let play = button(self.tr("Play"));
+        let analyse = button(self.tr("Analyse"));
+        let (play, analyse) = match validity {
+            Ok(()) => (
+                play.on_press(Message::PlayPosition),
+                analyse.on_press(Message::AnalysePosition),
+            ),
+            Err(_) => (play, analyse),
+        };
+
+        let panel = Column::new()
+            .spacing(20)
+            .padding(20)
+            .width(300)
+            .push(Text::new(self.tr("Board editor")).size(30))
+            .push(pick_list(
+                Brush::ALL.to_vec(),
+                Some(editor.brush),
+                Message::BrushSelected,
+            ))
+            .push(
+                button(Text::new(
+                    self.fill("{} to move", &[&self.language.color(&editor.color)]),
+                ))
+                .on_press(Message::SwapSideToMove),
+            )
+            .push(rights)
+            .push(pick_list(
+                editor.targets(),
+                Some(Target(editor.board.enpassant())),
+                Message::TargetSelected,
+            ))
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(button(self.tr("Clear")).on_press(Message::ClearBoard))
+                    .push(button(self.tr("Starting position")).on_press(Message::ResetBoard)),
+            )
+            .push(status)
+            .push(
+                Row::new()
+                    .spacing(10)
+                    .push(play)
+                    .push(analyse)
+                    .push(button(self.tr("Cancel")).on_press(Message::CloseEditor)),
+            );
+
+        Row::new()
+            .spacing(10)
+            .padding(10)
+            .height(Length::Fill)
+            .push(board)
+            .push(panel)
+    }
+
+    fn new_game_view(&self, dialog: &NewGame) -> Column<'_, Message, Renderer> {
+        let opponent = Row::new()
+            .decorate()
+            .spacing(20)
+            .push(Text::new(self.tr("Opponent")))
+            .push(pick_list(
+                Opponent::ALL.to_vec(),
+                Some(dialog.opponent),
+                Message::OpponentSelected,
+            ));
+
+        let difficulty = Row::new()
+            .decorate()
+            .spacing(20)
+            .push(Text::new(self.tr("Difficulty")))
+            .push(pick_list(
+                Difficulty::ALL.to_vec(),
+                Some(dialog.difficulty),
+                Message::DifficultySelected,
+            ));
+
+        let time_control = Row::new()
+            .decorate()
+            .spacing(20)
+            .push(Text::new(self.tr("Time control")))
+            .push(pick_list(
+                TimeControl::ALL.to_vec(),
+                Some(dialog.time_control),
+                Message::TimeControlSelected,
+            ))
+            .push(pick_list(
+                Bonus::choices(),
+                Some(dialog.bonus),
+                Message::BonusSelected,
+            ));
+
+        let book = Row::new()
+            .decorate()
+            .spacing(20)
+            .push(Text::new(self.tr("Book moves")))
+            .push(pick_list(
+                BOOK_MOVES.to_vec(),
+                Some(dialog.book_moves),
+                Message::BookMovesSelected,
+            ));
+
+        let buttons = Row::new()
+            .decorate()
+            .spacing(20)
+            .push(button(self.tr("Start")).on_press(Message::StartGame))
+            .push(button(self.tr("Cancel")).on_press(Message::CancelNewGame));
+
+        let column = Column::new()
+            .decorate()
+            .spacing(20)
+            .padding(40)
+            .push(Text::new(self.tr("New game")).size(40))
+            .push(opponent)
+            .push(difficulty)
+            .push(time_control);
+
+        // Book settings only matter once a book has been configured.
+        let column = match self.book {
+            Some(_) => column.push(book),
+            None => column,
+        };
+
+        column.push(buttons)
+    }
+
+    fn game_over_view(&self, outcome: Outcome) -> Column<'_, Message, Renderer> {
+        let headline = match outcome.winner() {
+            Some(color) => self.fill("{} wins", &[&self.language.color(&color)]),
+            None => self.tr("Draw").to_string(),
+        };
+
+        let buttons = Row::new()
+            .decorate()
+            .spacing(20)
+            .push(button(self.tr("New game")).on_press(Message::NewGame))
+            .push(button(self.tr("Rematch")).on_press(Message::Rematch))
+            .push(button(self.tr("Review game")).on_press(Message::ReviewGame))
+            .push(button(self.tr("Show activity")).on_press(Message::ToggleHeatmap))
+            .push(match self.annotating {
+                true => button(self.tr("Analysing game...")),
+                false => button(self.tr("Analyse game")).on_press(Message::AnalyseGame),
+            });
+
+        let column = Column::new()
+            .decorate()
+            .spacing(20)
+            .padding(40)
+            .push(Text::new(headline).size(40))
+            .push(Text::new(outcome.reason(self.language)).size(30))
+            .push(Text::new(outcome.result()).size(30));
+
+        // Accuracy is known once the game has been analysed.
+        let accuracies = self.evaluations().map(|evals| {
+            [Color::White, Color::Black].map(|color| accuracy::accuracy(evals, &color))
+        });
+        let column = match accuracies {
+            Some([Some(white), Some(black)]) => column.push(
+                Text::new(self.fill(
+                    "Accuracy: {} {}% - {} {}%",
+                    &[
+                        &self.language.color(&Color::White),
+                        &format!("{:.0}", white),
+                        &self.language.color(&Color::Black),
+                        &format!("{:.0}", black),
+                    ],
+                ))
+                .size(24),
+            ),
+            _ => column,
+        };
+
+        column.push(buttons)
+    }
+
+    // Evaluations of the positions of the game, as long as they belong to its moves.
+    fn evaluations(&self) -> Option<&[i32]> {
+        self.evaluations
+            .as_deref()
+            .filter(|evals| evals.len() == self.game.moves().len() + 1)
+    }
+
+    fn training_view(&self) -> Option<Row<'_, Message, Renderer>> {
+        let training = self.training.as_ref()?;
+        let puzzle = training.puzzle();
+        let (number, total) = training.position();
+        let record = training.record();
+
+        let task = match training.verdict() {
+            Some(Verdict::Solved) => self.tr("Solved!").to_string(),
+            Some(Verdict::Wrong) => self.tr("Not the right move, try again").to_string(),
+            Some(Verdict::Correct(_)) => self.tr("Correct, keep going").to_string(),
+            None => self.fill(
+                "Find the best move for {}",
+                &[&self.language.color(&self.game.color())],
+            ),
+        };
+
+        let text = self.fill(
+            "Puzzle {}/{}{} - {} - streak {} (best {}), solved {}, failed {}",
+            &[
+                &number,
+                &total,
+                &puzzle
+                    .rating
+                    .map_or(String::new(), |rating| format!(" ({})", rating)),
+                &task,
+                &record.streak,
+                &record.best_streak,
+                &record.solved,
+                &record.failed,
+            ],
+        );
+
+        Some(
+            Row::new()
+                .decorate()
+                .spacing(20)
+                .padding(10)
+                .push(Text::new(text).size(20))
+                .push(button(self.tr("Retry")).on_press(Message::RetryPuzzle))
+                .push(button(self.tr("Next puzzle")).on_press(Message::NextPuzzle))
+                .push(button(self.tr("Stop training")).on_press(Message::StopTraining)),
+        )
+    }
+
+    fn guess_view(&self) -> Option<Row<'_, Message, Renderer>> {
+        let guessing = self.guessing.as_ref()?;
+        let score = guessing.score();
+
+        let task = match guessing.verdict() {
+            None => self.fill(
+                "Guess the move for {}",
+                &[&self.language.color(&guessing.color())],
+            ),
+            Some(verdict) => {
+                let template = match (verdict.game, verdict.engine) {
+                    (true, true) => "Right, {} was played and is the engine's choice",
+                    (true, false) => "Right, {} was played",
+                    (false, true) => "{} was played, but the engine agrees with you",
+                    (false, false) => "Wrong, {} was played",
+                };
+                self.fill(template, &[&self.language.san(&verdict.played)])
+            }
+        };
+        let task = match guessing.finished() {
+            true => self.fill("{} - end of the game", &[&task]),
+            false => task,
+        };
+
+        let text = self.fill(
+            "{} - {} points, {} of {} moves as played",
+            &[&task, &score.points, &score.matches, &score.guesses],
+        );
+
+        Some(
+            Row::new()
+                .decorate()
+                .spacing(20)
+                .padding(10)
+                .push(Text::new(text).size(20))
+                .push(button(self.tr("Stop training")).on_press(Message::StopTraining)),
+        )
+    }
+
+    fn status_view<'a>(&self) -> Option<Row<'a, Message, Renderer>> {
+        if self.thinking.is_some() {
+            return Some(
+                Row::new()
+                    .decorate()
+                    .padding(10)
+                    .push(Text::new(self.tr("Thinking...")).size(30)),
+            );
+        }
+
+        match self.game.status() {
+            Ok(GameStatus::Ongoing) | Err(_) => None,
+            Ok(GameStatus::Check) => Some(
+                Row::new()
+                    .decorate()
+                    .padding(10)
+                    .push(Text::new(self.tr("Check")).size(30)),
+            ),
+            Ok(status @ GameStatus::DrawClaimable(_)) => Some(
+                Row::new()
+                    .decorate()
+                    .spacing(20)
+                    .padding(10)
+                    .push(Text::new(status.tr(self.language)).size(30))
+                    .push(button(self.tr("Claim draw")).on_press(Message::ClaimDraw)),
+            ),
+            Ok(status) => Some(
+                Row::new()
+                    .decorate()
+                    .spacing(20)
+                    .padding(10)
+                    .push(
+                        Text::new(self.fill("Game over: {}", &[&status.tr(self.language)]))
+                            .size(30),
+                    )
+                    .push(button(self.tr("New game")).on_press(Message::NewGame))
+                    .push(button(self.tr("Rematch")).on_press(Message::Rematch))
+                    .push(match self.annotating {
+                        true => button(self.tr("Analysing game...")),
+                        false => button(self.tr("Analyse game")).on_press(Message::AnalyseGame),
+                    }),
+            ),
+        }
+    }
+}
+
+impl Default for Gui {
+    fn default() -> Self {
+        let path = std::env::var("CHESS_DATABASE").unwrap_or(database::DEFAULT_PATH.to_string());
+
+        let (book, book_error) = match std::env::var("CHESS_BOOK").map(|path| Book::open(&path)) {
+            Ok(Ok(book)) => (Some(Arc::new(book)), None),
+            Ok(Err(e)) => (None, Some(e)),
+            Err(_) => (None, None),
+        };
+
+        let settings = Settings::open();
+        let fullscreen = settings.parse("fullscreen").unwrap_or(false);
+        // The geometry is kept as restored until the window reports otherwise.
+        let window_size = settings
+            .parse("window_width")
+            .zip(settings.parse("window_height"));
+        let window_position = settings.parse("window_x").zip(settings.parse("window_y"));
+        let app_theme = settings
+            .get("theme")
+            .and_then(AppTheme::from_name)
+            .unwrap_or(AppTheme::System);
+        let palette = settings
+            .get("palette")
+            .and_then(Palette::from_name)
+            .unwrap_or(Palette::Standard);
+        let language = settings
+            .get("language")
+            .and_then(Language::from_name)
+            .unwrap_or(Language::English);
+
+        let mut gui = Self {
+            game: Game::new(),
+            database: Database::new(&path),
+            explorer_path: path.clone(),
+            recorded: false,
+            #[cfg(feature = "lichess")]
+            lichess_url: String::new(),
+            opponent: Opponent::Human,
+            difficulty: Difficulty::Intermediate,
+            time_control: TimeControl::Unlimited,
+            bonus: Bonus::None,
+            book_moves: match book {
+                Some(_) => 10,
+                None => 0,
+            },
+            book,
+            dialog: None,
+            editor: None,
+            settings,
+            app_theme,
+            language,
+            window_size,
+            window_position,
+            system_dark: matches!(dark_light::detect(), dark_light::Mode::Dark),
+            autoplay: false,
+            autoplay_delay: AUTOPLAY_DELAY,
+            entry: String::new(),
+            entry_error: None,
+            notices: Vec::new(),
+            confirming: false,
+            dismissed: false,
+            thinking: None,
+            animation: None,
+            engine_path: std::env::var("CHESS_ENGINE").ok(),
+            external: Arc::new(Mutex::new(None)),
+            analysing: false,
+            analysis: Arc::new(Mutex::new(None)),
+            stop_analysis: Arc::new(AtomicBool::new(false)),
+            polling: false,
+            panel_open: true,
+            exploring: false,
+            explorer: None,
+            hint: None,
+            evaluation: None,
+            opening: None,
+            training: None,
+            guessing: None,
+            puzzles_path: std::env::var("CHESS_PUZZLES")
+                .unwrap_or(puzzle::DEFAULT_PATH.to_string()),
+            harvested: false,
+            viewing: None,
+            preview: None,
+            flipped: false,
+            auto_flip: false,
+            fullscreen,
+            board_theme: BoardTheme::Brown,
+            palette,
+            // The bundled sets always load.
+            sprites: Arc::new(Sprites::load(&PieceSet::Cburnett).unwrap_or_default()),
+            piece_set: PieceSet::Cburnett,
+            marks: HashMap::new(),
+            cursor: None,
+            overlay: false,
+            heatmap: None,
+            annotating: false,
+            evaluations: None,
+            reviewing: false,
+            judgements: HashMap::new(),
+            takeback: None,
+        };
+
+        if let Some(e) = book_error {
+            gui.notify(gui.fill("Could not open the opening book: {}", &[&e]));
+        }
+
+        gui
+    }
+}
+
+// Runs blocking work on its own thread so that the executor stays responsive.
+#[cfg(not(target_arch = "wasm32"))]
+fn background<T, F>(work: F) -> impl Future<Output = Option<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    let (sender, receiver) = oneshot::channel();
+    thread::spawn(move || sender.send(work()));
+
+    async move { receiver.await.ok() }
+}
+
+// The browser has a single thread, so the work runs once the executor gets to it.
+#[cfg(target_arch = "wasm32")]
+fn background<T, F>(work: F) -> impl Future<Output = Option<T>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> T + Send + 'static,
+{
+    async move { Some(work()) }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+async fn sleep(duration: Duration) {
+    background(move || thread::sleep(duration)).await;
+}
+
+#[cfg(target_arch = "wasm32")]
+async fn sleep(duration: Duration) {
+    gloo_timers::future::sleep(duration).await
+}
+
+// Native file dialogs, of which the browser has none.
+#[cfg(feature = "native-dialogs")]
+fn open_file(filter: &str, extensions: &[&str]) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter(filter, extensions)
+        .pick_file()
+}
+
+#[cfg(feature = "native-dialogs")]
+fn open_folder() -> Option<PathBuf> {
+    rfd::FileDialog::new().pick_folder()
+}
+
+#[cfg(feature = "native-dialogs")]
+fn save_file(filter: &str, extensions: &[&str], name: &str) -> Option<PathBuf> {
+    rfd::FileDialog::new()
+        .add_filter(filter, extensions)
+        .set_file_name(name)
+        .save_file()
+}
+
+#[cfg(not(feature = "native-dialogs"))]
+fn open_file(_: &str, _: &[&str]) -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(feature = "native-dialogs"))]
+fn open_folder() -> Option<PathBuf> {
+    None
+}
+
+#[cfg(not(feature = "native-dialogs"))]
+fn save_file(_: &str, _: &[&str], _: &str) -> Option<PathBuf> {
+    None
+}
+
+// Numbers a line of moves in SAN starting at the given ply.
+fn numbered(ply: usize, moves: Vec<String>) -> String {
+    moves
+        .into_iter()
+        .enumerate()
+        .map(|(i, san)| match ((ply + i) % 2, i) {
+            (0, _) => format!("{}. {}", (ply + i) / 2 + 1, san),
+            (_, 0) => format!("{}... {}", (ply + i) / 2 + 1, san),
+            _ => san,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn poll() -> Command<Message> {
+    Command::perform(sleep(POLL), |_| Message::AnalysisTick)
+}
+
+fn frame() -> Command<Message> {
+    Command::perform(sleep(FRAME), |_| Message::Tick(Instant::now()))
+}
+
+impl Application for Gui {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Theme = Theme;
+    type Flags = ();
+
+    fn new(_flags: ()) -> (Self, Command<Message>) {
+        // A PGN file given on the command line is replayed for review.
+        let mut gui = Self::default();
+
+        match std::env::args().nth(1).map(|path| Pgn::load(&path)) {
+            Some(Ok(pgn)) => gui.load(pgn.game),
+            Some(Err(e)) => gui.notify(e),
+            None => (),
+        }
+
+        // The size and position are restored as the window is created, fullscreen only after.
+        let command = match gui.fullscreen {
+            true => window::change_mode(window::Mode::Fullscreen),
+            false => Command::none(),
+        };
+
+        (gui, command)
+    }
+
+    fn title(&self) -> String {
+        "Chess".to_string()
+    }
+
+    fn theme(&self) -> Theme {
+        match (self.app_theme, self.system_dark) {
+            (AppTheme::Dark, _) | (AppTheme::System, true) => Theme::Dark,
+            (AppTheme::Light, _) | (AppTheme::System, false) => Theme::Light,
+        }
+    }
+
+    fn update(&mut self, msg: Message) -> Command<Message> {
+        let pos = match msg {
+            Message::Move(pos) => match &mut self.editor {
+                Some(editor) => {
+                    editor.paint(&pos);
+                    return Command::none();
+                }
+                None => pos,
+            },
+            Message::AbandonGame => {
+                let playing = !self.game.moves().is_empty()
+                    && self.game.outcome().is_none()
+                    && !self.in_training();
+                match playing {
+                    true => self.confirming = true,
+                    false => return self.update(Message::NewGame),
+                }
+                return Command::none();
+            }
+            Message::NewGame => {
+                self.confirming = false;
+                self.dialog = Some(NewGame {
+                    opponent: self.opponent,
+                    difficulty: self.difficulty,
+                    time_control: self.time_control,
+                    bonus: self.bonus,
+                    book_moves: self.book_moves,
+                });
+                return Command::none();
+            }
+            Message::OpponentSelected(opponent) => {
+                if let Some(dialog) = &mut self.dialog {
+                    dialog.opponent = opponent;
+                }
+                return Command::none();
+            }
+            Message::DifficultySelected(difficulty) => {
+                if let Some(dialog) = &mut self.dialog {
+                    dialog.difficulty = difficulty;
+                }
+                return Command::none();
+            }
+            Message::TimeControlSelected(time_control) => {
+                if let Some(dialog) = &mut self.dialog {
+                    dialog.time_control = time_control;
+                }
+                return Command::none();
+            }
+            Message::BonusSelected(bonus) => {
+                if let Some(dialog) = &mut self.dialog {
+                    dialog.bonus = bonus;
+                }
+                return Command::none();
+            }
+            Message::BookMovesSelected(book_moves) => {
+                if let Some(dialog) = &mut self.dialog {
+                    dialog.book_moves = book_moves;
+                }
+                return Command::none();
+            }
+            Message::StartGame => {
+                if let Some(dialog) = self.dialog.take() {
+                    self.opponent = dialog.opponent;
+                    self.difficulty = dialog.difficulty;
+                    self.time_control = dialog.time_control;
+                    self.bonus = dialog.bonus;
+                    self.book_moves = dialog.book_moves;
+                }
+                self.load(Game::new());
+                self.game.set_clock(self.time_control, self.bonus);
+                self.thinking = None;
+                self.animation = None;
+                return self.refresh();
+            }
+            Message::Rematch => {
+                // The players trade places, so the board turns around with them.
+                self.opponent = match self.opponent {
+                    Opponent::Computer(color) => Opponent::Computer(color.opposite()),
+                    opponent => opponent,
+                };
+                if self.opponent != Opponent::Spectate {
+                    self.flipped = !self.flipped;
+                }
+                self.dialog = None;
+                return self.update(Message::StartGame);
+            }
+            Message::CancelNewGame => {
+                self.confirming = false;
+                self.dialog = None;
+                return Command::none();
+            }
+            Message::ClockTick => {
+                // Otherwise the tick only redraws the running clock.
+                if self.game.adjudicate_flag().is_none() {
+                    return Command::none();
+                }
+                self.thinking = None;
+                self.animation = None;
+                self.record();
+                return self.refresh();
+            }
+            Message::NoticeTick => {
+                self.notices
+                    .retain(|(_, posted)| posted.elapsed() < NOTICE_DURATION);
+                return Command::none();
+            }
+            Message::DismissNotice(index) => {
+                if index < self.notices.len() {
+                    self.notices.remove(index);
+                }
+                return Command::none();
+            }
+            Message::Resign => {
+                let color = self.player();
+                if let Err(e) = self.game.resign(&color) {
+                    self.notify(e);
+                }
+                self.record();
+                return self.refresh();
+            }
+            Message::OfferDraw => {
+                let color = self.player();
+                if let Err(e) = self.game.offer_draw(&color) {
+                    self.notify(e);
+                    return Command::none();
+                }
+
+                // The engine answers at once, a human opponent in their own time.
+                if self.opponent.engine_plays(&color.opposite()) {
+                    match self.accepts_draw(&color.opposite()) {
+                        true => return self.update(Message::AcceptDraw),
+                        false => {
+                            self.game.decline_draw();
+                            self.notify(self.tr("The computer declines the draw"));
+                        }
+                    }
+                }
+                return self.refresh();
+            }
+            Message::AcceptDraw => {
+                if let Err(e) = self.game.accept_draw() {
+                    self.notify(e);
+                }
+                self.thinking = None;
+                self.record();
+                return self.refresh();
+            }
+            Message::DeclineDraw => {
+                self.game.decline_draw();
+                return Command::none();
+            }
+            Message::ReviewGame => {
+                self.dismissed = true;
+                self.viewing = Some(0);
+                return Command::none();
+            }
+            Message::EngineMoved(hash, reply) => {
+                // Replies to a position that has since been left behind are stale.
+                if self.thinking != Some(hash) {
+                    return Command::none();
+                }
+                self.thinking = None;
+
+                let lan = match reply {
+                    Ok(Some(lan)) => lan,
+                    Ok(None) => return Command::none(),
+                    Err(e) => {
+                        self.notify(e);
+                        return Command::none();
+                    }
+                };
+
+                return match self.play_animated(lan) {
+                    Some(animation) => Command::batch([animation, self.refresh()]),
+                    None => Command::none(),
+                };
+            }
+            Message::Tick(now) => {
+                if self.animation.as_ref().is_some_and(|a| a.finished(now)) {
+                    self.animation = None;
+                }
+                return match self.animation {
+                    Some(_) => frame(),
+                    None => Command::none(),
+                };
+            }
+            Message::Hint => {
+                // In a puzzle the hint is the next move of its solution.
+                if let Some(training) = &mut self.training {
+                    let hash = self.game.board().hash(&self.game.color());
+                    self.hint = training.reveal().map(|lan| (hash, lan));
+                    return Command::none();
+                }
+
+                let color = self.game.color();
+                if self.opponent.engine_plays(&color) || self.game.outcome().is_some() {
+                    return Command::none();
+                }
+
+                let board = self.game.board().clone();
+                let hash = board.hash(&color);
+                let language = self.language;
+
+                return Command::perform(
+                    background(move || ai::hint(board, color).map_err(|e| e.tr(language))),
+                    move |reply| {
+                        Message::HintReady(hash, reply.unwrap_or(Err("Engine stopped".to_string())))
+                    },
+                );
+            }
+            Message::HintReady(hash, reply) => {
+                match reply {
+                    Ok(lan) => self.hint = lan.map(|lan| (hash, lan)),
+                    Err(e) => self.notify(e),
+                }
+                return Command::none();
+            }
+            Message::Evaluated(hash, reply) => {
+                // Estimates of positions since left behind are stale.
+                if self.game.board().hash(&self.game.color()) != hash {
+                    return Command::none();
+                }
+
+                match reply {
+                    Ok(score) => self.evaluation = Some((hash, score)),
+                    Err(e) => self.notify(e),
+                }
+                return Command::none();
+            }
+            Message::Harvested(reply) => {
+                if let Err(e) = reply {
+                    self.notify(e);
+                }
+                return Command::none();
+            }
+            Message::LoadPuzzles => {
+                let path = open_file("lichess puzzles", &["csv"]);
+
+                let puzzles = match path.map(|path| puzzle::load(&path.to_string_lossy())) {
+                    Some(Ok(puzzles)) => puzzles,
+                    Some(Err(e)) => {
+                        self.notify(e);
+                        return Command::none();
+                    }
+                    None => return Command::none(),
+                };
+
+                self.training = Training::new(puzzles);
+                if let Some(game) = self.training.as_mut().map(|training| training.start()) {
+                    self.start_puzzle(game);
+                }
+                return Command::none();
+            }
+            Message::NextPuzzle => {
+                if let Some(game) = self
+                    .training
+                    .as_mut()
+                    .map(|training| training.next_puzzle())
+                {
+                    self.start_puzzle(game);
+                }
+                return Command::none();
+            }
+            Message::RetryPuzzle => {
+                if let Some(game) = self.training.as_mut().map(|training| training.start()) {
+                    self.start_puzzle(game);
+                }
+                return Command::none();
+            }
+            Message::GuessMoves => {
+                let path = open_file("PGN", &["pgn"]);
+
+                let pgn = match path.map(|path| Pgn::load(&path.to_string_lossy())) {
+                    Some(Ok(pgn)) => pgn,
+                    Some(Err(e)) => {
+                        self.notify(e);
+                        return Command::none();
+                    }
+                    None => return Command::none(),
+                };
+
+                // The winner's moves are the ones worth guessing, white's after a draw.
+                let color = pgn
+                    .game
+                    .outcome()
+                    .and_then(|outcome| outcome.winner())
+                    .unwrap_or(Color::White);
+                let mut guessing = match GuessTheMove::new(&pgn.game, color) {
+                    Ok(guessing) => guessing,
+                    Err(e) => {
+                        self.notify(e);
+                        return Command::none();
+                    }
+                };
+
+                match guessing.start() {
+                    Ok(game) => self.load(game),
+                    Err(e) => {
+                        self.notify(e);
+                        return Command::none();
+                    }
+                }
+                self.guessing = Some(guessing);
+                self.opponent = Opponent::Human;
+                // The guessing side plays from the bottom of the board.
+                self.flipped = false;
+                self.flipped = self.bottom() != color;
+                self.thinking = None;
+                self.animation = None;
+                self.hint = None;
+                self.evaluation = None;
+                return Command::none();
+            }
+            Message::Guessed(hash, guess, reply) => {
+                // Guesses in a training since stopped are of no interest.
+                if self.thinking != Some(hash) {
+                    return Command::none();
+                }
+                self.thinking = None;
+
+                let engine = match reply {
+                    Ok(lan) => lan,
+                    Err(e) => {
+                        self.notify(e);
+                        None
+                    }
+                };
+                let result = match self.guessing.as_mut() {
+                    Some(guessing) => guessing.guess(&mut self.game, guess, engine).map(|_| ()),
+                    None => return Command::none(),
+                };
+                if let Err(e) = result {
+                    self.notify(e);
+                }
+                return self.refresh();
+            }
+            Message::StopTraining => {
+                self.load(Game::new());
+                return self.refresh();
+            }
+            Message::ToggleReview => {
+                self.reviewing = !self.reviewing;
+                return Command::none();
+            }
+            Message::Reviewed(index, san, reply) => {
+                // The verdict is dropped if the move has been taken back meanwhile.
+                if self.game.moves().get(index) != Some(&san) {
+                    return Command::none();
+                }
+
+                match reply {
+                    Ok(Some(judgement)) => {
+                        self.judgements.insert(index, judgement);
+                        self.takeback = Some(index);
+                    }
+                    Ok(None) => (),
+                    Err(e) => self.notify(e),
+                }
+                return Command::none();
+            }
+            Message::TakeBack => {
+                let index = match self.takeback.take() {
+                    Some(index) => index,
+                    None => return Command::none(),
+                };
+
+                // The engine's reply, if already played, goes back along with the move.
+                while self.game.moves().len() > index {
+                    if let Err(e) = self.game.revert() {
+                        self.notify(e);
+                        break;
+                    }
+                }
+                self.judgements.retain(|i, _| *i < index);
+                self.thinking = None;
+                self.animation = None;
+                return self.refresh();
+            }
+            Message::Undo => {
+                let mut undone = 0;
+                while !self.game.moves().is_empty() {
+                    if let Err(e) = self.game.revert() {
+                        self.notify(e);
+                        break;
+                    }
+                    undone += 1;
+
+                    // Analysis steps back a ply at a time, games a full move, against the engine
+                    // as far as the user's own last move.
+                    let done = match self.opponent {
+                        _ if self.analysing => true,
+                        Opponent::Computer(engine) => engine != self.game.color(),
+                        Opponent::Human | Opponent::Spectate => undone == 2,
+                    };
+                    if done {
+                        break;
+                    }
+                }
+
+                let moves = self.game.moves().len();
+                self.judgements.retain(|i, _| *i < moves);
+                self.takeback = self.takeback.filter(|i| *i < moves);
+                self.thinking = None;
+                self.animation = None;
+                self.hint = None;
+                self.viewing = None;
+                self.preview = None;
+                return self.refresh();
+            }
+            Message::ToggleAnalysis => {
+                self.analysing = !self.analysing;
+                self.preview = None;
+                return self.analyse();
+            }
+            Message::AnalysisFinished(reply) => {
+                if let Err(e) = reply {
+                    self.notify(e);
+                }
+                return Command::none();
+            }
+            Message::AnalysisTick => {
+                // Polling goes on for as long as analysis is switched on.
+                self.polling = self.analysing;
+                return match self.polling {
+                    true => poll(),
+                    false => Command::none(),
+                };
+            }
+            Message::TogglePanel => {
+                self.panel_open = !self.panel_open;
+                return Command::none();
+            }
+            Message::Flip => {
+                self.flipped = !self.flipped;
+                return Command::none();
+            }
+            Message::MoveTyped(entry) => {
+                self.entry = entry;
+                self.entry_error = None;
+                return Command::none();
+            }
+            Message::SubmitMove => {
+                // Typed moves go into the game itself, branching off the position shown in analysis.
+                self.preview = None;
+                if let Some(moves) = self.viewing.take() {
+                    if self.analysing {
+                        self.branch(moves);
+                    }
+                }
+                if self.locked() {
+                    self.entry_error = Some(self.tr("it is not your move").to_string());
+                    return Command::none();
+                }
+
+                let played = self.game.moves().len();
+                let before = self.game.board().clone();
+                let color = self.game.color();
+
+                let result = self
+                    .read_move(&self.entry)
+                    .and_then(|lan| self.game.play_move(&lan.from, &lan.to, lan.promotion));
+                if let Err(e) = result {
+                    self.entry_error = Some(e.tr(self.language));
+                    return Command::none();
+                }
+                self.record();
+                self.entry.clear();
+
+                return self.user_moved(played, before, color);
+            }
+            Message::EditBoard => {
+                let color = match self.viewing {
+                    Some(moves) => self.game_at(moves).color(),
+                    None => self.game.color(),
+                };
+                self.editor = Some(Editor {
+                    board: self.shown_board(),
+                    color,
+                    brush: Brush::Piece('P'),
+                });
+                return Command::none();
+            }
+            Message::BrushSelected(brush) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.brush = brush;
+                }
+                return Command::none();
+            }
+            Message::SwapSideToMove => {
+                // A target is only ever left by a pawn of the side not to move.
+                if let Some(editor) = &mut self.editor {
+                    editor.color = editor.color.opposite();
+                    editor.board.set_enpassant(None);
+                }
+                return Command::none();
+            }
+            Message::CastlingToggled(color, direction, allowed) => {
+                if let Some(editor) = &mut self.editor {
+                    let mut castling = editor.board.castling();
+                    castling.set(&color, &direction, allowed);
+                    editor.board.set_castling(castling);
+                }
+                return Command::none();
+            }
+            Message::TargetSelected(Target(target)) => {
+                if let Some(editor) = &mut self.editor {
+                    editor.board.set_enpassant(target);
+                }
+                return Command::none();
+            }
+            Message::ClearBoard => {
+                if let Some(editor) = &mut self.editor {
+                    editor.board = Board::empty();
+                }
+                return Command::none();
+            }
+            Message::ResetBoard => {
+                if let Some(editor) = &mut self.editor {
+                    editor.board = Board::new();
+                    editor.color = Color::White;
+                }
+                return Command::none();
+            }
+            Message::PlayPosition | Message::AnalysePosition => {
+                let analysing = matches!(msg, Message::AnalysePosition);
+                let editor = match self.editor.take() {
+                    Some(editor) => editor,
+                    None => return Command::none(),
+                };
+                // Going through FEN validates the position and starts it without a history.
+                match Game::from_fen(&editor.board.to_fen(&editor.color, 1)) {
+                    Ok(game) => self.load(game),
+                    Err(e) => {
+                        self.notify(e);
+                        self.editor = Some(editor);
+                        return Command::none();
+                    }
+                }
+                self.thinking = None;
+                self.animation = None;
+                self.hint = None;
+                self.preview = None;
+                self.marks.clear();
+
+                if analysing {
+                    self.opponent = Opponent::Human;
+                    self.analysing = true;
+                    return Command::batch([self.refresh(), self.analyse()]);
+                }
+                self.game.set_clock(self.time_control, self.bonus);
+                return self.refresh();
+            }
+            Message::CloseEditor => {
+                self.editor = None;
+                return Command::none();
+            }
+            Message::WindowResized(width, height) => {
+                if !self.fullscreen {
+                    self.window_size = Some((width, height));
+                }
+                return Command::none();
+            }
+            Message::WindowMoved(x, y) => {
+                if !self.fullscreen {
+                    self.window_position = Some((x, y));
+                }
+                return Command::none();
+            }
+            Message::CloseRequested => {
+                // With the window about to go, an error can only be printed.
+                if let Err(e) = self.save_window() {
+                    eprintln!("{}", e);
+                }
+                return window::close();
+            }
+            Message::ToggleFullscreen => {
+                self.fullscreen = !self.fullscreen;
+                return window::change_mode(match self.fullscreen {
+                    true => window::Mode::Fullscreen,
+                    false => window::Mode::Windowed,
+                });
+            }
+            Message::Mark(mark) => {
+                let hash = self.shown_hash();
+                let marks = self.marks.entry(hash).or_default();
+                match marks.iter().position(|drawn| *drawn == mark) {
+                    Some(i) => {
+                        marks.remove(i);
+                    }
+                    None => marks.push(mark),
+                }
+                return Command::none();
+            }
+            Message::MoveCursor(right, up) => {
+                let (file, rank) = match self.bottom() {
+                    Color::White => (right, up),
+                    Color::Black => (-right, -up),
+                };
+                // The first press only shows the cursor, on the selected piece or else the king.
+                self.cursor = Some(match self.cursor {
+                    Some(pos) => pos.offset(file, rank).unwrap_or(pos),
+                    None => self.cursor_start(),
+                });
+                return Command::none();
+            }
+            // The board is hidden behind the new game dialog and the confirmation.
+            Message::ConfirmCursor if self.dialog.is_some() || self.confirming => {
+                return Command::none()
+            }
+            Message::ConfirmCursor => match self.cursor {
+                Some(pos) => return self.update(Message::Move(pos)),
+                None => {
+                    self.cursor = Some(self.cursor_start());
+                    return Command::none();
+                }
+            },
+            Message::ClearSelection => {
+                self.game.reset_turn();
+                return Command::none();
+            }
+            Message::ToggleAutoFlip => {
+                self.auto_flip = !self.auto_flip;
+                return Command::none();
+            }
+            Message::BoardThemeSelected(theme) => {
+                self.board_theme = theme;
+                return Command::none();
+            }
+            Message::PaletteSelected(palette) => {
+                self.palette = palette;
+                if let Err(e) = self.settings.set("palette", palette) {
+                    self.notify(e);
+                }
+                return Command::none();
+            }
+            Message::LanguageSelected(language) => {
+                self.language = language;
+                if let Err(e) = self.settings.set("language", language) {
+                    self.notify(e);
+                }
+                return Command::none();
+            }
+            Message::AppThemeSelected(theme) => {
+                self.app_theme = theme;
+                if let Err(e) = self.settings.set("theme", theme) {
+                    self.notify(e);
+                }
+                return Command::none();
+            }
+            Message::PieceSetSelected(set) => {
+                match Sprites::load(&set) {
+                    Ok(sprites) => {
+                        self.sprites = Arc::new(sprites);
+                        self.piece_set = set;
+                    }
+                    Err(e) => self.notify(e),
+                }
+                return Command::none();
+            }
+            Message::LoadPieceSet => {
+                return match open_folder() {
+                    Some(dir) => self.update(Message::PieceSetSelected(PieceSet::Custom(dir))),
+                    None => Command::none(),
+                };
+            }
+            Message::ToggleOverlay => {
+                self.overlay = !self.overlay;
+                return Command::none();
+            }
+            Message::ToggleHeatmap => {
+                self.heatmap = match self.heatmap {
+                    Some(_) => None,
+                    None => Some(Heatmap::new(&self.game)),
+                };
+                self.dismissed = true;
+                return Command::none();
+            }
+            Message::ToggleExplorer => {
+                self.exploring = !self.exploring;
+                return self.explore();
+            }
+            Message::ImportDatabase => {
+                let path = open_file("PGN", &["pgn"]);
+
+                if let Some(path) = path {
+                    self.explorer_path = path.to_string_lossy().to_string();
+                    self.exploring = true;
+                    return self.explore();
+                }
+                return Command::none();
+            }
+            Message::ExplorerBuilt(path, reply) => {
+                // An index of a file since replaced by another is of no use.
+                if path != self.explorer_path {
+                    return Command::none();
+                }
+
+                match reply {
+                    Ok(explorer) => self.explorer = Some(explorer),
+                    Err(e) => self.notify(e),
+                }
+                return Command::none();
+            }
+            Message::LoadReference(index, moves) => {
+                match Database::new(&self.explorer_path).load(index) {
+                    Ok(pgn) => {
+                        self.load(pgn.game.clone());
+                        self.annotations(&pgn);
+                        self.viewing = Some(moves).filter(|moves| *moves < self.game.moves().len());
+                    }
+                    Err(e) => self.notify(e),
+                }
+                return self.refresh();
+            }
+            Message::Jump(moves) => {
+                self.autoplay = false;
+                self.viewing = moves.filter(|moves| *moves < self.game.moves().len());
+                self.preview = None;
+                self.game.reset_turn();
+                return Command::none();
+            }
+            Message::StepBack => {
+                let current = self.viewing.unwrap_or(self.game.moves().len());
+                return match current {
+                    0 => Command::none(),
+                    current => self.update(Message::Jump(Some(current - 1))),
+                };
+            }
+            Message::StepForward => {
+                return match self.viewing {
+                    Some(moves) => self.update(Message::Jump(Some(moves + 1))),
+                    None => Command::none(),
+                };
+            }
+            Message::ToggleAutoplay => {
+                self.autoplay = !self.autoplay && !self.game.moves().is_empty();
+                // Playback starts over once the end has been reached.
+                if self.autoplay && self.viewing.is_none() {
+                    self.viewing = Some(0);
+                    self.preview = None;
+                    self.game.reset_turn();
+                }
+                return Command::none();
+            }
+            Message::AutoplayDelayChanged(delay) => {
+                self.autoplay_delay = delay;
+                return Command::none();
+            }
+            Message::AutoplayTick => {
+                let moves = match self.viewing {
+                    Some(moves) if self.autoplay => moves,
+                    _ => {
+                        self.autoplay = false;
+                        return Command::none();
+                    }
+                };
+
+                // The last move leads back to the game itself, which ends the playback.
+                let before = self.game_at(moves);
+                let next = moves + 1;
+                self.viewing = (next < self.game.moves().len()).then_some(next);
+                self.autoplay = self.viewing.is_some();
+
+                let lan = self
+                    .game
+                    .moves()
+                    .get(moves)
+                    .and_then(|san| San::parse(san).ok())
+                    .and_then(|san| {
+                        san.resolve(&mut before.board().clone(), &before.color())
+                            .ok()
+                    });
+                let piece = lan.and_then(|(from, _, _)| before.at(&from).ok().cloned());
+                return match (lan, piece) {
+                    (Some((from, to, promotion)), Some(piece)) => {
+                        self.animation = Some(Animation {
+                            lan: Lan::new(from, to, promotion),
+                            piece,
+                            start: Instant::now(),
+                        });
+                        frame()
+                    }
+                    _ => Command::none(),
+                };
+            }
+            Message::Preview(line) => {
+                let hash = self.game.board().hash(&self.game.color());
+                let analysis = self
+                    .analysis
+                    .lock()
+                    .ok()
+                    .and_then(|analysis| analysis.clone());
+
+                self.preview = match (line, analysis) {
+                    (Some(i), Some((analysed, _, analysis))) if analysed == hash => {
+                        analysis.lines.get(i).map(|line| (hash, line.pv.clone()))
+                    }
+                    _ => None,
+                };
+                return Command::none();
+            }
+            Message::ClaimDraw => {
+                if let Err(e) = self.game.claim_draw() {
+                    self.notify(e);
+                }
+                self.record();
+                return self.refresh();
+            }
+            Message::AnalyseGame => {
+                if self.annotating {
+                    return Command::none();
+                }
+                self.annotating = true;
+
+                let game = self.game.clone();
+                let language = self.language;
+                let work = move || {
+                    annotation::annotate(&game, Vec::new())
+                        .map(|pgn| pgn.to_string())
+                        .map_err(|e| e.tr(language))
+                };
+
+                return Command::perform(background(work), |reply| {
+                    Message::GameAnalysed(reply.unwrap_or(Err("Engine stopped".to_string())))
+                });
+            }
+            Message::GameAnalysed(reply) => {
+                self.annotating = false;
+
+                let pgn = match reply {
+                    Ok(pgn) => pgn,
+                    Err(e) => {
+                        self.notify(e);
+                        return Command::none();
+                    }
+                };
+                // The judgements come back as glyphs on the moves, to be shown in the list and
+                // graph, unless the game has gone on meanwhile.
+                let annotated = Pgn::parse(&pgn)
+                    .ok()
+                    .filter(|annotated| annotated.game.moves() == self.game.moves());
+                if let Some(annotated) = annotated {
+                    self.annotations(&annotated);
+                }
+
+                let path = save_file("PGN", &["pgn"], "analysis.pgn");
+
+                if let Some(path) = path {
+                    if let Err(e) = std::fs::write(path, pgn) {
+                        self.notify(e);
+                    }
+                }
+                return Command::none();
+            }
+            Message::ExportImage => {
+                let path = save_file("PNG image", &["png"], "board.png");
+
+                if let Some(path) = path {
+                    if let Err(e) = png::save(self.game.board(), true, &path) {
+                        self.notify(e);
+                    }
+                }
+                return Command::none();
+            }
+            Message::CopyFen => return clipboard::write(self.game.to_fen()),
+            Message::PasteFen => return clipboard::read(Message::FenPasted),
+            Message::FenPasted(fen) => {
+                match fen.as_deref().map(Game::from_fen) {
+                    Some(Ok(game)) => self.load(game),
+                    Some(Err(e)) => self.notify(e),
+                    None => (),
+                }
+                return self.refresh();
+            }
+            #[cfg(feature = "lichess")]
+            Message::LichessUrl(url) => {
+                self.lichess_url = url;
+                return Command::none();
+            }
+            #[cfg(feature = "lichess")]
+            Message::ImportLichess => {
+                let url = self.lichess_url.clone();
+                let language = self.language;
+                return Command::perform(
+                    async move { lichess::download(&url).map_err(|e| e.tr(language)) },
+                    Message::LichessImported,
+                );
+            }
+            #[cfg(feature = "lichess")]
+            Message::LichessImported(pgn) => {
+                match pgn.and_then(|pgn| Pgn::parse(&pgn).map_err(|e| e.tr(self.language))) {
+                    Ok(pgn) => self.load(pgn.game),
+                    Err(e) => self.notify(e),
+                }
+                return self.refresh();
+            }
+        };
+
+        // A click on the previewed board returns to the game, as does one on an earlier position
+        // unless analysis branches off from there.
+        if self.preview.take().is_some() {
+            return Command::none();
+        }
+        if let Some(moves) = self.viewing.take() {
+            if !self.analysing {
+                return Command::none();
+            }
+            self.branch(moves);
+        }
+
+        if self.locked() {
+            return Command::none();
+        }
+
+        let played = self.game.moves().len();
+        let before = self.game.board().clone();
+        let color = self.game.color();
+
+        if let Err(e) = self.game.advance(&pos, None) {
+            // Moves ignoring a check are told so rather than which rule they break.
+            let in_check = self.game.board().in_check(&color).unwrap_or(false);
+            match e {
+                CatchAllError::IllegalMove(IllegalMoveReason::LeavesKingInCheck) if in_check => {
+                    self.notify(self.tr("You are in check"))
+                }
+                e => self.notify(e),
+            }
+            self.game.reset_turn();
+        }
+        self.record();
+
+        self.user_moved(played, before, color)
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        // Keys typed into the move entry are not taken for shortcuts.
+        let keys = subscription::events_with(|event, status| match event {
+            _ if status == event::Status::Captured => None,
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code,
+                modifiers,
+            }) if modifiers.command() => match key_code {
+                keyboard::KeyCode::C => Some(Message::CopyFen),
+                keyboard::KeyCode::V => Some(Message::PasteFen),
+                _ => None,
+            },
+            // Shift with the arrows walks the board, while the arrows alone step through the moves.
+            Event::Keyboard(keyboard::Event::KeyPressed {
+                key_code,
+                modifiers,
+            }) if modifiers.shift() => match key_code {
+                keyboard::KeyCode::Left => Some(Message::MoveCursor(-1, 0)),
+                keyboard::KeyCode::Right => Some(Message::MoveCursor(1, 0)),
+                keyboard::KeyCode::Up => Some(Message::MoveCursor(0, 1)),
+                keyboard::KeyCode::Down => Some(Message::MoveCursor(0, -1)),
+                _ => None,
+            },
+            Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }) => match key_code {
+                keyboard::KeyCode::Left => Some(Message::StepBack),
+                keyboard::KeyCode::Right => Some(Message::StepForward),
+                keyboard::KeyCode::Home => Some(Message::Jump(Some(0))),
+                keyboard::KeyCode::End => Some(Message::Jump(None)),
+                keyboard::KeyCode::F11 => Some(Message::ToggleFullscreen),
+                keyboard::KeyCode::Enter | keyboard::KeyCode::Space => Some(Message::ConfirmCursor),
+                keyboard::KeyCode::Escape => Some(Message::ClearSelection),
+                _ => None,
+            },
+            Event::Window(window::Event::Resized { width, height }) => {
+                Some(Message::WindowResized(width, height))
+            }
+            Event::Window(window::Event::Moved { x, y }) => Some(Message::WindowMoved(x, y)),
+            Event::Window(window::Event::CloseRequested) => Some(Message::CloseRequested),
+            _ => None,
+        });
+
+        // Ticks only while a clock is running or messages wait to expire.
+        let clock = match self.game.clock().and_then(|clock| clock.running()) {
+            Some(_) => time::every(CLOCK_TICK).map(|_| Message::ClockTick),
+            None => Subscription::none(),
+        };
+        let notices = match self.notices.is_empty() {
+            true => Subscription::none(),
+            false => time::every(NOTICE_TICK).map(|_| Message::NoticeTick),
+        };
+        let autoplay = match self.autoplay {
+            true => time::every(Duration::from_secs_f32(self.autoplay_delay))
+                .map(|_| Message::AutoplayTick),
+            false => Subscription::none(),
+        };
+
+        Subscription::batch([keys, clock, notices, autoplay])
+    }
+
+    fn view(&self) -> Element<Message> {
+        if let Some(dialog) = &self.dialog {
+            return Container::new(self.new_game_view(dialog))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        if let Some(editor) = &self.editor {
+            return Container::new(self.editor_view(editor))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .into();
+        }
+
+        if self.confirming {
+            return Container::new(self.confirm_view())
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        // Trainings announce their end in their own row instead.
+        let outcome = self.game.outcome().filter(|_| !self.in_training());
+        if let Some(outcome) = outcome.filter(|_| !self.dismissed) {
+            return Container::new(self.game_over_view(outcome))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_x()
+                .center_y()
+                .into();
+        }
+
+        let mut column = Column::new().decorate().push(self.toolbar_view());
+        if let Some(notices) = self.notices_view() {
+            column = column.push(notices);
+        }
+        if let Some(training) = self.training_view() {
+            column = column.push(training);
+        }
+        if let Some(guessing) = self.guess_view() {
+            column = column.push(guessing);
+        }
+        if let Some(status) = self.status_view() {
+            column = column.push(status);
+        }
+        if let Some(offer) = self.draw_offer_view() {
+            column = column.push(offer);
+        }
+        // Captures as of the position shown, which may be an earlier one.
+        let captures = match self.viewing {
+            Some(moves) => material::captures(&self.game_at(moves)),
+            None => material::captures(&self.game),
+        };
+        // The board takes up whatever space the panels beside it leave.
+        let mut row = Row::new()
+            .decorate()
+            .spacing(10)
+            .height(Length::Fill)
+            .push(self.evaluation_view())
+            .push(
+                Column::new()
+                    .spacing(5)
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .push(self.player_view(&captures, self.bottom().opposite()))
+                    .push(self.board_view())
+                    .push(self.player_view(&captures, self.bottom())),
+            );
+        if let Some(moves) = self.moves_view() {
+            row = row.push(moves);
+        }
+        if let Some(analysis) = self.analysis_view() {
+            row = row.push(analysis);
+        }
+        if let Some(explorer) = self.explorer_view() {
+            row = row.push(explorer);
+        }
+        column = column.push(row);
+        if let Some(opening) = self.opening_view() {
+            column = column.push(opening);
+        }
+        column = column
+            .push(self.actions_view())
+            .push(self.status_bar_view());
+
+        Container::new(column)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .into()
     }
 }
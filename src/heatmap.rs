@@ -0,0 +1,62 @@
+use crate::game::Game;
+use crate::piece::Color;
+use crate::position::Position;
+
+use std::collections::HashMap;
+
+// How often each side stood on or attacked every square over the positions of a game.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Heatmap {
+    white: HashMap<Position, usize>,
+    black: HashMap<Position, usize>,
+}
+
+impl Heatmap {
+    // Counts every position of the game, from its end back to the one it started from.
+    pub fn new(game: &Game) -> Self {
+        let mut heatmap = Self::default();
+        let mut game = game.clone();
+
+        heatmap.add(&game);
+        while !game.moves().is_empty() && game.revert().is_ok() {
+            heatmap.add(&game);
+        }
+
+        heatmap
+    }
+
+    // Activity of white and black on the square, relative to the busiest square of either side.
+    pub fn heat(&self, pos: &Position) -> (f32, f32) {
+        let busiest = self
+            .white
+            .values()
+            .chain(self.black.values())
+            .max()
+            .copied()
+            .unwrap_or(0)
+            .max(1) as f32;
+        let count = |counts: &HashMap<Position, usize>| {
+            counts.get(pos).copied().unwrap_or(0) as f32 / busiest
+        };
+
+        (count(&self.white), count(&self.black))
+    }
+
+    fn add(&mut self, game: &Game) {
+        let board = game.board();
+
+        for color in [Color::White, Color::Black] {
+            let counts = match color {
+                Color::White => &mut self.white,
+                Color::Black => &mut self.black,
+            };
+
+            for (pos, attackers) in board.attack_counts(&color) {
+                *counts.entry(pos).or_insert(0) += attackers;
+            }
+            for (pos, _) in board.pieces().filter(|(_, piece)| piece.color() == color) {
+                *counts.entry(*pos).or_insert(0) += 1;
+            }
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use crate::piece::Color;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    English,
+    German,
+}
+
+// Letters of the king, queen, rook, bishop and knight in English SAN.
+const PIECE_LETTERS: [char; 5] = ['K', 'Q', 'R', 'B', 'N'];
+
+// German texts by the English ones they replace. Templates hold "{}" for each value filled in.
+#[rustfmt::skip]
+const GERMAN: &[(&str, &str)] = &[
+    ("White", "Weiß"),
+    ("Black", "Schwarz"),
+    ("Draw", "Remis"),
+    ("{} wins", "{} gewinnt"),
+    ("{} to move", "{} am Zug"),
+    ("{} offers a draw", "{} bietet Remis an"),
+    ("Draw offered by {}", "Remis angeboten von {}"),
+    ("Game over: {}", "Partie beendet: {}"),
+    ("Move {}", "Zug {}"),
+    ("State: {}", "Stand: {}"),
+    ("{} (depth {}) {}", "{} (Tiefe {}) {}"),
+    ("Fifty-move rule: {}/{}", "50-Züge-Regel: {}/{}"),
+    ("Solved!", "Gelöst!"),
+    ("Not the right move, try again", "Nicht der richtige Zug, versuche es nochmal"),
+    ("Correct, keep going", "Richtig, weiter so"),
+    ("Find the best move for {}", "Finde den besten Zug für {}"),
+    ("Puzzle {}/{}{} - {} - streak {} (best {}), solved {}, failed {}",
+     "Aufgabe {}/{}{} - {} - Serie {} (beste {}), gelöst {}, verfehlt {}"),
+    ("Analysing...", "Analysiere..."),
+    ("Board editor", "Stellungseditor"),
+    ("Book moves", "Buchzüge"),
+    ("Check", "Schach"),
+    ("Difficulty", "Spielstärke"),
+    ("Explorer", "Datenbank"),
+    ("Indexing games...", "Partien werden indiziert..."),
+    ("{} games in {}", "{} Partien in {}"),
+    ("{} unreadable games skipped", "{} unlesbare Partien übersprungen"),
+    ("Moves", "Züge"),
+    ("New game", "Neue Partie"),
+    ("No games reached this position", "Keine Partie erreichte diese Stellung"),
+    ("Games reaching this position", "Partien mit dieser Stellung"),
+    ("Show activity", "Aktivität zeigen"),
+    ("Hide activity", "Aktivität ausblenden"),
+    ("Guess the move", "Zug erraten"),
+    ("Guess the move for {}", "Errate den Zug für {}"),
+    ("Right, {} was played and is the engine's choice", "Richtig, {} wurde gespielt und ist die Wahl der Engine"),
+    ("Right, {} was played", "Richtig, {} wurde gespielt"),
+    ("{} was played, but the engine agrees with you", "{} wurde gespielt, aber die Engine stimmt dir zu"),
+    ("Wrong, {} was played", "Falsch, {} wurde gespielt"),
+    ("{} - end of the game", "{} - Ende der Partie"),
+    ("{} - {} points, {} of {} moves as played", "{} - {} Punkte, {} von {} Zügen wie gespielt"),
+    ("Opponent", "Gegner"),
+    ("The game in progress will be lost.", "Die laufende Partie geht verloren."),
+    ("Thinking...", "Denke nach..."),
+    ("Time control", "Bedenkzeit"),
+    ("Valid position", "Gültige Stellung"),
+    ("Abandon game", "Partie abbrechen"),
+    ("Accept", "Annehmen"),
+    ("Analyse game", "Partie analysieren"),
+    ("Analyse", "Analysieren"),
+    ("Analysing game...", "Analysiere Partie..."),
+    ("Back to game", "Zurück zur Partie"),
+    ("Cancel", "Abbrechen"),
+    ("Check blunders", "Fehler prüfen"),
+    ("Claim draw", "Remis beanspruchen"),
+    ("Clear", "Leeren"),
+    ("Copy FEN", "FEN kopieren"),
+    ("Decline", "Ablehnen"),
+    ("Dismiss", "Schließen"),
+    ("Edit board", "Stellung bearbeiten"),
+    ("Export image", "Bild exportieren"),
+    ("Fixed board", "Brett festhalten"),
+    ("Flip board", "Brett drehen"),
+    ("Fullscreen", "Vollbild"),
+    ("Hide attacks", "Angriffe ausblenden"),
+    ("Hint", "Tipp"),
+    ("Import PGN", "PGN importieren"),
+    ("Import", "Importieren"),
+    ("Keep playing", "Weiterspielen"),
+    ("Leave fullscreen", "Vollbild verlassen"),
+    ("Load pieces", "Figuren laden"),
+    ("Next puzzle", "Nächste Aufgabe"),
+    ("Offer draw", "Remis anbieten"),
+    ("Paste FEN", "FEN einfügen"),
+    ("Pause", "Pause"),
+    ("Play", "Spielen"),
+    ("Puzzles", "Aufgaben"),
+    ("Rematch", "Revanche"),
+    ("Resign", "Aufgeben"),
+    ("Retry", "Nochmal"),
+    ("Review game", "Partie nachspielen"),
+    ("Show attacks", "Angriffe zeigen"),
+    ("Start", "Starten"),
+    ("Starting position", "Grundstellung"),
+    ("Stop analysis", "Analyse beenden"),
+    ("Stop blunder check", "Fehlerprüfung beenden"),
+    ("Stop training", "Training beenden"),
+    ("Take back", "Zurücknehmen"),
+    ("Turn board each move", "Brett mit jedem Zug drehen"),
+    ("Undo", "Rückgängig"),
+    ("Type a move, e.g. Nf3", "Zug eingeben, z. B. Sf3"),
+    ("lichess game URL", "URL der lichess-Partie"),
+    ("You are in check", "Du stehst im Schach"),
+    ("it is not your move", "du bist nicht am Zug"),
+    ("The computer declines the draw", "Der Computer lehnt das Remis ab"),
+    ("Accuracy: {} {}% - {} {}%", "Genauigkeit: {} {}% - {} {}%"),
+    ("{}, empty", "{}, leer"),
+    ("white pawn", "weißer Bauer"),
+    ("white knight", "weißer Springer"),
+    ("white bishop", "weißer Läufer"),
+    ("white rook", "weißer Turm"),
+    ("white queen", "weiße Dame"),
+    ("white king", "weißer König"),
+    ("black pawn", "schwarzer Bauer"),
+    ("black knight", "schwarzer Springer"),
+    ("black bishop", "schwarzer Läufer"),
+    ("black rook", "schwarzer Turm"),
+    ("black queen", "schwarze Dame"),
+    ("black king", "schwarzer König"),
+    ("Could not open the opening book: {}", "Das Eröffnungsbuch konnte nicht geöffnet werden: {}"),
+    ("by checkmate", "durch Schachmatt"),
+    ("by stalemate", "durch Patt"),
+    ("by {}", "durch {}"),
+    ("{} resigned", "{} hat aufgegeben"),
+    ("{} ran out of time", "{} hat die Zeit überschritten"),
+    ("threefold repetition", "dreifache Stellungswiederholung"),
+    ("fifty-move rule", "50-Züge-Regel"),
+    ("fivefold repetition", "fünffache Stellungswiederholung"),
+    ("seventy-five-move rule", "75-Züge-Regel"),
+    ("agreement", "Vereinbarung"),
+    ("timeout against insufficient material", "Zeitüberschreitung gegen ungenügendes Material"),
+    ("ongoing", "läuft"),
+    ("check", "Schach"),
+    ("draw by {} can be claimed", "Remis durch {} kann beansprucht werden"),
+    ("checkmate, {} wins", "Schachmatt, {} gewinnt"),
+    ("resignation, {} wins", "Aufgabe, {} gewinnt"),
+    ("timeout, {} wins", "Zeitüberschreitung, {} gewinnt"),
+    ("draw by stalemate", "Remis durch Patt"),
+    ("draw by {}", "Remis durch {}"),
+    ("there is no piece to move", "dort steht keine Figur"),
+    ("it is not this side's turn", "diese Seite ist nicht am Zug"),
+    ("the destination is occupied by an own piece", "auf dem Zielfeld steht eine eigene Figur"),
+    ("the piece cannot move that way", "die Figur kann nicht so ziehen"),
+    ("the path is blocked", "der Weg ist versperrt"),
+    ("the king would be in check", "der König stünde im Schach"),
+    ("the king or rook has already moved", "König oder Turm haben schon gezogen"),
+    ("the squares between king and rook are occupied", "die Felder zwischen König und Turm sind besetzt"),
+    ("the king cannot castle out of check", "der König kann nicht aus dem Schach rochieren"),
+    ("the king cannot castle through an attacked square", "der König kann nicht über ein angegriffenes Feld rochieren"),
+    ("the promotion is invalid", "die Umwandlung ist ungültig"),
+    ("no legal moves possible", "kein legaler Zug möglich"),
+    ("invalid castle", "ungültige Rochade"),
+    ("the move cache is empty", "es gibt keinen Zug zurückzunehmen"),
+    ("the king does not exist", "der König fehlt"),
+    ("the path is invalid", "der Weg ist ungültig"),
+    ("the field is empty", "das Feld ist leer"),
+    ("the turn is invalid", "der Zug ist ungültig"),
+    ("illegal move: {}", "unerlaubter Zug: {}"),
+    ("the game is over", "die Partie ist beendet"),
+    ("no draw has been offered", "es wurde kein Remis angeboten"),
+    ("no draw can be claimed", "es kann kein Remis beansprucht werden"),
+    ("each side needs exactly one king", "jede Seite braucht genau einen König"),
+    ("a pawn is on the first or last rank", "ein Bauer steht auf der ersten oder letzten Reihe"),
+    ("the side not to move is in check", "die Seite, die nicht am Zug ist, steht im Schach"),
+    ("too many pieces for one side", "zu viele Figuren für eine Seite"),
+    ("invalid en passant square", "ungültiges En-passant-Feld"),
+    ("the move notation is malformed", "die Zugnotation ist fehlerhaft"),
+    ("the move notation is ambiguous", "die Zugnotation ist mehrdeutig"),
+    ("no legal move matches the notation", "kein legaler Zug passt zur Notation"),
+    ("the coordinate notation is malformed", "die Koordinatennotation ist fehlerhaft"),
+    ("the square is not on the board", "das Feld liegt nicht auf dem Brett"),
+    ("the image could not be exported", "das Bild konnte nicht exportiert werden"),
+    ("the piece drawing {} could not be read", "die Figurenzeichnung {} konnte nicht gelesen werden"),
+    ("the FEN has the wrong number of fields", "die FEN hat die falsche Anzahl an Feldern"),
+    ("the piece placement is malformed", "die Figurenstellung ist fehlerhaft"),
+    ("the side to move is malformed", "die Seite am Zug ist fehlerhaft"),
+    ("the castling rights are malformed", "die Rochaderechte sind fehlerhaft"),
+    ("the en passant square is malformed", "das En-passant-Feld ist fehlerhaft"),
+    ("the half-move clock is malformed", "der Halbzugzähler ist fehlerhaft"),
+    ("the full-move number is malformed or too large", "die Zugnummer ist fehlerhaft oder zu groß"),
+    ("the position is invalid: {}", "die Stellung ist ungültig: {}"),
+    ("the PGN could not be read: {}", "die PGN konnte nicht gelesen werden: {}"),
+    ("a tag pair is malformed", "ein Tag ist fehlerhaft"),
+    ("the FEN tag is invalid: {}", "der FEN-Tag ist ungültig: {}"),
+    ("the move {} cannot be played: {}", "der Zug {} kann nicht gespielt werden: {}"),
+    ("the game could not be downloaded: {}", "die Partie konnte nicht heruntergeladen werden: {}"),
+    ("the puzzles could not be read: {}", "die Aufgaben konnten nicht gelesen werden: {}"),
+    ("a puzzle line is malformed", "eine Aufgabenzeile ist fehlerhaft"),
+    ("the puzzle position is invalid: {}", "die Stellung der Aufgabe ist ungültig: {}"),
+    ("the puzzle move {} cannot be played: {}", "der Aufgabenzug {} kann nicht gespielt werden: {}"),
+    ("the engine could not be started: {}", "die Engine konnte nicht gestartet werden: {}"),
+    ("the engine could not be reached: {}", "die Engine ist nicht erreichbar: {}"),
+    ("the engine did not answer the UCI handshake", "die Engine hat nicht auf den UCI-Handshake geantwortet"),
+    ("the engine terminated unexpectedly", "die Engine wurde unerwartet beendet"),
+];
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::German];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Language::ALL
+            .into_iter()
+            .find(|language| language.to_string().eq_ignore_ascii_case(name))
+    }
+
+    // Text in this language, or the English one where it has no translation.
+    pub fn tr(&self, text: &'static str) -> &'static str {
+        let table: &[(&str, &str)] = match self {
+            Language::English => return text,
+            Language::German => GERMAN,
+        };
+
+        table
+            .iter()
+            .find(|(english, _)| *english == text)
+            .map_or(text, |(_, translated)| translated)
+    }
+
+    // Translates a template and fills its placeholders in order.
+    pub fn fill(&self, template: &'static str, values: &[&dyn fmt::Display]) -> String {
+        values
+            .iter()
+            .fold(self.tr(template).to_string(), |text, value| {
+                text.replacen("{}", &value.to_string(), 1)
+            })
+    }
+
+    pub fn color(&self, color: &Color) -> &'static str {
+        match color {
+            Color::White => self.tr("White"),
+            Color::Black => self.tr("Black"),
+        }
+    }
+
+    // Letters of the king, queen, rook, bishop and knight.
+    pub fn piece_letters(&self) -> [char; 5] {
+        match self {
+            Language::English => PIECE_LETTERS,
+            Language::German => ['K', 'D', 'T', 'L', 'S'],
+        }
+    }
+
+    // Writes a move given in English SAN with the piece letters of this language. Files are
+    // lowercase, so every uppercase letter but castling's O names a piece.
+    pub fn san(&self, san: &str) -> String {
+        let letters = self.piece_letters();
+
+        san.chars()
+            .map(
+                |c| match PIECE_LETTERS.iter().position(|letter| *letter == c) {
+                    Some(i) => letters[i],
+                    None => c,
+                },
+            )
+            .collect()
+    }
+
+    // Reads a move typed with the piece letters of this language back into English SAN.
+    pub fn to_english(&self, san: &str) -> String {
+        let letters = self.piece_letters();
+
+        san.chars()
+            .map(|c| match letters.iter().position(|letter| *letter == c) {
+                Some(i) => PIECE_LETTERS[i],
+                None => c,
+            })
+            .collect()
+    }
+}
+
+// Values shown to the user, such as errors and the state of the game, worded in a language.
+pub trait Translate {
+    fn tr(&self, language: Language) -> String;
+}
+
+impl<T: Translate + ?Sized> Translate for &T {
+    fn tr(&self, language: Language) -> String {
+        (**self).tr(language)
+    }
+}
+
+// Text is taken to be worded in the language already.
+impl Translate for str {
+    fn tr(&self, _: Language) -> String {
+        self.to_string()
+    }
+}
+
+impl Translate for String {
+    fn tr(&self, _: Language) -> String {
+        self.clone()
+    }
+}
+
+// Messages of the operating system are left as it words them.
+impl Translate for std::io::Error {
+    fn tr(&self, _: Language) -> String {
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "English"),
+            Language::German => write!(f, "Deutsch"),
+        }
+    }
+}
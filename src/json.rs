@@ -0,0 +1,153 @@
+use crate::clock::{Bonus, Clock};
+use crate::error::CatchAllError;
+use crate::game::{Game, Turn};
+use crate::lan::Lan;
+use crate::outcome::{DrawReason, GameStatus};
+use crate::piece::Color;
+use crate::position::Position;
+
+// Game state for frontends that do not link against iced, serialized as:
+//
+// {
+//   "fen": string,                    position in Forsyth-Edwards notation
+//   "pieces": { square: symbol },     e.g. "e1": "K", lowercase for black
+//   "turn": "white" | "black",        side to move
+//   "selected": square | null,        square picked in a two-step move
+//   "legal_moves": [lan],             e.g. "e2e4" or "e7e8q"
+//   "status": {
+//     "state": "ongoing" | "check" | "draw_claimable" | "checkmate"
+//            | "resignation" | "timeout" | "stalemate" | "draw",
+//     "winner": "white" | "black" | null,
+//     "reason": draw reason | null    e.g. "threefold_repetition"
+//   },
+//   "moves": [san],                   moves played so far
+//   "draw_offer": "white" | "black" | null,
+//   "clock": {                        null when the game is untimed
+//     "white": number,                milliseconds left
+//     "black": number,
+//     "running": "white" | "black" | null,
+//     "bonus": {
+//       "kind": "none" | "increment" | "bronstein" | "delay",
+//       "milliseconds": number
+//     }
+//   } | null,
+//   "halfmove_clock": number,
+//   "fullmove_number": number
+// }
+pub fn state(game: &mut Game) -> Result<String, CatchAllError> {
+    let pieces = (0..8)
+        .rev()
+        .flat_map(|rank| (0..8).map(move |file| Position::new(file, rank)))
+        .filter_map(|pos| {
+            game.at(&pos).ok().map(|piece| {
+                format!(
+                    "{}:{}",
+                    string(&pos.to_string()),
+                    string(&piece.symbol().to_string())
+                )
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let selected = match game.turn() {
+        Turn::Select(_, pos) => string(&pos.to_string()),
+        _ => "null".to_string(),
+    };
+
+    let legal_moves = game
+        .legal_moves()
+        .into_iter()
+        .map(|(from, to, kind)| string(&Lan::new(from, to, kind.promotion()).to_string()))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let moves = game
+        .moves()
+        .iter()
+        .map(|san| string(san))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    let draw_offer = game
+        .draw_offer()
+        .map_or("null".to_string(), |color| color_string(&color));
+
+    let clock = game.clock().map_or("null".to_string(), clock);
+
+    Ok(format!(
+        "{{\"fen\":{},\"pieces\":{{{}}},\"turn\":{},\"selected\":{},\"legal_moves\":[{}],\"status\":{},\"moves\":[{}],\"draw_offer\":{},\"clock\":{},\"halfmove_clock\":{},\"fullmove_number\":{}}}",
+        string(&game.to_fen()),
+        pieces,
+        color_string(&game.color()),
+        selected,
+        legal_moves,
+        status(game.status()?),
+        moves,
+        draw_offer,
+        clock,
+        game.halfmove_clock(),
+        game.fullmove_number(),
+    ))
+}
+
+fn status(status: GameStatus) -> String {
+    let (state, winner, reason) = match status {
+        GameStatus::Ongoing => ("ongoing", None, None),
+        GameStatus::Check => ("check", None, None),
+        GameStatus::DrawClaimable(reason) => ("draw_claimable", None, Some(reason)),
+        GameStatus::Checkmate(color) => ("checkmate", Some(color), None),
+        GameStatus::Resignation(color) => ("resignation", Some(color), None),
+        GameStatus::Timeout(color) => ("timeout", Some(color), None),
+        GameStatus::Stalemate => ("stalemate", None, None),
+        GameStatus::DrawBy(reason) => ("draw", None, Some(reason)),
+    };
+
+    format!(
+        "{{\"state\":{},\"winner\":{},\"reason\":{}}}",
+        string(state),
+        winner.map_or("null".to_string(), |color| color_string(&color)),
+        reason.map_or("null".to_string(), |reason| string(draw_reason(&reason))),
+    )
+}
+
+fn clock(clock: &Clock) -> String {
+    let (kind, bonus) = match clock.bonus() {
+        Bonus::None => ("none", 0),
+        Bonus::Increment(time) => ("increment", time.as_millis()),
+        Bonus::Bronstein(time) => ("bronstein", time.as_millis()),
+        Bonus::Delay(time) => ("delay", time.as_millis()),
+    };
+
+    format!(
+        "{{\"white\":{},\"black\":{},\"running\":{},\"bonus\":{{\"kind\":{},\"milliseconds\":{}}}}}",
+        clock.remaining(&Color::White).as_millis(),
+        clock.remaining(&Color::Black).as_millis(),
+        clock.running().map_or("null".to_string(), |color| color_string(&color)),
+        string(kind),
+        bonus,
+    )
+}
+
+fn draw_reason(reason: &DrawReason) -> &'static str {
+    match reason {
+        DrawReason::ThreefoldRepetition => "threefold_repetition",
+        DrawReason::FiftyMoveRule => "fifty_move_rule",
+        DrawReason::FivefoldRepetition => "fivefold_repetition",
+        DrawReason::SeventyFiveMoveRule => "seventy_five_move_rule",
+        DrawReason::Agreement => "agreement",
+        DrawReason::TimeoutVsInsufficientMaterial => "timeout_vs_insufficient_material",
+    }
+}
+
+fn color_string(color: &Color) -> String {
+    match color {
+        Color::White => string("white"),
+        Color::Black => string("black"),
+    }
+}
+
+// Notation never contains quotes or backslashes, so no escaping is needed.
+fn string(value: &str) -> String {
+    format!("\"{}\"", value)
+}
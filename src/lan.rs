@@ -0,0 +1,55 @@
+use crate::error::CatchAllError;
+use crate::piece::{Color, Promotion};
+use crate::position::Position;
+
+use std::fmt;
+
+// Long algebraic coordinate notation as used by UCI, e.g. "e2e4" or "e7e8q".
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lan {
+    pub from: Position,
+    pub to: Position,
+    pub promotion: Option<Promotion>,
+}
+
+impl Lan {
+    pub fn new(from: Position, to: Position, promotion: Option<Promotion>) -> Self {
+        Self {
+            from,
+            to,
+            promotion,
+        }
+    }
+
+    pub fn parse(lan: &str) -> Result<Self, CatchAllError> {
+        let lan = lan.trim();
+
+        let square = |range| {
+            lan.get(range)
+                .and_then(|square: &str| square.parse::<Position>().ok())
+                .ok_or(CatchAllError::BadLan)
+        };
+
+        let promotion = match lan.get(4..) {
+            Some("") => None,
+            Some("q") => Some(Promotion::Queen),
+            Some("r") => Some(Promotion::Rook),
+            Some("b") => Some(Promotion::Bishop),
+            Some("n") => Some(Promotion::Knight),
+            _ => return Err(CatchAllError::BadLan),
+        };
+
+        Ok(Self::new(square(0..2)?, square(2..4)?, promotion))
+    }
+}
+
+impl fmt::Display for Lan {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.from, self.to)?;
+
+        match self.promotion {
+            Some(promotion) => write!(f, "{}", promotion.piece(Color::Black).symbol()),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Chess rules, engine and file formats, with the iced desktop app as one frontend on top.
+//!
+//! - [`game::Game`] plays a game move by move: turns, clocks, draw offers and the outcome.
+//! - [`board::Board`] holds a position and generates and makes legal moves.
+//! - [`piece::Piece`], [`position::Position`], [`r#move::Move`] and [`path::Path`] are its building
+//!   blocks.
+//! - [`fen`], [`san`], [`lan`] and [`pgn`] read and write the usual notations.
+//! - [`search`] and [`engine`] find moves, [`uci`] speaks to other engines and GUIs.
+//!
+//! The [`gui`] module and the image export in [`png`] and [`sprite`] need the default `gui`
+//! feature. Build with `default-features = false` to use the rest without iced.
+
+pub mod accuracy;
+pub mod ai;
+pub mod annotation;
+pub mod bitboard;
+pub mod board;
+pub mod book;
+pub mod castling;
+pub mod clock;
+pub mod database;
+pub mod eco;
+pub mod engine;
+pub mod error;
+pub mod evaluation;
+pub mod explorer;
+pub mod fen;
+pub mod game;
+pub mod guess;
+#[cfg(feature = "gui")]
+pub mod gui;
+pub mod heatmap;
+pub mod i18n;
+pub mod json;
+pub mod lan;
+#[cfg(feature = "lichess")]
+pub mod lichess;
+pub mod mate;
+pub mod material;
+pub mod r#move;
+pub mod ordering;
+pub mod outcome;
+pub mod path;
+pub mod pgn;
+pub mod piece;
+#[cfg(feature = "gui")]
+pub mod png;
+pub mod position;
+pub mod puzzle;
+pub mod san;
+pub mod search;
+pub mod selfplay;
+pub mod settings;
+#[cfg(feature = "gui")]
+pub mod sprite;
+pub mod squares;
+pub mod svg;
+pub mod tactics;
+pub mod terminal;
+pub mod timing;
+pub mod transposition;
+#[cfg(feature = "tui")]
+pub mod tui;
+pub mod uci;
+pub mod zobrist;
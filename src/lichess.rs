@@ -0,0 +1,37 @@
+use crate::error::PgnError;
+use crate::pgn::Pgn;
+
+const EXPORT_URL: &str = "https://lichess.org/game/export";
+
+// Extracts the eight character game ID from a lichess URL such as
+// https://lichess.org/abcdEFGH/black, or accepts a bare ID.
+pub fn game_id(url: &str) -> Option<String> {
+    let path = url
+        .trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("lichess.org/")
+        .trim_start_matches("game/export/");
+
+    let id: String = path
+        .chars()
+        .take_while(char::is_ascii_alphanumeric)
+        .collect();
+
+    (id.len() == 8 || id.len() == 12).then(|| id[..8].to_string())
+}
+
+pub fn download(url: &str) -> Result<String, PgnError> {
+    let id = game_id(url).ok_or_else(|| PgnError::Download(format!("no game ID in {}", url)))?;
+
+    ureq::get(&format!("{}/{}", EXPORT_URL, id))
+        .set("Accept", "application/x-chess-pgn")
+        .call()
+        .map_err(|e| PgnError::Download(e.to_string()))?
+        .into_string()
+        .map_err(|e| PgnError::Io(e.kind()))
+}
+
+pub fn fetch(url: &str) -> Result<Pgn, PgnError> {
+    download(url).and_then(|pgn| Pgn::parse(&pgn))
+}
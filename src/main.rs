@@ -1,25 +1,32 @@
-mod board;
-mod error;
-mod game;
-mod gui;
-mod r#move;
-mod piece;
-mod position;
-mod path;
-
-use gui::Gui;
+use chess::gui::Gui;
+use chess::settings;
 use iced::window;
-use iced::Sandbox;
+use iced::Application;
 use iced::Settings;
 
 pub fn main() -> iced::Result {
-    // todo!("Display pop-up on checkmate or stalemate before resetting game");
     // todo!("Reduce responsibilities of board");
-    // todo!("Handle draw by three-fold repetition (Zobrist hasing), by insufficient material, by 50 move rule (simple capture counter)");
+    // todo!("Handle draw by insufficient material");
+
+    // The window opens where it was closed, and as large.
+    let saved = settings::Settings::open();
+    let size = saved
+        .parse("window_width")
+        .zip(saved.parse("window_height"))
+        .unwrap_or((1260, 1000));
+    let position = match saved.parse("window_x").zip(saved.parse("window_y")) {
+        Some((x, y)) => window::Position::Specific(x, y),
+        None => window::Position::default(),
+    };
 
     Gui::run(Settings {
+        // The window is closed by the app once it has saved its geometry.
+        exit_on_close_request: false,
         window: window::Settings {
-            size: (800, 800),
+            size,
+            position,
+            // The board shrinks with the window, down to a size still fit to play on.
+            min_size: Some((900, 700)),
             ..window::Settings::default()
         },
         ..Settings::default()
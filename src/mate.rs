@@ -0,0 +1,91 @@
+use crate::board::Board;
+use crate::error::CatchAllError;
+use crate::lan::Lan;
+use crate::piece::Color;
+
+// Proves the shortest forced mate for the side to move within the given number of plies.
+// The line answers every attacking move with the defence that holds out longest.
+#[rustfmt::skip]
+pub fn solve_mate(board: &Board, color: &Color, max_plies: usize) -> Result<Option<Vec<Lan>>, CatchAllError> {
+    let mut board = board.clone();
+
+    for plies in (1..=max_plies).step_by(2) {
+        if let Some(line) = attack(&mut board, color, plies)? {
+            return Ok(Some(line));
+        }
+    }
+
+    Ok(None)
+}
+
+// Finds an attacking move after which every defence runs into mate within the remaining plies.
+#[rustfmt::skip]
+fn attack(board: &mut Board, color: &Color, plies: usize) -> Result<Option<Vec<Lan>>, CatchAllError> {
+    let defender = color.opposite();
+    // Checks are tried first, and the mating move itself is always one.
+    let mut candidates = Vec::new();
+    for (from, to, kind) in board.legal_moves(color) {
+        board.make(&from, &to, kind.promotion())?;
+        let check = board.in_check(&defender)?;
+        board.revert()?;
+
+        match check {
+            true => candidates.insert(0, (from, to, kind)),
+            false if plies > 1 => candidates.push((from, to, kind)),
+            false => (),
+        }
+    }
+
+    for (from, to, kind) in candidates {
+        let lan = Lan::new(from, to, kind.promotion());
+
+        board.make(&from, &to, kind.promotion())?;
+        let line = defend(board, &defender, plies - 1);
+        board.revert()?;
+
+        if let Some(line) = line? {
+            return Ok(Some([vec![lan], line].concat()));
+        }
+    }
+
+    Ok(None)
+}
+
+// Returns the longest line by which the defender gets mated, or None if some defence escapes.
+#[rustfmt::skip]
+fn defend(board: &mut Board, color: &Color, plies: usize) -> Result<Option<Vec<Lan>>, CatchAllError> {
+    let moves = board.legal_moves(color);
+
+    if moves.is_empty() {
+        return match board.in_check(color)? {
+            true => Ok(Some(Vec::new())),
+            false => Ok(None),
+        };
+    }
+
+    if plies == 0 {
+        return Ok(None);
+    }
+
+    let mut longest: Option<Vec<Lan>> = None;
+
+    for (from, to, kind) in moves {
+        board.make(&from, &to, kind.promotion())?;
+        let line = attack(board, &color.opposite(), plies - 1);
+        board.revert()?;
+
+        let line = match line? {
+            Some(line) => [vec![Lan::new(from, to, kind.promotion())], line].concat(),
+            None => return Ok(None),
+        };
+
+        if longest
+            .as_ref()
+            .is_none_or(|longest| line.len() > longest.len())
+        {
+            longest = Some(line);
+        }
+    }
+
+    Ok(longest)
+}
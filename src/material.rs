@@ -0,0 +1,70 @@
+use crate::game::Game;
+use crate::piece::{Color, Piece};
+
+// Pieces taken by either side over a game and the material balance they add up to.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Captures {
+    // Pieces captured by white and by black, most valuable first.
+    pub white: Vec<Piece>,
+    pub black: Vec<Piece>,
+    // Material white is ahead by in pawns, counting promotions as well as captures.
+    pub balance: i32,
+}
+
+impl Captures {
+    // Pieces captured by the given side.
+    pub fn by(&self, color: &Color) -> &[Piece] {
+        match color {
+            Color::White => &self.white,
+            Color::Black => &self.black,
+        }
+    }
+}
+
+// Conventional piece values in pawns.
+pub fn points(piece: &Piece) -> i32 {
+    match piece {
+        Piece::Pawn(_, _) => 1,
+        Piece::Knight(_) | Piece::Bishop(_) => 3,
+        Piece::Rook(_) => 5,
+        Piece::Queen(_) => 9,
+        Piece::King(_) => 0,
+    }
+}
+
+// Collects the captures from the moves made in the game rather than comparing positions,
+// so that games set up from a FEN only count what was taken since.
+pub fn captures(game: &Game) -> Captures {
+    let mut captures = Captures::default();
+
+    for (made, san) in game.board().history().iter().zip(game.moves()) {
+        let color = made.piece.color();
+        let sign = match color {
+            Color::White => 1,
+            Color::Black => -1,
+        };
+
+        if let Some((_, piece)) = &made.captured {
+            captures.balance += sign * points(piece);
+            match color {
+                Color::White => captures.white.push(piece.clone()),
+                Color::Black => captures.black.push(piece.clone()),
+            }
+        }
+
+        // The promoted piece is named after the equals sign of the move.
+        let promoted = san
+            .split_once('=')
+            .and_then(|(_, promotion)| promotion.chars().next())
+            .and_then(Piece::from_symbol);
+        if let Some(piece) = promoted {
+            captures.balance += sign * (points(&piece) - 1);
+        }
+    }
+
+    for pieces in [&mut captures.white, &mut captures.black] {
+        pieces.sort_by_key(|piece| -points(piece));
+    }
+
+    captures
+}
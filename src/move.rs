@@ -1,6 +1,7 @@
+use crate::piece::Promotion;
 use crate::position::{Distance, Position};
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Direction {
     Up,
     Right,
@@ -8,12 +9,35 @@ pub enum Direction {
     Left,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Action {
     Regular,
     Capture,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum MoveKind {
+    Quiet,
+    DoublePush,
+    Capture,
+    EnPassant,
+    // Holds the direction the king castles towards.
+    Castle(Direction),
+    Promotion(Promotion),
+    PromotionCapture(Promotion),
+}
+
+impl MoveKind {
+    pub fn promotion(&self) -> Option<Promotion> {
+        match self {
+            MoveKind::Promotion(promotion) | MoveKind::PromotionCapture(promotion) => {
+                Some(*promotion)
+            }
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub enum Move {
     Straight(Direction, usize, Action),
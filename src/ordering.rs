@@ -0,0 +1,126 @@
+use crate::bitboard;
+use crate::board::Board;
+use crate::lan::Lan;
+use crate::piece::{Color, Piece};
+use crate::position::Position;
+use crate::r#move::MoveKind;
+
+use std::cmp::Reverse;
+
+const HASH_MOVE: i32 = 1_000_000;
+const CAPTURE: i32 = 100_000;
+const PROMOTION: i32 = 90_000;
+const KILLER: i32 = 80_000;
+// History scores are halved once one reaches this, staying below the killer moves.
+const HISTORY_LIMIT: i32 = 50_000;
+
+pub struct MoveOrdering {
+    // Two quiet moves per ply that recently caused a beta cutoff.
+    killers: Vec<[Option<Lan>; 2]>,
+    // Accumulated cutoffs of quiet moves by origin and destination square.
+    history: Vec<[i32; 64]>,
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        Self {
+            killers: Vec::new(),
+            history: vec![[0; 64]; 64],
+        }
+    }
+
+    // Forgets the killers of the previous search and fades its history.
+    pub fn new_search(&mut self) {
+        self.killers.clear();
+        self.history
+            .iter_mut()
+            .flatten()
+            .for_each(|score| *score /= 2);
+    }
+
+    pub fn clear(&mut self) {
+        self.killers.clear();
+        self.history = vec![[0; 64]; 64];
+    }
+
+    // Sorts the hash move first, then captures by MVV-LVA, promotions, killers and quiets by history.
+    #[rustfmt::skip]
+    pub fn order(&self, board: &Board, moves: &mut [(Position, Position, MoveKind)], hash_move: Option<Lan>, ply: usize) {
+        moves.sort_by_cached_key(|(from, to, kind)| {
+            let lan = Lan::new(*from, *to, kind.promotion());
+
+            let score = match kind {
+                _ if Some(lan) == hash_move => HASH_MOVE,
+                MoveKind::Capture | MoveKind::PromotionCapture(_) => CAPTURE + Self::mvv_lva(board, from, to),
+                // A pawn takes a pawn.
+                MoveKind::EnPassant => CAPTURE + 9,
+                MoveKind::Promotion(promotion) => PROMOTION + rank(&promotion.piece(Color::White)),
+                _ if self.is_killer(&lan, ply) => KILLER,
+                _ => self.history[bitboard::square(from)][bitboard::square(to)],
+            };
+
+            Reverse(score)
+        });
+    }
+
+    // Remembers a quiet move that refuted the position at the given ply.
+    pub fn cutoff(&mut self, lan: Lan, kind: &MoveKind, depth: usize, ply: usize) {
+        if !matches!(
+            kind,
+            MoveKind::Quiet | MoveKind::DoublePush | MoveKind::Castle(_)
+        ) {
+            return;
+        }
+
+        if self.killers.len() <= ply {
+            self.killers.resize(ply + 1, [None; 2]);
+        }
+
+        let killers = &mut self.killers[ply];
+        if killers[0] != Some(lan) {
+            killers[1] = killers[0];
+            killers[0] = Some(lan);
+        }
+
+        let score = &mut self.history[bitboard::square(&lan.from)][bitboard::square(&lan.to)];
+        *score += (depth * depth) as i32;
+
+        if *score >= HISTORY_LIMIT {
+            self.history
+                .iter_mut()
+                .flatten()
+                .for_each(|score| *score /= 2);
+        }
+    }
+
+    fn is_killer(&self, lan: &Lan, ply: usize) -> bool {
+        self.killers
+            .get(ply)
+            .is_some_and(|killers| killers.contains(&Some(*lan)))
+    }
+
+    // Most valuable victim first, ties broken by the least valuable attacker.
+    fn mvv_lva(board: &Board, from: &Position, to: &Position) -> i32 {
+        let victim = board.at(to).map_or(0, rank);
+        let attacker = board.at(from).map_or(0, rank);
+
+        10 * victim - attacker
+    }
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn rank(piece: &Piece) -> i32 {
+    match piece {
+        Piece::Pawn(_, _) => 1,
+        Piece::Knight(_) => 2,
+        Piece::Bishop(_) => 3,
+        Piece::Rook(_) => 4,
+        Piece::Queen(_) => 5,
+        Piece::King(_) => 6,
+    }
+}
@@ -0,0 +1,162 @@
+use crate::i18n::{Language, Translate};
+use crate::piece::Color;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawReason {
+    ThreefoldRepetition,
+    FiftyMoveRule,
+    FivefoldRepetition,
+    SeventyFiveMoveRule,
+    Agreement,
+    // The flag of a side fell while the other could not have mated it.
+    TimeoutVsInsufficientMaterial,
+}
+
+impl DrawReason {
+    // Claimable draws end the game only if a player claims them, all others end it immediately.
+    pub fn claimable(&self) -> bool {
+        matches!(
+            self,
+            DrawReason::ThreefoldRepetition | DrawReason::FiftyMoveRule
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Outcome {
+    // Holds the color of the winning side.
+    Checkmate(Color),
+    Resignation(Color),
+    Timeout(Color),
+    Stalemate,
+    Draw(DrawReason),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GameStatus {
+    Ongoing,
+    Check,
+    DrawClaimable(DrawReason),
+    // Holds the color of the winning side.
+    Checkmate(Color),
+    Resignation(Color),
+    Timeout(Color),
+    Stalemate,
+    DrawBy(DrawReason),
+}
+
+impl Outcome {
+    // Game termination marker as used by PGN.
+    pub fn result(&self) -> &'static str {
+        match self {
+            Outcome::Stalemate | Outcome::Draw(_) => "1/2-1/2",
+            outcome => match outcome.winner() {
+                Some(Color::White) => "1-0",
+                _ => "0-1",
+            },
+        }
+    }
+
+    pub fn winner(&self) -> Option<Color> {
+        match self {
+            Outcome::Checkmate(color) | Outcome::Resignation(color) | Outcome::Timeout(color) => {
+                Some(*color)
+            }
+            Outcome::Stalemate | Outcome::Draw(_) => None,
+        }
+    }
+
+    // How the game came to an end, without who won it.
+    pub fn reason(&self, language: Language) -> String {
+        match self {
+            Outcome::Checkmate(_) => language.tr("by checkmate").to_string(),
+            Outcome::Resignation(color) => {
+                language.fill("{} resigned", &[&language.color(&color.opposite())])
+            }
+            Outcome::Timeout(color) => {
+                language.fill("{} ran out of time", &[&language.color(&color.opposite())])
+            }
+            Outcome::Stalemate => language.tr("by stalemate").to_string(),
+            Outcome::Draw(reason) => language.fill("by {}", &[&reason.tr(language)]),
+        }
+    }
+}
+
+impl From<Outcome> for GameStatus {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Checkmate(color) => GameStatus::Checkmate(color),
+            Outcome::Resignation(color) => GameStatus::Resignation(color),
+            Outcome::Timeout(color) => GameStatus::Timeout(color),
+            Outcome::Stalemate => GameStatus::Stalemate,
+            Outcome::Draw(reason) => GameStatus::DrawBy(reason),
+        }
+    }
+}
+
+impl Translate for DrawReason {
+    fn tr(&self, language: Language) -> String {
+        let text = match self {
+            DrawReason::ThreefoldRepetition => "threefold repetition",
+            DrawReason::FiftyMoveRule => "fifty-move rule",
+            DrawReason::FivefoldRepetition => "fivefold repetition",
+            DrawReason::SeventyFiveMoveRule => "seventy-five-move rule",
+            DrawReason::Agreement => "agreement",
+            DrawReason::TimeoutVsInsufficientMaterial => "timeout against insufficient material",
+        };
+
+        language.tr(text).to_string()
+    }
+}
+
+impl Translate for Outcome {
+    fn tr(&self, language: Language) -> String {
+        GameStatus::from(*self).tr(language)
+    }
+}
+
+impl Translate for GameStatus {
+    fn tr(&self, language: Language) -> String {
+        match self {
+            GameStatus::Ongoing => language.tr("ongoing").to_string(),
+            GameStatus::Check => language.tr("check").to_string(),
+            GameStatus::DrawClaimable(reason) => {
+                language.fill("draw by {} can be claimed", &[&reason.tr(language)])
+            }
+            GameStatus::Checkmate(color) => {
+                language.fill("checkmate, {} wins", &[&language.color(color)])
+            }
+            GameStatus::Resignation(color) => {
+                language.fill("resignation, {} wins", &[&language.color(color)])
+            }
+            GameStatus::Timeout(color) => {
+                language.fill("timeout, {} wins", &[&language.color(color)])
+            }
+            GameStatus::Stalemate => language.tr("draw by stalemate").to_string(),
+            GameStatus::DrawBy(reason) => language.fill("draw by {}", &[&reason.tr(language)]),
+        }
+    }
+}
+
+impl fmt::Display for DrawReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
+
+impl fmt::Display for GameStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.tr(Language::English))
+    }
+}
@@ -48,7 +48,7 @@ impl Path {
         Path(
             (pos.rank..pos.rank + steps)
                 .skip(1)
-                .map(move |r| Position::new(pos.file, r))
+                .map_while(move |r| Position::try_new(pos.file, r))
                 .collect(),
         )
     }
@@ -58,7 +58,7 @@ impl Path {
             (pos.rank - steps..pos.rank)
                 .skip(1)
                 .rev()
-                .map(move |r| Position::new(pos.file, r))
+                .map_while(move |r| Position::try_new(pos.file, r))
                 .collect(),
         )
     }
@@ -67,7 +67,7 @@ impl Path {
         Path(
             (pos.file..pos.file + steps)
                 .skip(1)
-                .map(move |f| Position::new(f, pos.rank))
+                .map_while(move |f| Position::try_new(f, pos.rank))
                 .collect(),
         )
     }
@@ -77,7 +77,7 @@ impl Path {
             (pos.file - steps..pos.file)
                 .skip(1)
                 .rev()
-                .map(move |f| Position::new(f, pos.rank))
+                .map_while(move |f| Position::try_new(f, pos.rank))
                 .collect(),
         )
     }
@@ -87,7 +87,7 @@ impl Path {
             (pos.file..pos.file + steps)
                 .zip(pos.rank..pos.rank + steps)
                 .skip(1)
-                .map(move |(f, r)| Position::new(f, r))
+                .map_while(move |(f, r)| Position::try_new(f, r))
                 .collect(),
         )
     }
@@ -98,7 +98,7 @@ impl Path {
                 .rev()
                 .zip(pos.rank..pos.rank + steps)
                 .skip(1)
-                .map(move |(f, r)| Position::new(f, r))
+                .map_while(move |(f, r)| Position::try_new(f, r))
                 .collect(),
         )
     }
@@ -108,7 +108,7 @@ impl Path {
             (pos.file..pos.file + steps)
                 .zip((pos.rank - steps..=pos.rank).rev())
                 .skip(1)
-                .map(move |(f, r)| Position::new(f, r))
+                .map_while(move |(f, r)| Position::try_new(f, r))
                 .collect(),
         )
     }
@@ -119,7 +119,7 @@ impl Path {
                 .zip(pos.rank - steps..pos.rank)
                 .skip(1)
                 .rev()
-                .map(move |(f, r)| Position::new(f, r))
+                .map_while(move |(f, r)| Position::try_new(f, r))
                 .collect(),
         )
     }
@@ -0,0 +1,461 @@
+use crate::board::Board;
+use crate::eco;
+use crate::error::PgnError;
+use crate::game::Game;
+use crate::piece::Color;
+use crate::san::San;
+
+use std::fmt;
+use std::fs;
+use std::io;
+
+const RESULTS: [&str; 4] = ["1-0", "0-1", "1/2-1/2", "*"];
+
+// Move suffix annotations and their numeric annotation glyphs.
+const SUFFIXES: [(&str, u8); 6] = [
+    ("!!", 3),
+    ("??", 4),
+    ("!?", 5),
+    ("?!", 6),
+    ("!", 1),
+    ("?", 2),
+];
+
+enum Token {
+    Move(String),
+    Nag(u8),
+    Comment(String),
+    Open,
+    Close,
+    DrawOffer,
+    Result(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub san: String,
+    pub nags: Vec<u8>,
+    pub comment_before: Option<String>,
+    pub comment: Option<String>,
+    // Alternatives to this move, each starting from the position before it.
+    pub variations: Vec<Vec<Node>>,
+    // Whether a draw was offered with the move, written as "(=)".
+    pub draw_offer: bool,
+}
+
+pub struct Pgn {
+    pub tags: Vec<(String, String)>,
+    pub moves: Vec<Node>,
+    pub game: Game,
+}
+
+impl Pgn {
+    pub fn load(path: &str) -> Result<Self, PgnError> {
+        fs::read_to_string(path)
+            .map_err(|e| PgnError::Io(e.kind()))
+            .and_then(|pgn| Pgn::parse(&pgn))
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn parse(pgn: &str) -> Result<Self, PgnError> {
+        let (mut tags, movetext) = Pgn::tags(pgn)?;
+
+        let mut game = match tags.iter().find(|(name, _)| name == "FEN") {
+            Some((_, fen)) => Game::from_fen(fen).map_err(PgnError::Fen)?,
+            None => Game::new(),
+        };
+
+        let tokens = Pgn::tokens(&movetext);
+        let mut end = 0;
+        let moves = Pgn::line(&tokens, &mut end);
+
+        // The result closing the movetext stands in for a missing Result tag.
+        if let Some(Token::Result(result)) = end.checked_sub(1).and_then(|i| tokens.get(i)) {
+            if !tags.iter().any(|(name, _)| name == "Result") {
+                tags.push(("Result".to_string(), result.clone()));
+            }
+        }
+
+        for node in &moves {
+            for variation in &node.variations {
+                Pgn::check(game.board(), game.color(), variation)?;
+            }
+            Pgn::replay(&mut game, &node.san)?;
+        }
+
+        Ok(Self { tags, moves, game })
+    }
+
+    // Records a game played in the app, deriving the result and start position tags from it.
+    pub fn from_game(game: &Game, tags: Vec<(String, String)>) -> Self {
+        let mut tags: Vec<_> = tags
+            .into_iter()
+            .filter(|(name, _)| !["Result", "SetUp", "FEN"].contains(&name.as_str()))
+            .collect();
+
+        let result = game.outcome().map_or("*", |outcome| outcome.result());
+        tags.push(("Result".to_string(), result.to_string()));
+
+        // The opening is only filled in where the caller has not named it.
+        if let Some(opening) = eco::classify(game) {
+            for (name, value) in [("ECO", opening.eco), ("Opening", opening.name)] {
+                if !tags.iter().any(|(tag, _)| tag == name) {
+                    tags.push((name.to_string(), value));
+                }
+            }
+        }
+
+        if game.initial_fen() != Board::new().to_fen(&Color::White, 1) {
+            tags.push(("SetUp".to_string(), "1".to_string()));
+            tags.push(("FEN".to_string(), game.initial_fen().to_string()));
+        }
+
+        // Offers accepted before the offering side moved go with the last move instead.
+        let last = game.moves().len().saturating_sub(1);
+        let offers: Vec<_> = game
+            .draw_offers()
+            .iter()
+            .map(|index| *index.min(&last))
+            .collect();
+
+        let moves = game
+            .moves()
+            .iter()
+            .enumerate()
+            .map(|(i, san)| Node {
+                san: san.clone(),
+                nags: Vec::new(),
+                comment_before: None,
+                comment: None,
+                variations: Vec::new(),
+                draw_offer: offers.contains(&i),
+            })
+            .collect();
+
+        Self {
+            tags,
+            moves,
+            game: game.clone(),
+        }
+    }
+
+    pub fn tag(&self, name: &str) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|(tag, _)| tag == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    // Splits the leading tag pairs of the form [Name "Value"] from the movetext.
+    pub fn tags(pgn: &str) -> Result<(Vec<(String, String)>, String), PgnError> {
+        let mut tags = Vec::new();
+        let mut lines = pgn.lines().map(str::trim).peekable();
+
+        while let Some(line) = lines.next_if(|line| line.is_empty() || line.starts_with('[')) {
+            if line.is_empty() {
+                continue;
+            }
+
+            let (name, value) = line
+                .strip_prefix('[')
+                .and_then(|line| line.strip_suffix(']'))
+                .and_then(|line| line.split_once(char::is_whitespace))
+                .ok_or(PgnError::Tag)?;
+
+            let value = value
+                .trim()
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .ok_or(PgnError::Tag)?;
+
+            tags.push((name.to_string(), value.replace("\\\"", "\"")));
+        }
+
+        Ok((tags, lines.collect::<Vec<_>>().join("\n")))
+    }
+
+    fn tokens(movetext: &str) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        let mut word = String::new();
+        let mut chars = movetext.chars();
+
+        while let Some(c) = chars.next() {
+            if c.is_whitespace() || "{;()".contains(c) {
+                Pgn::word(&mut tokens, &mut word);
+            }
+
+            match c {
+                '{' => {
+                    let comment: String = chars.by_ref().take_while(|c| *c != '}').collect();
+                    tokens.push(Token::Comment(
+                        comment.split_whitespace().collect::<Vec<_>>().join(" "),
+                    ));
+                }
+                ';' => {
+                    let comment: String = chars.by_ref().take_while(|c| *c != '\n').collect();
+                    tokens.push(Token::Comment(comment.trim().to_string()));
+                }
+                // A draw offer looks like a variation holding nothing but an equals sign.
+                '(' if chars.as_str().starts_with("=)") => {
+                    chars.nth(1);
+                    tokens.push(Token::DrawOffer);
+                }
+                '(' => tokens.push(Token::Open),
+                ')' => tokens.push(Token::Close),
+                c if c.is_whitespace() => (),
+                c => word.push(c),
+            }
+        }
+        Pgn::word(&mut tokens, &mut word);
+
+        tokens
+    }
+
+    // Classifies a whitespace-delimited word, dropping move numbers such as "12." or "12...", also
+    // when attached to the move, and the "e.p." some writers append to en passant captures.
+    fn word(tokens: &mut Vec<Token>, word: &mut String) {
+        let number = word.trim_start_matches(|c: char| c.is_ascii_digit());
+        let token = match number.strip_prefix('.') {
+            Some(rest) if number.len() < word.len() => rest.trim_start_matches('.'),
+            _ => word.as_str(),
+        };
+
+        if RESULTS.contains(&token) {
+            tokens.push(Token::Result(token.to_string()));
+        } else if let Some(nag) = token.strip_prefix('$') {
+            tokens.extend(nag.parse().ok().map(Token::Nag));
+        } else if !token.is_empty() && token != "e.p." {
+            tokens.push(Token::Move(token.to_string()));
+        }
+
+        word.clear();
+    }
+
+    // Builds the tree of a line up to its closing parenthesis or the game result.
+    fn line(tokens: &[Token], i: &mut usize) -> Vec<Node> {
+        let mut nodes: Vec<Node> = Vec::new();
+        let mut comment_before = None;
+
+        while let Some(token) = tokens.get(*i) {
+            *i += 1;
+
+            match token {
+                Token::Move(word) => {
+                    let (san, nag) = SUFFIXES
+                        .iter()
+                        .find_map(|(suffix, nag)| {
+                            word.strip_suffix(suffix).map(|san| (san, Some(*nag)))
+                        })
+                        .unwrap_or((word, None));
+
+                    nodes.push(Node {
+                        san: san.to_string(),
+                        nags: nag.into_iter().collect(),
+                        comment_before: comment_before.take(),
+                        comment: None,
+                        variations: Vec::new(),
+                        draw_offer: false,
+                    });
+                }
+                Token::Nag(nag) => nodes
+                    .last_mut()
+                    .into_iter()
+                    .for_each(|node| node.nags.push(*nag)),
+                Token::Comment(comment) => match nodes.last_mut() {
+                    Some(Node {
+                        comment: Some(previous),
+                        ..
+                    }) => {
+                        previous.push(' ');
+                        previous.push_str(comment);
+                    }
+                    Some(node) => node.comment = Some(comment.clone()),
+                    None => comment_before = Some(comment.clone()),
+                },
+                Token::DrawOffer => nodes
+                    .last_mut()
+                    .into_iter()
+                    .for_each(|node| node.draw_offer = true),
+                Token::Open => {
+                    let variation = Pgn::line(tokens, i);
+                    if let Some(node) = nodes.last_mut() {
+                        node.variations.push(variation);
+                    }
+                }
+                Token::Close | Token::Result(_) => break,
+            }
+        }
+
+        nodes
+    }
+
+    // Verifies that every move of a variation is legal, recursing into nested variations.
+    fn check(board: &Board, color: Color, line: &[Node]) -> Result<(), PgnError> {
+        let mut board = board.clone();
+        let mut color = color;
+
+        for node in line {
+            for variation in &node.variations {
+                Pgn::check(&board, color, variation)?;
+            }
+
+            San::parse(&node.san)
+                .and_then(|san| san.resolve(&mut board, &color))
+                .and_then(|(from, to, promotion)| board.advance(&color, &from, &to, promotion))
+                .map_err(|e| PgnError::IllegalMove(node.san.clone(), e))?;
+
+            color = color.opposite();
+        }
+
+        Ok(())
+    }
+
+    fn replay(game: &mut Game, san: &str) -> Result<(), PgnError> {
+        game.advance_san(san)
+            .map(|_| ())
+            .map_err(|e| PgnError::IllegalMove(san.to_string(), e))
+    }
+
+    // Writes the movetext tokens of a line starting at the given ply.
+    fn write(words: &mut Vec<String>, line: &[Node], ply: usize) {
+        // Black moves need their number after anything interrupting the main line.
+        let mut numbered = true;
+
+        for (i, node) in line.iter().enumerate() {
+            let ply = ply + i;
+
+            if let Some(comment) = &node.comment_before {
+                words.push(format!("{{{}}}", comment));
+                numbered = true;
+            }
+
+            match (ply % 2, numbered) {
+                (0, _) => words.push(format!("{}.", ply / 2 + 1)),
+                (_, true) => words.push(format!("{}...", ply / 2 + 1)),
+                _ => (),
+            }
+
+            words.push(node.san.clone());
+            words.extend(node.nags.iter().map(|nag| format!("${}", nag)));
+            if node.draw_offer {
+                words.push("(=)".to_string());
+            }
+            numbered = false;
+
+            if let Some(comment) = &node.comment {
+                words.push(format!("{{{}}}", comment));
+                numbered = true;
+            }
+
+            for variation in &node.variations {
+                let mut inner = Vec::new();
+                Pgn::write(&mut inner, variation, ply);
+                words.push(format!("({})", inner.join(" ")));
+                numbered = true;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Pgn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (name, value) in &self.tags {
+            writeln!(f, "[{} \"{}\"]", name, value.replace('"', "\\\""))?;
+        }
+        writeln!(f)?;
+
+        let mut words = Vec::new();
+        Pgn::write(&mut words, &self.moves, self.game.ply() - self.moves.len());
+        words.push(self.tag("Result").unwrap_or("*").to_string());
+
+        // Movetext lines are wrapped at 80 characters.
+        let mut line = String::new();
+        for word in words {
+            if !line.is_empty() && line.len() + word.len() >= 80 {
+                writeln!(f, "{}", line)?;
+                line.clear();
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        writeln!(f, "{}", line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Pgn;
+
+    const ANNOTATED: &str = r#"[Event "Test"]
+[Result "1/2-1/2"]
+
+{Opening} 1. e4 e5 {Open game} 2. Nf3!? (2. f4 exf4 (2... d5) 3. Nf3 $1) 2... Nc6 $6 {Knight out;
+too early?} 3. Bb5 (=) a6 ; the Morphy defence
+4.Ba4 1/2-1/2
+"#;
+
+    #[test]
+    fn comments_and_nags() {
+        let pgn = Pgn::parse(ANNOTATED).unwrap();
+        let moves = &pgn.moves;
+
+        assert_eq!(moves.len(), 7);
+        assert_eq!(moves[0].comment_before.as_deref(), Some("Opening"));
+        assert_eq!(moves[1].comment.as_deref(), Some("Open game"));
+        assert_eq!(moves[2].san, "Nf3");
+        assert_eq!(moves[2].nags, [5]);
+        assert_eq!(moves[3].nags, [6]);
+        assert_eq!(moves[3].comment.as_deref(), Some("Knight out; too early?"));
+        assert_eq!(moves[5].comment.as_deref(), Some("the Morphy defence"));
+        assert_eq!(pgn.tag("Result"), Some("1/2-1/2"));
+    }
+
+    #[test]
+    fn nested_variations() {
+        let pgn = Pgn::parse(ANNOTATED).unwrap();
+        let variation = &pgn.moves[2].variations[0];
+
+        let sans: Vec<_> = variation.iter().map(|node| node.san.as_str()).collect();
+        assert_eq!(sans, ["f4", "exf4", "Nf3"]);
+        assert_eq!(variation[1].variations[0][0].san, "d5");
+        assert_eq!(variation[2].nags, [1]);
+        assert_eq!(pgn.game.moves().len(), 7);
+    }
+
+    #[test]
+    fn draw_offers() {
+        let pgn = Pgn::parse(ANNOTATED).unwrap();
+
+        let offers: Vec<_> = pgn.moves.iter().map(|node| node.draw_offer).collect();
+        assert_eq!(offers, [false, false, false, false, true, false, false]);
+        assert!(pgn.moves[4].variations.is_empty());
+    }
+
+    #[test]
+    fn move_numbers_and_en_passant() {
+        let pgn = Pgn::parse("1.e4 Nf6 2.e5 d5 3.exd6 e.p. 3...exd6 *").unwrap();
+
+        assert_eq!(pgn.game.moves(), ["e4", "Nf6", "e5", "d5", "exd6", "exd6"]);
+        assert_eq!(pgn.tag("Result"), Some("*"));
+    }
+
+    #[test]
+    fn writes_what_it_reads() {
+        let pgn = Pgn::parse(ANNOTATED).unwrap();
+        let written = Pgn::parse(&pgn.to_string()).unwrap();
+
+        assert_eq!(written.tags, pgn.tags);
+        assert_eq!(written.moves, pgn.moves);
+    }
+
+    #[test]
+    fn illegal_variation() {
+        assert!(Pgn::parse("1. e4 (1. e5) e5 *").is_err());
+        assert!(Pgn::parse("1. e4 e5 2. Ke3 *").is_err());
+    }
+}
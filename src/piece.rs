@@ -1,13 +1,23 @@
-use crate::error::CatchAllError;
+use crate::error::{CatchAllError, IllegalMoveReason};
 use crate::position::Position;
 use crate::r#move::{Action, Direction, Move};
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Color {
     White,
     Black,
 }
 
+impl Color {
+    pub fn opposite(&self) -> Self {
+        match self {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        }
+    }
+}
+
 impl From<Position> for Color {
     fn from(position: Position) -> Self {
         if (position.file + position.rank) % 2 == 0 {
@@ -19,59 +29,118 @@ impl From<Position> for Color {
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct MoveCounter(pub u32);
 
 impl MoveCounter {
     pub fn increment(&mut self) {
         self.0 += 1;
     }
+}
 
-    pub fn decrement(&mut self) {
-        self.0 -= 1;
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Promotion {
+    Knight,
+    Bishop,
+    Rook,
+    Queen,
+}
+
+impl Promotion {
+    pub fn piece(&self, color: Color) -> Piece {
+        match self {
+            Promotion::Knight => Piece::Knight(color),
+            Promotion::Bishop => Piece::Bishop(color),
+            Promotion::Rook => Piece::Rook(color),
+            Promotion::Queen => Piece::Queen(color),
+        }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Piece {
     Pawn(Color, MoveCounter),
     Knight(Color),
     Bishop(Color),
-    Rook(Color, MoveCounter),
+    Rook(Color),
     Queen(Color),
-    King(Color, MoveCounter),
+    King(Color),
 }
 
 impl Piece {
     pub fn update(&mut self) {
         match self {
-            Piece::Pawn(_, ref mut counter)
-            | Piece::Rook(_, ref mut counter)
-            | Piece::King(_, ref mut counter) => {
-                counter.increment();
-            }
+            Piece::Pawn(_, ref mut counter) => counter.increment(),
             _ => (),
         }
     }
 
-    pub fn revert(&mut self) {
+    pub fn color(&self) -> Color {
         match self {
-            Piece::Pawn(_, ref mut counter)
-            | Piece::Rook(_, ref mut counter)
-            | Piece::King(_, ref mut counter) => {
-                counter.decrement();
-            }
-            _ => (),
+            Piece::Pawn(color, _) => *color,
+            Piece::Knight(color) => *color,
+            Piece::Bishop(color) => *color,
+            Piece::Rook(color) => *color,
+            Piece::Queen(color) => *color,
+            Piece::King(color) => *color,
         }
     }
 
-    pub fn color(&self) -> Color {
+    // Material value in centipawns, the king being priceless.
+    pub fn value(&self) -> i32 {
         match self {
-            Piece::Pawn(color, _) => color.clone(),
-            Piece::Knight(color) => color.clone(),
-            Piece::Bishop(color) => color.clone(),
-            Piece::Rook(color, _) => color.clone(),
-            Piece::Queen(color) => color.clone(),
-            Piece::King(color, _) => color.clone(),
+            Piece::Pawn(_, _) => 100,
+            Piece::Knight(_) => 320,
+            Piece::Bishop(_) => 330,
+            Piece::Rook(_) => 500,
+            Piece::Queen(_) => 900,
+            Piece::King(_) => 0,
+        }
+    }
+
+    // Letter used by FEN, uppercase for white and lowercase for black.
+    pub fn symbol(&self) -> char {
+        let symbol = self.to_string().chars().next().unwrap_or('?');
+
+        match self.color() {
+            Color::White => symbol,
+            Color::Black => symbol.to_ascii_lowercase(),
+        }
+    }
+
+    pub fn from_symbol(symbol: char) -> Option<Self> {
+        let color = match symbol.is_ascii_uppercase() {
+            true => Color::White,
+            false => Color::Black,
+        };
+
+        match symbol.to_ascii_uppercase() {
+            'P' => Some(Piece::Pawn(color, MoveCounter(0))),
+            'N' => Some(Piece::Knight(color)),
+            'B' => Some(Piece::Bishop(color)),
+            'R' => Some(Piece::Rook(color)),
+            'Q' => Some(Piece::Queen(color)),
+            'K' => Some(Piece::King(color)),
+            _ => None,
+        }
+    }
+
+    pub fn glyph(&self) -> char {
+        match (self, self.color()) {
+            (Piece::Pawn(_, _), Color::White) => '♙',
+            (Piece::Knight(_), Color::White) => '♘',
+            (Piece::Bishop(_), Color::White) => '♗',
+            (Piece::Rook(_), Color::White) => '♖',
+            (Piece::Queen(_), Color::White) => '♕',
+            (Piece::King(_), Color::White) => '♔',
+            (Piece::Pawn(_, _), Color::Black) => '♟',
+            (Piece::Knight(_), Color::Black) => '♞',
+            (Piece::Bishop(_), Color::Black) => '♝',
+            (Piece::Rook(_), Color::Black) => '♜',
+            (Piece::Queen(_), Color::Black) => '♛',
+            (Piece::King(_), Color::Black) => '♚',
         }
     }
 
@@ -89,10 +158,6 @@ impl Piece {
                             |_| Some(to),
                         )
                     }
-                    // Piece::King(_, _) => {
-                    //     let mv = Move::new(from, &to, Action::Regular);
-                    //     Piece::can_reach_king(&mv, &MoveCounter(1)).map_or(None, |_| Some(to))
-                    // }
                     _ => {
                         let mv = Move::new(from, &to, Action::Regular);
                         self.can_reach(&mv).map_or(None, |_| Some(to))
@@ -107,9 +172,9 @@ impl Piece {
             Piece::Pawn(color, counter) => Piece::can_reach_pawn(mv, color, counter),
             Piece::Knight(_) => Piece::can_reach_knight(mv),
             Piece::Bishop(_) => Piece::can_reach_bishop(mv),
-            Piece::Rook(_, _) => Piece::can_reach_rook(mv),
+            Piece::Rook(_) => Piece::can_reach_rook(mv),
             Piece::Queen(_) => Piece::can_reach_queen(mv),
-            Piece::King(_, state) => Piece::can_reach_king(mv, state),
+            Piece::King(_) => Piece::can_reach_king(mv),
         }
     }
 
@@ -122,7 +187,7 @@ impl Piece {
             (Move::Straight(Direction::Down, 2, Action::Regular), Color::Black, MoveCounter(0)) => Ok(()),
             (Move::Straight(Direction::Down, 1, Action::Regular), Color::Black, _) => Ok(()),
             (Move::Diagonal(Direction::Down, Direction::Left | Direction::Right, 1, Action::Capture), Color::Black, _) => Ok(()),
-            _ => Err(CatchAllError::UnreachableField),
+            _ => Err(CatchAllError::IllegalMove(IllegalMoveReason::Unreachable)),
         }
     }
 
@@ -130,7 +195,7 @@ impl Piece {
     fn can_reach_knight(mv: &Move) -> Result<(), CatchAllError> {
         match mv {
             Move::Jump(_) => Ok(()),
-            _ => Err(CatchAllError::UnreachableField),
+            _ => Err(CatchAllError::IllegalMove(IllegalMoveReason::Unreachable)),
         }
     }
 
@@ -138,7 +203,7 @@ impl Piece {
     fn can_reach_bishop(mv: &Move) -> Result<(), CatchAllError> {
         match mv {
             Move::Diagonal(_, _, _, _) => Ok(()),
-            _ => Err(CatchAllError::UnreachableField),
+            _ => Err(CatchAllError::IllegalMove(IllegalMoveReason::Unreachable)),
         }
     }
 
@@ -146,7 +211,7 @@ impl Piece {
     fn can_reach_rook(mv: &Move) -> Result<(), CatchAllError> {
         match mv {
             Move::Straight(_, _, _) => Ok(()),
-            _ => Err(CatchAllError::UnreachableField),
+            _ => Err(CatchAllError::IllegalMove(IllegalMoveReason::Unreachable)),
         }
     }
 
@@ -155,18 +220,18 @@ impl Piece {
         match mv {
             Move::Straight(_, _, _) => Ok(()),
             Move::Diagonal(_, _, _, _) => Ok(()),
-            _ => Err(CatchAllError::UnreachableField),
+            _ => Err(CatchAllError::IllegalMove(IllegalMoveReason::Unreachable)),
         }
     }
 
     #[rustfmt::skip]
-    fn can_reach_king(mv: &Move, counter: &MoveCounter) -> Result<(), CatchAllError> {
-        match (mv, counter) {
-            (Move::Straight(_, 1, _), _) => Ok(()),
-            (Move::Diagonal(_, _, 1, _), _) => Ok(()),
-            (Move::Straight(Direction::Left, 2, Action::Regular), MoveCounter(0)) => Ok(()),
-            (Move::Straight(Direction::Right, 2, Action::Regular), MoveCounter(0)) => Ok(()),
-            _ => Err(CatchAllError::UnreachableField),
+    fn can_reach_king(mv: &Move) -> Result<(), CatchAllError> {
+        // Castling rights are not a property of the king and are assessed by the board.
+        match mv {
+            Move::Straight(_, 1, _) => Ok(()),
+            Move::Diagonal(_, _, 1, _) => Ok(()),
+            Move::Straight(Direction::Left | Direction::Right, 2, Action::Regular) => Ok(()),
+            _ => Err(CatchAllError::IllegalMove(IllegalMoveReason::Unreachable)),
         }
     }
 }
@@ -177,15 +242,15 @@ impl ToString for Piece {
             // Piece::Pawn(_, _) => "♟".to_string(),
             // Piece::Knight(_) => "♞".to_string(),
             // Piece::Bishop(_) => "♝".to_string(),
-            // Piece::Rook(_, _) => "♜".to_string(),
+            // Piece::Rook(_) => "♜".to_string(),
             // Piece::Queen(_) => "♛".to_string(),
-            // Piece::King(_, _) => "♚".to_string(),
+            // Piece::King(_) => "♚".to_string(),
             Piece::Pawn(_, _) => "P".to_string(),
             Piece::Knight(_) => "N".to_string(),
             Piece::Bishop(_) => "B".to_string(),
-            Piece::Rook(_, _) => "R".to_string(),
+            Piece::Rook(_) => "R".to_string(),
             Piece::Queen(_) => "Q".to_string(),
-            Piece::King(_, _) => "K".to_string(),
+            Piece::King(_) => "K".to_string(),
         }
     }
 }
@@ -0,0 +1,27 @@
+use crate::board::Board;
+use crate::error::CatchAllError;
+use crate::svg;
+
+use resvg::usvg::{TreeParsing, TreeTextToPath};
+use resvg::{tiny_skia, usvg};
+use std::path::Path;
+
+// Rasterizes the SVG diagram, drawing the piece glyphs with the system fonts.
+pub fn save(board: &Board, highlight: bool, path: &Path) -> Result<(), CatchAllError> {
+    let mut fonts = usvg::fontdb::Database::new();
+    fonts.load_system_fonts();
+
+    let mut tree = usvg::Tree::from_str(&svg::render(board, highlight), &usvg::Options::default())
+        .map_err(|_| CatchAllError::ImageExport)?;
+    tree.convert_text(&fonts);
+
+    let size = tree.size.to_int_size();
+    let mut pixmap =
+        tiny_skia::Pixmap::new(size.width(), size.height()).ok_or(CatchAllError::ImageExport)?;
+
+    resvg::Tree::from_usvg(&tree).render(tiny_skia::Transform::default(), &mut pixmap.as_mut());
+
+    pixmap
+        .save_png(path)
+        .map_err(|_| CatchAllError::ImageExport)
+}
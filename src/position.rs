@@ -1,3 +1,8 @@
+use crate::error::CatchAllError;
+
+use std::fmt;
+use std::str::FromStr;
+
 pub struct Distance {
     pub file: isize,
     pub rank: isize,
@@ -26,12 +31,61 @@ impl Default for Position {
 
 impl Position {
     pub fn new(file: usize, rank: usize) -> Self {
+        Self::try_new(file, rank).unwrap_or_else(|| panic!("Invalid position ({}, {})", file, rank))
+    }
+
+    // Square at the given coordinates, if they lie on the board.
+    pub fn try_new(file: usize, rank: usize) -> Option<Self> {
         let position = Self { file, rank };
-        assert!(position.valid(), "Invalid position ({}, {})", file, rank);
-        position
+        position.valid().then_some(position)
+    }
+
+    // Square the given number of files and ranks away, if that is still on the board.
+    pub fn offset(&self, files: isize, ranks: isize) -> Option<Self> {
+        Self::try_new(
+            self.file.checked_add_signed(files)?,
+            self.rank.checked_add_signed(ranks)?,
+        )
     }
 
     fn valid(&self) -> bool {
         (self.file < 8) && (self.rank < 8)
     }
 }
+
+impl fmt::Display for Position {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", (b'a' + self.file as u8) as char, self.rank + 1)
+    }
+}
+
+impl FromStr for Position {
+    type Err = CatchAllError;
+
+    fn from_str(square: &str) -> Result<Self, Self::Err> {
+        match square.as_bytes() {
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                Ok(Self::new((file - b'a') as usize, (rank - b'1') as usize))
+            }
+            _ => Err(CatchAllError::BadSquare),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Position {
+    // Squares serialize as algebraic strings so that they can key JSON maps.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Position {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let square = <String as serde::Deserialize>::deserialize(deserializer)?;
+        square
+            .parse()
+            .map_err(|_| serde::de::Error::custom(format!("invalid square {}", square)))
+    }
+}
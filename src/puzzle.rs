@@ -0,0 +1,265 @@
+use crate::error::PuzzleError;
+use crate::game::Game;
+use crate::lan::Lan;
+use crate::outcome::Outcome;
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+
+pub const DEFAULT_PATH: &str = "puzzles.csv";
+const HEADER: &str = "PuzzleId,FEN,Moves,Rating,RatingDeviation,Popularity,NbPlays,Themes,GameUrl,OpeningTags";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Puzzle {
+    pub id: String,
+    // Position before the opponent's move that sets up the puzzle.
+    pub fen: String,
+    pub setup: Lan,
+    // The solver's moves and the opponent's replies in turn, starting with the solver.
+    pub solution: Vec<Lan>,
+    pub rating: Option<u32>,
+    pub themes: Vec<String>,
+}
+
+impl Puzzle {
+    // Reads a line of the lichess puzzle database: id, FEN, moves, rating, rating deviation,
+    // popularity, plays, themes, game URL and opening. Its first move is the opponent's,
+    // setting up the position the solver is presented with.
+    pub fn from_csv(line: &str) -> Result<Self, PuzzleError> {
+        let fields: Vec<&str> = line.trim().split(',').collect();
+        let (id, fen, moves) = match fields.as_slice() {
+            [id, fen, moves, ..] => (id, fen, moves),
+            _ => return Err(PuzzleError::Format),
+        };
+
+        let mut moves = moves
+            .split_whitespace()
+            .map(|lan| Lan::parse(lan).map_err(|_| PuzzleError::Format));
+        let setup = moves.next().ok_or(PuzzleError::Format)??;
+        let solution = moves.collect::<Result<Vec<_>, _>>()?;
+
+        if solution.is_empty() {
+            return Err(PuzzleError::Format);
+        }
+
+        let puzzle = Self {
+            id: id.to_string(),
+            fen: fen.to_string(),
+            setup,
+            solution,
+            rating: fields.get(3).and_then(|rating| rating.parse().ok()),
+            themes: fields
+                .get(7)
+                .map(|themes| themes.split_whitespace().map(String::from).collect())
+                .unwrap_or_default(),
+        };
+
+        // Playing the whole line through makes sure every move of it is legal.
+        let mut game = puzzle.game()?;
+        for lan in &puzzle.solution {
+            play(&mut game, lan)?;
+        }
+
+        Ok(puzzle)
+    }
+
+    // Writes the puzzle as a line of the lichess puzzle database, leaving out what is unknown.
+    pub fn to_csv(&self) -> String {
+        let moves = [self.setup]
+            .iter()
+            .chain(&self.solution)
+            .map(|lan| lan.to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        format!(
+            "{},{},{},{},,,,{},,",
+            self.id,
+            self.fen,
+            moves,
+            self.rating
+                .map_or(String::new(), |rating| rating.to_string()),
+            self.themes.join(" ")
+        )
+    }
+
+    // The game at the position the solver is presented with.
+    pub fn game(&self) -> Result<Game, PuzzleError> {
+        let mut game = Game::from_fen(&self.fen).map_err(PuzzleError::Fen)?;
+        play(&mut game, &self.setup)?;
+        Ok(game)
+    }
+}
+
+fn play(game: &mut Game, lan: &Lan) -> Result<(), PuzzleError> {
+    game.play_move(&lan.from, &lan.to, lan.promotion)
+        .map(|_| ())
+        .map_err(|e| PuzzleError::IllegalMove(lan.to_string(), e))
+}
+
+// Reads a puzzle collection in the lichess CSV format, with or without its header line.
+pub fn load(path: &str) -> Result<Vec<Puzzle>, PuzzleError> {
+    fs::read_to_string(path)
+        .map_err(|e| PuzzleError::Io(e.kind()))?
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with("PuzzleId"))
+        .map(Puzzle::from_csv)
+        .collect()
+}
+
+// Adds puzzles to a collection, starting a new one with the lichess header line.
+pub fn append(path: &str, puzzles: &[Puzzle]) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+
+    if file.metadata()?.len() == 0 {
+        writeln!(file, "{}", HEADER)?;
+    }
+
+    puzzles
+        .iter()
+        .try_for_each(|puzzle| writeln!(file, "{}", puzzle.to_csv()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Verdict {
+    // The move was right and the opponent answers with the given reply.
+    Correct(Lan),
+    Solved,
+    Wrong,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Record {
+    pub solved: usize,
+    pub failed: usize,
+    // Puzzles solved in a row without a mistake, and the longest such run.
+    pub streak: usize,
+    pub best_streak: usize,
+}
+
+// Works through a collection of puzzles one after the other, keeping score.
+pub struct Training {
+    puzzles: Vec<Puzzle>,
+    index: usize,
+    // Number of moves of the current solution already played.
+    progress: usize,
+    // Whether the current puzzle has gone wrong, so that solving it no longer counts.
+    failed: bool,
+    verdict: Option<Verdict>,
+    record: Record,
+}
+
+impl Training {
+    // A training needs at least one puzzle.
+    pub fn new(puzzles: Vec<Puzzle>) -> Option<Self> {
+        if puzzles.is_empty() {
+            return None;
+        }
+
+        Some(Self {
+            puzzles,
+            index: 0,
+            progress: 0,
+            failed: false,
+            verdict: None,
+            record: Record::default(),
+        })
+    }
+
+    pub fn puzzle(&self) -> &Puzzle {
+        &self.puzzles[self.index]
+    }
+
+    // Number of the current puzzle, counting from one, and the size of the collection.
+    pub fn position(&self) -> (usize, usize) {
+        (self.index + 1, self.puzzles.len())
+    }
+
+    pub fn verdict(&self) -> Option<Verdict> {
+        self.verdict
+    }
+
+    pub fn record(&self) -> Record {
+        self.record
+    }
+
+    // Sets up the current puzzle from the beginning.
+    pub fn start(&mut self) -> Result<Game, PuzzleError> {
+        self.progress = 0;
+        self.verdict = None;
+        self.puzzle().game()
+    }
+
+    // Moves on to the next puzzle, starting over once the collection is exhausted.
+    pub fn next_puzzle(&mut self) -> Result<Game, PuzzleError> {
+        self.index = (self.index + 1) % self.puzzles.len();
+        self.failed = false;
+        self.start()
+    }
+
+    // Judges the solver's latest move, given the game right after it.
+    // Any move that mates is as good as the one in the solution.
+    pub fn check(&mut self, game: &Game) -> Result<Verdict, PuzzleError> {
+        if self.progress >= self.solution().len() {
+            return Ok(Verdict::Solved);
+        }
+
+        let expected = self.expected_game()?;
+        let correct = expected.board().hash(&expected.color()) == game.board().hash(&game.color())
+            || matches!(game.outcome(), Some(Outcome::Checkmate(_)));
+
+        let reply = self.solution().get(self.progress + 1).copied();
+        let verdict = match (correct, reply) {
+            (false, _) => {
+                self.fail();
+                Verdict::Wrong
+            }
+            (true, Some(reply)) if game.outcome().is_none() => {
+                self.progress += 2;
+                Verdict::Correct(reply)
+            }
+            (true, _) => {
+                self.progress = self.solution().len();
+                if !self.failed {
+                    self.record.solved += 1;
+                    self.record.streak += 1;
+                    self.record.best_streak = self.record.best_streak.max(self.record.streak);
+                }
+                Verdict::Solved
+            }
+        };
+
+        self.verdict = Some(verdict);
+        Ok(verdict)
+    }
+
+    // Gives away the next move of the solution, which counts as failing the puzzle.
+    pub fn reveal(&mut self) -> Option<Lan> {
+        let lan = self.solution().get(self.progress).copied()?;
+        self.fail();
+        Some(lan)
+    }
+
+    fn solution(&self) -> &[Lan] {
+        &self.puzzle().solution
+    }
+
+    fn fail(&mut self) {
+        if !self.failed {
+            self.failed = true;
+            self.record.failed += 1;
+            self.record.streak = 0;
+        }
+    }
+
+    // The game as it should stand after the solver's next move.
+    fn expected_game(&self) -> Result<Game, PuzzleError> {
+        let mut game = self.puzzle().game()?;
+
+        for lan in self.solution().iter().take(self.progress + 1) {
+            play(&mut game, lan)?;
+        }
+
+        Ok(game)
+    }
+}
@@ -0,0 +1,289 @@
+use crate::board::Board;
+use crate::error::CatchAllError;
+use crate::lan::Lan;
+use crate::piece::{Color, Promotion};
+use crate::position::Position;
+use crate::r#move::{Direction, MoveKind};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum San {
+    Castle(Direction),
+    Move {
+        // Uppercase piece letter, 'P' for pawns.
+        symbol: char,
+        file: Option<usize>,
+        rank: Option<usize>,
+        to: Position,
+        promotion: Option<Promotion>,
+    },
+}
+
+impl San {
+    pub fn parse(san: &str) -> Result<Self, CatchAllError> {
+        let san = san
+            .trim()
+            .trim_end_matches("e.p.")
+            .trim_end_matches(|c| "+#!?".contains(c));
+
+        match san {
+            "O-O" | "0-0" => return Ok(San::Castle(Direction::Right)),
+            "O-O-O" | "0-0-0" => return Ok(San::Castle(Direction::Left)),
+            _ => (),
+        }
+
+        // The promotion piece may be given with or without a leading '='.
+        let (san, promotion) = match san.char_indices().last() {
+            Some((i, 'Q')) => (&san[..i], Some(Promotion::Queen)),
+            Some((i, 'R')) => (&san[..i], Some(Promotion::Rook)),
+            Some((i, 'B')) => (&san[..i], Some(Promotion::Bishop)),
+            Some((i, 'N')) => (&san[..i], Some(Promotion::Knight)),
+            _ => (san, None),
+        };
+        let san = san.trim_end_matches('=');
+
+        let (symbol, rest) = match san.chars().next() {
+            Some(symbol @ ('N' | 'B' | 'R' | 'Q' | 'K')) => (symbol, &san[1..]),
+            Some(_) => ('P', san),
+            None => return Err(CatchAllError::BadSan),
+        };
+
+        let rest: Vec<u8> = rest.bytes().filter(|c| *c != b'x').collect();
+        let (hint, square) = rest.split_at(rest.len().checked_sub(2).ok_or(CatchAllError::BadSan)?);
+
+        let to = std::str::from_utf8(square)
+            .ok()
+            .and_then(|square| square.parse::<Position>().ok())
+            .ok_or(CatchAllError::BadSan)?;
+
+        let (file, rank) = match hint {
+            [] => (None, None),
+            [file @ b'a'..=b'h'] => (Some((file - b'a') as usize), None),
+            [rank @ b'1'..=b'8'] => (None, Some((rank - b'1') as usize)),
+            [file @ b'a'..=b'h', rank @ b'1'..=b'8'] => {
+                (Some((file - b'a') as usize), Some((rank - b'1') as usize))
+            }
+            _ => return Err(CatchAllError::BadSan),
+        };
+
+        if symbol != 'P' && promotion.is_some() {
+            return Err(CatchAllError::BadSan);
+        }
+
+        Ok(San::Move {
+            symbol,
+            file,
+            rank,
+            to,
+            promotion,
+        })
+    }
+
+    // Matches the move against the legal moves of the given side.
+    #[rustfmt::skip]
+    pub fn resolve(&self, board: &mut Board, color: &Color) -> Result<(Position, Position, Option<Promotion>), CatchAllError> {
+        let moves = board.legal_moves(color);
+
+        let candidates: Vec<_> = match self {
+            San::Castle(direction) => moves
+                .into_iter()
+                .filter(|(_, _, kind)| kind == &MoveKind::Castle(*direction))
+                .map(|(from, to, _)| (from, to, None))
+                .collect(),
+            San::Move { symbol, file, rank, to, promotion } => moves
+                .into_iter()
+                .filter(|(from, target, kind)| {
+                    target == to
+                        && &kind.promotion() == promotion
+                        && file.is_none_or(|file| file == from.file)
+                        && rank.is_none_or(|rank| rank == from.rank)
+                        && board
+                            .at(from)
+                            .is_ok_and(|piece| &piece.symbol().to_ascii_uppercase() == symbol)
+                })
+                .map(|(from, to, _)| (from, to, *promotion))
+                .collect(),
+        };
+
+        match candidates[..] {
+            [candidate] => Ok(candidate),
+            [] => Err(CatchAllError::NoMatchingMove),
+            _ => Err(CatchAllError::AmbiguousSan),
+        }
+    }
+
+    // Formats a legal move, played without a promotion piece as a queen promotion.
+    #[rustfmt::skip]
+    pub fn format(board: &mut Board, color: &Color, from: &Position, to: &Position, promotion: Option<Promotion>) -> Result<String, CatchAllError> {
+        let moves = board.legal_moves(color);
+        let symbol = board.at(from)?.symbol().to_ascii_uppercase();
+
+        let kind = moves
+            .iter()
+            .find(|(f, t, kind)| {
+                f == from && t == to && kind.promotion() == promotion.or(kind.promotion().and(Some(Promotion::Queen)))
+            })
+            .map(|(_, _, kind)| *kind)
+            .ok_or(CatchAllError::NoMatchingMove)?;
+
+        let rivals: Vec<&Position> = moves
+            .iter()
+            .filter(|(f, t, _)| {
+                t == to && f != from && board.at(f).is_ok_and(|piece| piece.symbol().to_ascii_uppercase() == symbol)
+            })
+            .map(|(f, _, _)| f)
+            .collect();
+
+        let capture = matches!(
+            kind,
+            MoveKind::Capture | MoveKind::EnPassant | MoveKind::PromotionCapture(_)
+        );
+
+        let san = match kind {
+            MoveKind::Castle(Direction::Right) => "O-O".to_string(),
+            MoveKind::Castle(Direction::Left) => "O-O-O".to_string(),
+            _ => {
+                let square = from.to_string();
+
+                let hint = match symbol {
+                    'P' if capture => &square[..1],
+                    'P' => "",
+                    _ if rivals.is_empty() => "",
+                    _ if rivals.iter().all(|rival| rival.file != from.file) => &square[..1],
+                    _ if rivals.iter().all(|rival| rival.rank != from.rank) => &square[1..],
+                    _ => &square,
+                };
+
+                format!(
+                    "{}{}{}{}{}",
+                    if symbol == 'P' { String::new() } else { symbol.to_string() },
+                    hint,
+                    if capture { "x" } else { "" },
+                    to,
+                    kind.promotion().map_or(String::new(), |p| format!("={}", p.piece(Color::White).symbol())),
+                )
+            }
+        };
+
+        board.advance(color, from, to, kind.promotion())?;
+
+        let opponent = color.opposite();
+        let suffix = match (board.in_check(&opponent), board.legal_moves(&opponent).is_empty()) {
+            (Ok(true), true) => "#",
+            (Ok(true), false) => "+",
+            _ => "",
+        };
+
+        board.revert()?;
+
+        Ok(san + suffix)
+    }
+
+    // Formats a sequence of moves played one after another from the given position.
+    #[rustfmt::skip]
+    pub fn format_line(board: &Board, color: &Color, line: &[Lan]) -> Result<Vec<String>, CatchAllError> {
+        let mut board = board.clone();
+        let mut color = *color;
+
+        line.iter()
+            .map(|lan| {
+                let san = San::format(&mut board, &color, &lan.from, &lan.to, lan.promotion)?;
+                board.advance(&color, &lan.from, &lan.to, lan.promotion)?;
+                color = color.opposite();
+                Ok(san)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::San;
+    use crate::fen::Fen;
+    use crate::piece::Promotion;
+    use crate::position::Position;
+    use crate::r#move::Direction;
+
+    fn format(fen: &str, from: &str, to: &str, promotion: Option<Promotion>) -> String {
+        let Fen {
+            mut board, color, ..
+        } = Fen::parse(fen).unwrap();
+        let (from, to) = (from.parse().unwrap(), to.parse().unwrap());
+        San::format(&mut board, &color, &from, &to, promotion).unwrap()
+    }
+
+    #[test]
+    fn parse_moves() {
+        assert_eq!(
+            San::parse("Nbd7").unwrap(),
+            San::Move {
+                symbol: 'N',
+                file: Some(1),
+                rank: None,
+                to: Position::new(3, 6),
+                promotion: None,
+            }
+        );
+        assert_eq!(
+            San::parse("R1xa3+").unwrap(),
+            San::Move {
+                symbol: 'R',
+                file: None,
+                rank: Some(0),
+                to: Position::new(0, 2),
+                promotion: None,
+            }
+        );
+        assert_eq!(
+            San::parse("Qh4e1#").unwrap(),
+            San::Move {
+                symbol: 'Q',
+                file: Some(7),
+                rank: Some(3),
+                to: Position::new(4, 0),
+                promotion: None,
+            }
+        );
+        assert_eq!(San::parse("O-O-O").unwrap(), San::Castle(Direction::Left));
+        assert_eq!(San::parse("0-0!?").unwrap(), San::Castle(Direction::Right));
+    }
+
+    #[test]
+    fn parse_promotions() {
+        let promotion = |san| match San::parse(san).unwrap() {
+            San::Move { promotion, .. } => promotion,
+            San::Castle(_) => None,
+        };
+
+        assert_eq!(promotion("e8=Q"), Some(Promotion::Queen));
+        assert_eq!(promotion("bxa1N+"), Some(Promotion::Knight));
+        assert!(San::parse("Ne8=Q").is_err());
+        assert!(San::parse("Zz9").is_err());
+        assert!(San::parse("").is_err());
+    }
+
+    #[test]
+    fn format_disambiguation() {
+        // Knights on b1 and f3 both reach d2: the file tells them apart.
+        let fen = "4k3/8/8/8/8/5N2/8/1N2K3 w - - 0 1";
+        assert_eq!(format(fen, "b1", "d2", None), "Nbd2");
+
+        // Rooks on a1 and a5 share the file: the rank tells them apart.
+        let fen = "4k3/8/8/R7/8/8/8/R3K3 w - - 0 1";
+        assert_eq!(format(fen, "a1", "a3", None), "R1a3");
+
+        // Three queens reach e4, the one on h4 only told apart by both.
+        let fen = "1k6/8/8/8/Q6Q/8/8/4K2Q w - - 0 1";
+        assert_eq!(format(fen, "h4", "e4", None), "Qh4e4");
+    }
+
+    #[test]
+    fn format_special_moves() {
+        let fen = "4k3/1P6/8/8/8/8/8/4K2R w K - 0 1";
+        assert_eq!(format(fen, "b7", "b8", Some(Promotion::Queen)), "b8=Q+");
+        assert_eq!(format(fen, "b7", "b8", Some(Promotion::Knight)), "b8=N");
+        assert_eq!(format(fen, "e1", "g1", None), "O-O");
+
+        let fen = "4k3/8/8/3pP3/8/8/8/4K3 w - d6 0 1";
+        assert_eq!(format(fen, "e5", "d6", None), "exd6");
+    }
+}
@@ -0,0 +1,293 @@
+use crate::board::Board;
+use crate::error::CatchAllError;
+use crate::evaluation;
+use crate::lan::Lan;
+use crate::ordering::MoveOrdering;
+use crate::piece::Color;
+use crate::timing::{Instant, SystemTime};
+use crate::transposition::{Bound, Entry, TranspositionTable};
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+pub const MATE: i32 = 100_000;
+// Size of the transposition table of the searches the app runs on its own.
+pub const TABLE_MEGABYTES: usize = 16;
+const INFINITY: i32 = MATE + 1;
+// Scores beyond this bound announce a forced mate.
+const MATE_BOUND: i32 = MATE - 1000;
+// Number of nodes between two looks at the clock, a power of two.
+const CLOCK_INTERVAL: u64 = 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    pub depth: usize,
+    pub time: Option<Duration>,
+    // Largest random offset in centipawns added to every leaf evaluation.
+    pub noise: i32,
+}
+
+impl Limits {
+    pub fn depth(depth: usize) -> Self {
+        Self {
+            depth,
+            time: None,
+            noise: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    pub best_move: Option<Lan>,
+    // Expected continuation starting with the best move.
+    pub pv: Vec<Lan>,
+    // Centipawns from the perspective of the side to move.
+    pub score: i32,
+    pub depth: usize,
+    pub nodes: u64,
+}
+
+pub struct Search {
+    table: TranspositionTable,
+    ordering: MoveOrdering,
+    nodes: u64,
+    deadline: Option<Instant>,
+    // Raised from another thread to end the search early.
+    stop: Arc<AtomicBool>,
+    // Whether the search may be cut short, which it can't before the first iteration completes.
+    interruptible: bool,
+    stopped: bool,
+    noise: i32,
+    seed: u64,
+}
+
+impl Search {
+    pub fn new(megabytes: usize) -> Self {
+        Self {
+            table: TranspositionTable::new(megabytes),
+            ordering: MoveOrdering::new(),
+            nodes: 0,
+            deadline: None,
+            stop: Arc::new(AtomicBool::new(false)),
+            interruptible: false,
+            stopped: false,
+            noise: 0,
+            seed: SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .map_or(0, |time| time.as_nanos() as u64)
+                | 1,
+        }
+    }
+
+    #[rustfmt::skip]
+    pub fn search(&mut self, board: &mut Board, color: &Color, limits: &Limits) -> Result<SearchResult, CatchAllError> {
+        self.search_with(board, color, limits, |_| ())
+    }
+
+    // Deepens iteratively up to the given depth, so shallower results order the deeper searches.
+    // Each completed iteration is reported, one cut short by a limit is discarded for the last complete one.
+    #[rustfmt::skip]
+    pub fn search_with(&mut self, board: &mut Board, color: &Color, limits: &Limits, mut report: impl FnMut(&SearchResult)) -> Result<SearchResult, CatchAllError> {
+        self.table.new_search();
+        self.ordering.new_search();
+        self.nodes = 0;
+        self.deadline = limits.time.map(|time| Instant::now() + time);
+        self.interruptible = false;
+        self.stopped = false;
+        self.noise = limits.noise;
+
+        let mut result = SearchResult {
+            best_move: None,
+            pv: Vec::new(),
+            score: 0,
+            depth: 0,
+            nodes: 0,
+        };
+
+        for depth in 1..=limits.depth.max(1) {
+            let score = self.negamax(board, color, depth, 0, -INFINITY, INFINITY)?;
+
+            if self.stopped {
+                break;
+            }
+
+            let pv = self.principal_variation(board, color, depth)?;
+
+            result = SearchResult {
+                best_move: pv.first().copied(),
+                pv,
+                score,
+                depth,
+                nodes: self.nodes,
+            };
+            report(&result);
+
+            self.interruptible = true;
+            if self.should_stop() {
+                break;
+            }
+        }
+
+        Ok(result)
+    }
+
+    // Handle to end a running search from another thread, which then returns its last complete result.
+    pub fn stop_signal(&self) -> Arc<AtomicBool> {
+        Arc::clone(&self.stop)
+    }
+
+    pub fn clear(&mut self) {
+        self.table.clear();
+        self.ordering.clear();
+    }
+
+    #[rustfmt::skip]
+    fn negamax(&mut self, board: &mut Board, color: &Color, depth: usize, ply: usize, alpha: i32, beta: i32) -> Result<i32, CatchAllError> {
+        self.nodes += 1;
+
+        if self.nodes & (CLOCK_INTERVAL - 1) == 0 && self.should_stop() {
+            self.stopped = true;
+        }
+        if self.stopped {
+            return Ok(0);
+        }
+
+        if ply > 0 && board.halfmove_clock() >= 100 {
+            return Ok(0);
+        }
+
+        let hash = board.hash(color);
+        let entry = self.table.probe(hash).copied();
+
+        if let Some(entry) = entry.filter(|entry| ply > 0 && entry.depth as usize >= depth) {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return Ok(score),
+                Bound::Lower if score >= beta => return Ok(score),
+                Bound::Upper if score <= alpha => return Ok(score),
+                _ => (),
+            }
+        }
+
+        let mut moves = board.legal_moves(color);
+
+        if moves.is_empty() {
+            return match board.in_check(color)? {
+                true => Ok(-MATE + ply as i32),
+                false => Ok(0),
+            };
+        }
+
+        if depth == 0 {
+            return Ok(evaluation::evaluate(board, color) + self.jitter());
+        }
+
+        let hash_move = entry.and_then(|entry| entry.best_move);
+        self.ordering.order(board, &mut moves, hash_move, ply);
+
+        let start = alpha;
+        let mut alpha = alpha;
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+
+        for (from, to, kind) in moves {
+            board.make(&from, &to, kind.promotion())?;
+            let score = -self.negamax(board, &color.opposite(), depth - 1, ply + 1, -beta, -alpha)?;
+            board.revert()?;
+
+            if score > best_score {
+                best_score = score;
+                best_move = Some(Lan::new(from, to, kind.promotion()));
+            }
+
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                self.ordering.cutoff(Lan::new(from, to, kind.promotion()), &kind, depth, ply);
+                break;
+            }
+        }
+
+        let bound = match best_score {
+            score if score >= beta => Bound::Lower,
+            score if score > start => Bound::Exact,
+            _ => Bound::Upper,
+        };
+
+        // Scores of an interrupted search are meaningless and must not pollute the table.
+        if !self.stopped {
+            self.table.store(Entry::new(hash, depth as u8, to_table(best_score, ply), bound, best_move));
+        }
+
+        Ok(best_score)
+    }
+
+    fn should_stop(&self) -> bool {
+        self.interruptible
+            && (self.stop.load(Ordering::Relaxed)
+                || self
+                    .deadline
+                    .is_some_and(|deadline| Instant::now() >= deadline))
+    }
+
+    // Follows the best moves stored in the table, checking each is still legal.
+    #[rustfmt::skip]
+    fn principal_variation(&self, board: &mut Board, color: &Color, depth: usize) -> Result<Vec<Lan>, CatchAllError> {
+        let mut pv = Vec::new();
+        let mut color = *color;
+
+        while pv.len() < depth {
+            let best = match self.table.probe(board.hash(&color)).and_then(|entry| entry.best_move) {
+                Some(best) => best,
+                None => break,
+            };
+
+            let legal = board.legal_moves(&color).iter().any(|(from, to, kind)| {
+                Lan::new(*from, *to, kind.promotion()) == best
+            });
+            if !legal {
+                break;
+            }
+
+            board.make(&best.from, &best.to, best.promotion)?;
+            pv.push(best);
+            color = color.opposite();
+        }
+
+        for _ in &pv {
+            board.revert()?;
+        }
+
+        Ok(pv)
+    }
+
+    fn jitter(&mut self) -> i32 {
+        if self.noise == 0 {
+            return 0;
+        }
+
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+
+        (self.seed % (2 * self.noise as u64 + 1)) as i32 - self.noise
+    }
+}
+
+// Mate scores are stored relative to the node rather than the root.
+fn to_table(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score > MATE_BOUND => score + ply as i32,
+        score if score < -MATE_BOUND => score - ply as i32,
+        score => score,
+    }
+}
+
+fn from_table(score: i32, ply: usize) -> i32 {
+    match score {
+        score if score > MATE_BOUND => score - ply as i32,
+        score if score < -MATE_BOUND => score + ply as i32,
+        score => score,
+    }
+}
@@ -0,0 +1,111 @@
+use crate::ai::Difficulty;
+use crate::error::CatchAllError;
+use crate::game::Game;
+use crate::outcome::Outcome;
+use crate::pgn::Pgn;
+use crate::piece::Color;
+use crate::search::{Search, TABLE_MEGABYTES};
+
+// Games still running after this many plies are abandoned without a result.
+const MAX_PLIES: usize = 600;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Player {
+    pub name: String,
+    pub difficulty: Difficulty,
+}
+
+impl Player {
+    pub fn new(difficulty: Difficulty) -> Self {
+        Self {
+            name: format!("chess ({})", difficulty),
+            difficulty,
+        }
+    }
+}
+
+// Results from the point of view of the first player.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tally {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+    pub unfinished: usize,
+}
+
+impl Tally {
+    // Points of the first player, a draw counting half.
+    pub fn score(&self) -> f64 {
+        self.wins as f64 + self.draws as f64 / 2.0
+    }
+}
+
+// Plays one game between the two players, claiming draws as soon as they become available.
+pub fn play(white: &Player, black: &Player) -> Result<Game, CatchAllError> {
+    let mut game = Game::new();
+    let mut searches = [Search::new(TABLE_MEGABYTES), Search::new(TABLE_MEGABYTES)];
+
+    while game.outcome().is_none() && game.ply() < MAX_PLIES {
+        if game.claimable_draw().is_some() {
+            game.claim_draw()?;
+            break;
+        }
+
+        let color = game.color();
+        let (player, search) = match color {
+            Color::White => (white, &mut searches[0]),
+            Color::Black => (black, &mut searches[1]),
+        };
+
+        let mut board = game.board().clone();
+        let best = search
+            .search(&mut board, &color, &player.difficulty.limits())?
+            .best_move
+            .ok_or(CatchAllError::NoLegalMoves)?;
+
+        game.play_move(&best.from, &best.to, best.promotion)?;
+    }
+
+    Ok(game)
+}
+
+// Plays a match of the given number of games, the first player taking white in every even round.
+#[rustfmt::skip]
+pub fn run(first: &Player, second: &Player, games: usize, mut finished: impl FnMut(&Pgn, &Tally)) -> Result<Tally, CatchAllError> {
+    let mut tally = Tally::default();
+
+    for round in 0..games {
+        let (white, black) = match round % 2 {
+            0 => (first, second),
+            _ => (second, first),
+        };
+        let first_color = match round % 2 {
+            0 => Color::White,
+            _ => Color::Black,
+        };
+
+        let game = play(white, black)?;
+
+        match game.outcome() {
+            Some(Outcome::Checkmate(winner) | Outcome::Resignation(winner) | Outcome::Timeout(winner)) if winner == first_color => tally.wins += 1,
+            Some(Outcome::Checkmate(_) | Outcome::Resignation(_) | Outcome::Timeout(_)) => tally.losses += 1,
+            Some(Outcome::Stalemate | Outcome::Draw(_)) => tally.draws += 1,
+            None => tally.unfinished += 1,
+        }
+
+        let tags = [
+            ("Event", "Self-play".to_string()),
+            ("Site", "?".to_string()),
+            ("Round", (round + 1).to_string()),
+            ("White", white.name.clone()),
+            ("Black", black.name.clone()),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value))
+        .collect();
+
+        finished(&Pgn::from_game(&game, tags), &tally);
+    }
+
+    Ok(tally)
+}
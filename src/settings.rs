@@ -0,0 +1,59 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+pub const DEFAULT_PATH: &str = "settings.txt";
+
+// Preferences kept between sessions, as lines of the form "name = value".
+pub struct Settings {
+    path: PathBuf,
+    values: BTreeMap<String, String>,
+}
+
+impl Settings {
+    // Settings from the file named by CHESS_SETTINGS, or the default one.
+    pub fn open() -> Self {
+        Settings::load(&std::env::var("CHESS_SETTINGS").unwrap_or(DEFAULT_PATH.to_string()))
+    }
+
+    // A missing or unreadable file leaves every preference at its default.
+    pub fn load(path: &str) -> Self {
+        let values = fs::read_to_string(path)
+            .unwrap_or_default()
+            .lines()
+            .filter_map(|line| line.split_once('='))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+
+        Self {
+            path: path.into(),
+            values,
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.values.get(name).map(String::as_str)
+    }
+
+    pub fn parse<T: FromStr>(&self, name: &str) -> Option<T> {
+        self.get(name).and_then(|value| value.parse().ok())
+    }
+
+    // Changes a preference and writes all of them back to the file.
+    pub fn set(&mut self, name: &str, value: impl ToString) -> io::Result<()> {
+        self.values.insert(name.to_string(), value.to_string());
+        self.save()
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let lines: String = self
+            .values
+            .iter()
+            .map(|(name, value)| format!("{} = {}\n", name, value))
+            .collect();
+
+        fs::write(&self.path, lines)
+    }
+}
@@ -0,0 +1,369 @@
+use crate::error::CatchAllError;
+use crate::piece::Piece;
+
+use resvg::usvg::tiny_skia_path::{self, PathSegment};
+use resvg::usvg::{self, NodeExt, TreeParsing};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+// Side length of the square the drawings are made on.
+pub const SIZE: f32 = 45.0;
+
+pub type Point = (f32, f32);
+pub type Rgb = (u8, u8, u8);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Segment {
+    MoveTo(Point),
+    LineTo(Point),
+    // Two control points followed by the end point.
+    CubicTo(Point, Point, Point),
+    Close,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Shape {
+    Path(Vec<Segment>),
+    Circle(Point, f32),
+}
+
+// One shape of a drawing, painted in order over the ones before it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Layer {
+    pub shape: Shape,
+    pub fill: Option<Rgb>,
+    // Outline color and width.
+    pub stroke: Option<(Rgb, f32)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PieceSet {
+    Cburnett,
+    Geometric,
+    // Directory holding the drawings as wP.svg to bK.svg.
+    Custom(PathBuf),
+}
+
+impl PieceSet {
+    pub const BUNDLED: [PieceSet; 2] = [PieceSet::Cburnett, PieceSet::Geometric];
+}
+
+impl fmt::Display for PieceSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PieceSet::Cburnett => write!(f, "Cburnett"),
+            PieceSet::Geometric => write!(f, "Geometric"),
+            PieceSet::Custom(dir) => match dir.file_name() {
+                Some(name) => write!(f, "Custom ({})", name.to_string_lossy()),
+                None => write!(f, "Custom"),
+            },
+        }
+    }
+}
+
+// File names of the drawings of a set, keyed by the glyph of the piece they show.
+const FILES: [(char, &str); 12] = [
+    ('♙', "wP.svg"),
+    ('♘', "wN.svg"),
+    ('♗', "wB.svg"),
+    ('♖', "wR.svg"),
+    ('♕', "wQ.svg"),
+    ('♔', "wK.svg"),
+    ('♟', "bP.svg"),
+    ('♞', "bN.svg"),
+    ('♝', "bB.svg"),
+    ('♜', "bR.svg"),
+    ('♛', "bQ.svg"),
+    ('♚', "bK.svg"),
+];
+
+// Piece drawings after Colin M.L. Burnett's set, reduced to paths and circles with absolute
+// coordinates and presentation attributes so that they can be read without a full SVG parser.
+const CBURNETT: [&str; 12] = [
+    include_str!("sprites/cburnett/wP.svg"),
+    include_str!("sprites/cburnett/wN.svg"),
+    include_str!("sprites/cburnett/wB.svg"),
+    include_str!("sprites/cburnett/wR.svg"),
+    include_str!("sprites/cburnett/wQ.svg"),
+    include_str!("sprites/cburnett/wK.svg"),
+    include_str!("sprites/cburnett/bP.svg"),
+    include_str!("sprites/cburnett/bN.svg"),
+    include_str!("sprites/cburnett/bB.svg"),
+    include_str!("sprites/cburnett/bR.svg"),
+    include_str!("sprites/cburnett/bQ.svg"),
+    include_str!("sprites/cburnett/bK.svg"),
+];
+
+// Plain silhouettes in the same reduced form.
+const GEOMETRIC: [&str; 12] = [
+    include_str!("sprites/geometric/wP.svg"),
+    include_str!("sprites/geometric/wN.svg"),
+    include_str!("sprites/geometric/wB.svg"),
+    include_str!("sprites/geometric/wR.svg"),
+    include_str!("sprites/geometric/wQ.svg"),
+    include_str!("sprites/geometric/wK.svg"),
+    include_str!("sprites/geometric/bP.svg"),
+    include_str!("sprites/geometric/bN.svg"),
+    include_str!("sprites/geometric/bB.svg"),
+    include_str!("sprites/geometric/bR.svg"),
+    include_str!("sprites/geometric/bQ.svg"),
+    include_str!("sprites/geometric/bK.svg"),
+];
+
+// Drawings scaled to a size, by glyph and the size in whole pixels.
+type Scaled = HashMap<(char, u32), Arc<[Layer]>>;
+
+// The drawings of a piece set, together with copies scaled to the sizes they have been drawn at.
+#[derive(Debug, Default)]
+pub struct Sprites {
+    layers: HashMap<char, Vec<Layer>>,
+    scaled: Mutex<Scaled>,
+}
+
+impl Sprites {
+    // Bundled sets are read with the reduced parser, user-provided ones with a full SVG parser.
+    pub fn load(set: &PieceSet) -> Result<Self, CatchAllError> {
+        let layers = match set {
+            PieceSet::Cburnett => bundled(&CBURNETT),
+            PieceSet::Geometric => bundled(&GEOMETRIC),
+            PieceSet::Custom(dir) => FILES
+                .iter()
+                .map(|(glyph, file)| {
+                    let path = dir.join(file);
+                    std::fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|svg| convert(&svg))
+                        .map(|layers| (*glyph, layers))
+                        .ok_or(CatchAllError::BadPieceSet(
+                            path.to_string_lossy().to_string(),
+                        ))
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        Ok(Self {
+            layers,
+            scaled: Mutex::new(HashMap::new()),
+        })
+    }
+
+    // The drawing of a piece on a square of the given side, centered on the origin.
+    pub fn sprite(&self, piece: &Piece, size: f32) -> Arc<[Layer]> {
+        let key = (piece.glyph(), size.round() as u32);
+        let mut scaled = match self.scaled.lock() {
+            Ok(scaled) => scaled,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+
+        scaled
+            .entry(key)
+            .or_insert_with(|| {
+                let layers = self.layers.get(&key.0).map_or(&[][..], |layers| layers);
+                layers
+                    .iter()
+                    .map(|layer| layer.scaled(size / SIZE))
+                    .collect()
+            })
+            .clone()
+    }
+}
+
+impl Layer {
+    // The layer scaled by the given factor about the center of its square, which becomes the origin.
+    fn scaled(&self, scale: f32) -> Layer {
+        let point = |(x, y): Point| ((x - SIZE / 2.0) * scale, (y - SIZE / 2.0) * scale);
+
+        let shape = match &self.shape {
+            Shape::Circle(center, radius) => Shape::Circle(point(*center), radius * scale),
+            Shape::Path(segments) => Shape::Path(
+                segments
+                    .iter()
+                    .map(|segment| match *segment {
+                        Segment::MoveTo(to) => Segment::MoveTo(point(to)),
+                        Segment::LineTo(to) => Segment::LineTo(point(to)),
+                        Segment::CubicTo(first, second, to) => {
+                            Segment::CubicTo(point(first), point(second), point(to))
+                        }
+                        Segment::Close => Segment::Close,
+                    })
+                    .collect(),
+            ),
+        };
+
+        Layer {
+            shape,
+            fill: self.fill,
+            stroke: self.stroke.map(|(color, width)| (color, width * scale)),
+        }
+    }
+}
+
+fn bundled(sources: &[&str; 12]) -> HashMap<char, Vec<Layer>> {
+    FILES
+        .iter()
+        .zip(sources)
+        .map(|((glyph, _), svg)| (*glyph, layers(svg)))
+        .collect()
+}
+
+// Reads any SVG document, flattening it into paths with their transforms applied and scaling its
+// view box to a square of side SIZE. Gradients and patterns are left unpainted.
+fn convert(svg: &str) -> Option<Vec<Layer>> {
+    let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).ok()?;
+    let rect = tree.view_box.rect;
+    let scale = SIZE / rect.width().max(rect.height());
+    let rgb = |paint: &usvg::Paint| match paint {
+        usvg::Paint::Color(color) => Some((color.red, color.green, color.blue)),
+        _ => None,
+    };
+
+    let mut layers = Vec::new();
+    for node in tree.root.descendants() {
+        let path = match &*node.borrow() {
+            usvg::NodeKind::Path(path) => path.clone(),
+            _ => continue,
+        };
+
+        let transform = node.abs_transform();
+        let point = |point: tiny_skia_path::Point| {
+            let mut points = [point];
+            transform.map_points(&mut points);
+            (
+                (points[0].x - rect.x()) * scale,
+                (points[0].y - rect.y()) * scale,
+            )
+        };
+
+        // Quadratic curves become cubic ones, which need the point the curve starts from.
+        let mut segments = Vec::new();
+        let mut last = (0.0, 0.0);
+        for segment in path.data.segments() {
+            let segment = match segment {
+                PathSegment::MoveTo(to) => Segment::MoveTo(point(to)),
+                PathSegment::LineTo(to) => Segment::LineTo(point(to)),
+                PathSegment::QuadTo(control, to) => {
+                    let (control, to) = (point(control), point(to));
+                    let towards = |from: Point| {
+                        (
+                            from.0 + 2.0 / 3.0 * (control.0 - from.0),
+                            from.1 + 2.0 / 3.0 * (control.1 - from.1),
+                        )
+                    };
+                    Segment::CubicTo(towards(last), towards(to), to)
+                }
+                PathSegment::CubicTo(first, second, to) => {
+                    Segment::CubicTo(point(first), point(second), point(to))
+                }
+                PathSegment::Close => Segment::Close,
+            };
+
+            last = match segment {
+                Segment::MoveTo(to) | Segment::LineTo(to) | Segment::CubicTo(_, _, to) => to,
+                Segment::Close => last,
+            };
+            segments.push(segment);
+        }
+
+        // Strokes grow with the transform, taken to scale both axes alike.
+        let stretch = (transform.sx * transform.sy - transform.kx * transform.ky)
+            .abs()
+            .sqrt();
+
+        layers.push(Layer {
+            shape: Shape::Path(segments),
+            fill: path.fill.as_ref().and_then(|fill| rgb(&fill.paint)),
+            stroke: path.stroke.as_ref().and_then(|stroke| {
+                let color = rgb(&stroke.paint)?;
+                Some((color, stroke.width.get() * stretch * scale))
+            }),
+        });
+    }
+
+    Some(layers)
+}
+
+// Reads the path and circle elements of an SVG document, skipping anything malformed.
+fn layers(svg: &str) -> Vec<Layer> {
+    svg.split('<')
+        .filter_map(|element| {
+            let shape = match element.split_whitespace().next()? {
+                "path" => Shape::Path(segments(attribute(element, "d")?)?),
+                "circle" => Shape::Circle(
+                    (number(element, "cx")?, number(element, "cy")?),
+                    number(element, "r")?,
+                ),
+                _ => return None,
+            };
+
+            let stroke = attribute(element, "stroke").and_then(rgb);
+            let width = number(element, "stroke-width").unwrap_or(1.0);
+
+            Some(Layer {
+                shape,
+                fill: attribute(element, "fill").and_then(rgb),
+                stroke: stroke.map(|stroke| (stroke, width)),
+            })
+        })
+        .collect()
+}
+
+fn attribute<'a>(element: &'a str, name: &str) -> Option<&'a str> {
+    let start = element.find(&format!(" {}=\"", name))? + name.len() + 3;
+    let length = element[start..].find('"')?;
+    Some(&element[start..start + length])
+}
+
+fn number(element: &str, name: &str) -> Option<f32> {
+    attribute(element, name)?.parse().ok()
+}
+
+// Colors of the form #rrggbb, None standing for no paint at all.
+fn rgb(color: &str) -> Option<Rgb> {
+    let hex = color.strip_prefix('#').filter(|hex| hex.len() == 6)?;
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    Some((channel(0)?, channel(2)?, channel(4)?))
+}
+
+// Reads path data made of absolute M, L, C and Z commands, with commas or spaces between numbers.
+fn segments(data: &str) -> Option<Vec<Segment>> {
+    let tokens: Vec<&str> = data
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|token| !token.is_empty())
+        .collect();
+
+    let mut segments = Vec::new();
+    let mut i = 0;
+    let point = |i: &mut usize| -> Option<Point> {
+        let x = tokens.get(*i)?.parse().ok()?;
+        let y = tokens.get(*i + 1)?.parse().ok()?;
+        *i += 2;
+        Some((x, y))
+    };
+
+    let mut command = "";
+    while i < tokens.len() {
+        // A command letter may be left out when it repeats.
+        if tokens[i].starts_with(|c: char| c.is_ascii_alphabetic()) {
+            command = tokens[i];
+            i += 1;
+        }
+
+        // Closing takes no numbers, so a number right after it is malformed.
+        if command == "Z" {
+            segments.push(Segment::Close);
+            command = "";
+            continue;
+        }
+
+        segments.push(match command {
+            "M" => Segment::MoveTo(point(&mut i)?),
+            "L" => Segment::LineTo(point(&mut i)?),
+            "C" => Segment::CubicTo(point(&mut i)?, point(&mut i)?, point(&mut i)?),
+            _ => return None,
+        });
+    }
+
+    Some(segments)
+}
@@ -0,0 +1,98 @@
+use crate::piece::Piece;
+use crate::position::Position;
+
+#[cfg(feature = "serde")]
+use std::collections::HashMap;
+
+// Every square of the board, by index from a1 through h1 up to h8.
+const POSITIONS: [Position; 64] = {
+    let mut positions = [Position { file: 0, rank: 0 }; 64];
+    let mut i = 0;
+    while i < 64 {
+        positions[i] = Position {
+            file: i % 8,
+            rank: i / 8,
+        };
+        i += 1;
+    }
+    positions
+};
+
+// Pieces of a position by square. Lookups index an array rather than hash, and iteration runs
+// through the squares in the same order every time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(from = "HashMap<Position, Piece>", into = "HashMap<Position, Piece>")
+)]
+pub struct Squares([Option<Piece>; 64]);
+
+impl Squares {
+    pub fn new() -> Self {
+        Self(std::array::from_fn(|_| None))
+    }
+
+    pub fn get(&self, pos: &Position) -> Option<&Piece> {
+        self.0[Self::index(pos)].as_ref()
+    }
+
+    pub fn contains_key(&self, pos: &Position) -> bool {
+        self.get(pos).is_some()
+    }
+
+    pub fn insert(&mut self, pos: Position, piece: Piece) -> Option<Piece> {
+        self.0[Self::index(&pos)].replace(piece)
+    }
+
+    pub fn remove(&mut self, pos: &Position) -> Option<Piece> {
+        self.0[Self::index(pos)].take()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Position, &Piece)> {
+        POSITIONS
+            .iter()
+            .zip(self.0.iter())
+            .filter_map(|(pos, piece)| Some((pos, piece.as_ref()?)))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Position> {
+        self.iter().map(|(pos, _)| pos)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Piece> {
+        self.0.iter().flatten()
+    }
+
+    fn index(pos: &Position) -> usize {
+        pos.rank * 8 + pos.file
+    }
+}
+
+impl Default for Squares {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<HashMap<Position, Piece>> for Squares {
+    fn from(pieces: HashMap<Position, Piece>) -> Self {
+        pieces
+            .into_iter()
+            .fold(Squares::new(), |mut squares, (pos, piece)| {
+                squares.insert(pos, piece);
+                squares
+            })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<Squares> for HashMap<Position, Piece> {
+    fn from(squares: Squares) -> Self {
+        squares
+            .iter()
+            .map(|(pos, piece)| (*pos, piece.clone()))
+            .collect()
+    }
+}
@@ -0,0 +1,74 @@
+use crate::board::Board;
+use crate::piece::Color;
+use crate::position::Position;
+
+use std::fs;
+use std::io;
+
+const SQUARE: usize = 60;
+const LIGHT_SQUARE: &str = "#f0d9b5";
+const DARK_SQUARE: &str = "#b58863";
+const LAST_MOVE: &str = "#cdd26a";
+
+// Renders the board as an SVG diagram, optionally highlighting the squares of the last move.
+pub fn render(board: &Board, highlight: bool) -> String {
+    let size = SQUARE * 8;
+    let last_move = board
+        .last_move()
+        .filter(|_| highlight)
+        .map(|mv| [mv.from, mv.to]);
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{0}\" viewBox=\"0 0 {0} {0}\">\n",
+        size
+    );
+
+    for rank in 0..8 {
+        for file in 0..8 {
+            let pos = Position::new(file, rank);
+            let (x, y) = (file * SQUARE, (7 - rank) * SQUARE);
+
+            let (fill, label) = match Color::from(pos) {
+                Color::White => (LIGHT_SQUARE, DARK_SQUARE),
+                Color::Black => (DARK_SQUARE, LIGHT_SQUARE),
+            };
+            let fill = match last_move {
+                Some(squares) if squares.contains(&pos) => LAST_MOVE,
+                _ => fill,
+            };
+
+            svg.push_str(&format!(
+                "<rect x=\"{}\" y=\"{}\" width=\"{2}\" height=\"{2}\" fill=\"{3}\"/>\n",
+                x, y, SQUARE, fill
+            ));
+
+            // Coordinates run along the left and bottom edges.
+            if file == 0 {
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" font-size=\"12\" font-family=\"sans-serif\" fill=\"{}\">{}</text>\n",
+                    x + 3, y + 14, label, rank + 1
+                ));
+            }
+            if rank == 0 {
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" font-size=\"12\" font-family=\"sans-serif\" fill=\"{}\">{}</text>\n",
+                    x + SQUARE - 10, y + SQUARE - 4, label, (b'a' + file as u8) as char
+                ));
+            }
+
+            if let Ok(piece) = board.at(&pos) {
+                svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" font-size=\"48\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>\n",
+                    x + SQUARE / 2, y + SQUARE / 2, piece.glyph()
+                ));
+            }
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+pub fn save(board: &Board, highlight: bool, path: &str) -> io::Result<()> {
+    fs::write(path, render(board, highlight))
+}
@@ -0,0 +1,92 @@
+use crate::error::CatchAllError;
+use crate::game::{Game, Ply};
+use crate::lan::Lan;
+use crate::mate;
+use crate::puzzle::Puzzle;
+use crate::search::{Limits, Search, TABLE_MEGABYTES};
+
+// Longest forced mate looked for, in plies.
+const MATE_PLIES: usize = 3;
+// Depth each candidate move is searched to when looking for a winning tactic.
+const TACTIC_DEPTH: usize = 3;
+// A tactic has to win this much, more than any other move and more than the position offered
+// before the opponent's move.
+const TACTIC_SCORE: i32 = 300;
+const TACTIC_MARGIN: i32 = 200;
+
+// Finds the positions of a game where the side to move had a forced mate or a single move
+// winning material, each set up by the opponent's move before it.
+pub fn find_puzzles(game: &Game) -> Result<Vec<Puzzle>, CatchAllError> {
+    let plies = game.plies()?;
+    let mut search = Search::new(TABLE_MEGABYTES);
+    let mut puzzles = Vec::new();
+
+    // How each position stands for the side to move, one ply deeper than the moves are searched.
+    let limits = Limits::depth(TACTIC_DEPTH + 1);
+    let scores = plies
+        .iter()
+        .map(|ply| {
+            let mut board = ply.board.clone();
+            Ok(search.search(&mut board, &ply.color, &limits)?.score)
+        })
+        .collect::<Result<Vec<_>, CatchAllError>>()?;
+
+    // Positions inside the solution of the last puzzle found add nothing new.
+    let mut skip_until = 0;
+
+    for (i, pair) in plies.windows(2).enumerate() {
+        let (setup, ply) = (&pair[0], &pair[1]);
+        if i + 1 < skip_until {
+            continue;
+        }
+
+        let (solution, themes) = match mate::solve_mate(&ply.board, &ply.color, MATE_PLIES)? {
+            Some(line) => {
+                let moves = line.len().div_ceil(2);
+                (line, vec!["mate".to_string(), format!("mateIn{}", moves)])
+            }
+            // Only a position much better than the one before can hold a winning tactic.
+            None if scores[i + 1] < TACTIC_SCORE || scores[i + 1] + scores[i] < TACTIC_MARGIN => {
+                continue
+            }
+            None => match winning_move(&mut search, ply)? {
+                Some(lan) => (vec![lan], vec!["advantage".to_string()]),
+                None => continue,
+            },
+        };
+
+        skip_until = i + 1 + solution.len();
+        puzzles.push(Puzzle {
+            id: format!("{:016x}", ply.board.hash(&ply.color)),
+            fen: setup.fen.clone(),
+            setup: setup.lan,
+            solution,
+            rating: None,
+            themes,
+        });
+    }
+
+    Ok(puzzles)
+}
+
+// The move winning clearly more than any other.
+#[rustfmt::skip]
+fn winning_move(search: &mut Search, ply: &Ply) -> Result<Option<Lan>, CatchAllError> {
+    let limits = Limits::depth(TACTIC_DEPTH);
+
+    let mut scores = Vec::new();
+    let mut board = ply.board.clone();
+    for (from, to, kind) in board.legal_moves(&ply.color) {
+        board.make(&from, &to, kind.promotion())?;
+        let score = -search.search(&mut board, &ply.color.opposite(), &limits)?.score;
+        board.revert()?;
+
+        scores.push((score, Lan::new(from, to, kind.promotion())));
+    }
+    scores.sort_by_key(|(score, _)| -score);
+
+    Ok(match scores.as_slice() {
+        [(best, lan), (second, _), ..] if *best >= TACTIC_SCORE && *best - *second >= TACTIC_MARGIN => Some(*lan),
+        _ => None,
+    })
+}
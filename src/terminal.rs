@@ -0,0 +1,38 @@
+use crate::board::Board;
+use crate::piece::Color;
+use crate::position::Position;
+
+const LIGHT_SQUARE: &str = "\x1b[48;2;240;217;181m\x1b[38;2;0;0;0m";
+const DARK_SQUARE: &str = "\x1b[48;2;181;136;99m\x1b[38;2;0;0;0m";
+const RESET: &str = "\x1b[0m";
+
+// Renders the board with Unicode glyphs, coloring the squares with ANSI escape codes if requested.
+pub fn render(board: &Board, ansi: bool) -> String {
+    let mut out = String::new();
+
+    for rank in (0..8).rev() {
+        out.push_str(&format!("{} ", rank + 1));
+
+        for file in 0..8 {
+            let pos = Position::new(file, rank);
+            let glyph = board.at(&pos).map_or(' ', |piece| piece.glyph());
+
+            match (ansi, Color::from(pos)) {
+                (true, Color::White) => {
+                    out.push_str(&format!("{} {} {}", LIGHT_SQUARE, glyph, RESET))
+                }
+                (true, Color::Black) => {
+                    out.push_str(&format!("{} {} {}", DARK_SQUARE, glyph, RESET))
+                }
+                (false, _) => {
+                    out.push_str(&format!(" {} ", if glyph == ' ' { '·' } else { glyph }))
+                }
+            }
+        }
+
+        out.push('\n');
+    }
+
+    out.push_str("   a  b  c  d  e  f  g  h");
+    out
+}
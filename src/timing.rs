@@ -0,0 +1,5 @@
+// Readings of the system clock, which the standard library cannot take in the browser.
+#[cfg(not(all(target_arch = "wasm32", feature = "gui")))]
+pub use std::time::{Instant, SystemTime, UNIX_EPOCH};
+#[cfg(all(target_arch = "wasm32", feature = "gui"))]
+pub use web_time::{Instant, SystemTime, UNIX_EPOCH};
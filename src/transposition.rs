@@ -0,0 +1,104 @@
+use crate::lan::Lan;
+
+use std::mem;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    // The score failed high and is at least the stored value.
+    Lower,
+    // The score failed low and is at most the stored value.
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Entry {
+    pub hash: u64,
+    pub depth: u8,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Lan>,
+    generation: u8,
+}
+
+impl Entry {
+    pub fn new(hash: u64, depth: u8, score: i32, bound: Bound, best_move: Option<Lan>) -> Self {
+        Self {
+            hash,
+            depth,
+            score,
+            bound,
+            best_move,
+            generation: 0,
+        }
+    }
+}
+
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    generation: u8,
+}
+
+impl TranspositionTable {
+    // Allocates the largest power of two number of entries fitting into the given size.
+    pub fn new(megabytes: usize) -> Self {
+        let count = (megabytes * 1024 * 1024 / mem::size_of::<Option<Entry>>()).max(1);
+        let count = 1 << (usize::BITS - 1 - count.leading_zeros());
+
+        Self {
+            entries: vec![None; count],
+            generation: 0,
+        }
+    }
+
+    pub fn probe(&self, hash: u64) -> Option<&Entry> {
+        self.entries[self.index(hash)]
+            .as_ref()
+            .filter(|entry| entry.hash == hash)
+    }
+
+    // Entries from earlier searches are always replaced, current ones only by deeper or equal results.
+    pub fn store(&mut self, entry: Entry) {
+        let index = self.index(entry.hash);
+        let generation = self.generation;
+
+        let replace = match &self.entries[index] {
+            None => true,
+            Some(old) => {
+                old.hash == entry.hash || old.generation != generation || entry.depth >= old.depth
+            }
+        };
+
+        if replace {
+            self.entries[index] = Some(Entry {
+                generation,
+                ..entry
+            });
+        }
+    }
+
+    // Ages all entries, called once before each search.
+    pub fn new_search(&mut self) {
+        self.generation = self.generation.wrapping_add(1);
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.iter_mut().for_each(|entry| *entry = None);
+        self.generation = 0;
+    }
+
+    // Permille of sampled entries written by the current search, as reported by UCI.
+    pub fn hashfull(&self) -> usize {
+        self.entries
+            .iter()
+            .take(1000)
+            .filter(|entry| entry.is_some_and(|entry| entry.generation == self.generation))
+            .count()
+            * 1000
+            / self.entries.len().min(1000)
+    }
+
+    fn index(&self, hash: u64) -> usize {
+        (hash as usize) & (self.entries.len() - 1)
+    }
+}
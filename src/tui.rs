@@ -0,0 +1,297 @@
+use crate::ai::{self, Difficulty};
+use crate::clock::{self, Bonus, TimeControl};
+use crate::game::{Game, Turn};
+use crate::lan::Lan;
+use crate::piece::Color;
+use crate::position::Position;
+
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::execute;
+use crossterm::terminal::{self, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout, Rect};
+use ratatui::style::{self, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Paragraph, Wrap};
+use ratatui::{Frame, Terminal};
+
+use std::io;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+const LIGHT_SQUARE: style::Color = style::Color::Rgb(240, 217, 181);
+const DARK_SQUARE: style::Color = style::Color::Rgb(181, 136, 99);
+const SELECTED_SQUARE: style::Color = style::Color::Rgb(230, 90, 70);
+const DESTINATION_SQUARE: style::Color = style::Color::Rgb(130, 151, 105);
+const CURSOR_SQUARE: style::Color = style::Color::Rgb(86, 160, 233);
+// Width of the board panel: three columns a square, the rank labels and the border.
+const BOARD_WIDTH: u16 = 8 * 3 + 4;
+// How long to wait for a key before redrawing the clocks.
+const TICK: Duration = Duration::from_millis(100);
+
+struct Tui {
+    game: Game,
+    // Side the built-in engine plays and how strongly, when not both sides are human.
+    engine: Option<(Color, Difficulty)>,
+    thinking: Option<Receiver<Result<Option<Lan>, String>>>,
+    cursor: Position,
+    // Move being typed in SAN, and what went wrong with the last one.
+    entry: String,
+    message: Option<String>,
+}
+
+impl Tui {
+    fn new(engine: Option<(Color, Difficulty)>, control: TimeControl, bonus: Bonus) -> Self {
+        let mut game = Game::new();
+        game.set_clock(control, bonus);
+
+        Self {
+            game,
+            engine,
+            thinking: None,
+            cursor: Position::new(4, 1),
+            entry: String::new(),
+            message: None,
+        }
+    }
+
+    fn engine_to_move(&self) -> bool {
+        self.engine
+            .is_some_and(|(color, _)| color == self.game.color() && self.game.outcome().is_none())
+    }
+
+    // Starts the engine on its move, or plays the move once it has been found.
+    fn update(&mut self) {
+        self.game.adjudicate_flag();
+
+        if let Some(reply) = self
+            .thinking
+            .as_ref()
+            .and_then(|thinking| thinking.try_recv().ok())
+        {
+            self.thinking = None;
+            let played = match reply {
+                Ok(Some(lan)) => self
+                    .game
+                    .play_move(&lan.from, &lan.to, lan.promotion)
+                    .map(|_| ())
+                    .map_err(|e| e.to_string()),
+                Ok(None) => Ok(()),
+                Err(e) => Err(e),
+            };
+            if let Err(e) = played {
+                self.message = Some(e);
+            }
+        }
+
+        let (color, difficulty) = match self.engine {
+            Some(engine) if self.thinking.is_none() && self.engine_to_move() => engine,
+            _ => return,
+        };
+        let board = self.game.board().clone();
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let reply = ai::think(board, color, difficulty, None).map_err(|e| e.to_string());
+            let _ = sender.send(reply);
+        });
+        self.thinking = Some(receiver);
+    }
+
+    // Handles a key, telling whether the user asked to quit.
+    fn key(&mut self, code: KeyCode, modifiers: KeyModifiers) -> bool {
+        if modifiers.contains(KeyModifiers::CONTROL) {
+            return matches!(code, KeyCode::Char('c') | KeyCode::Char('q'));
+        }
+
+        match code {
+            KeyCode::Left => self.step(-1, 0),
+            KeyCode::Right => self.step(1, 0),
+            KeyCode::Up => self.step(0, 1),
+            KeyCode::Down => self.step(0, -1),
+            KeyCode::Char(c) if !c.is_whitespace() => self.entry.push(c),
+            KeyCode::Backspace => {
+                self.entry.pop();
+            }
+            KeyCode::Esc => {
+                self.entry.clear();
+                self.game.reset_turn();
+            }
+            KeyCode::Enter if !self.entry.is_empty() => self.submit(),
+            KeyCode::Enter | KeyCode::Char(' ') => self.select(),
+            _ => (),
+        }
+
+        false
+    }
+
+    fn step(&mut self, files: isize, ranks: isize) {
+        self.cursor = self.cursor.offset(files, ranks).unwrap_or(self.cursor);
+    }
+
+    // Picks up the piece under the cursor, or puts the one picked up down there.
+    fn select(&mut self) {
+        if self.engine_to_move() {
+            return;
+        }
+
+        self.message = match self.game.advance(&self.cursor, None) {
+            Ok(_) => None,
+            Err(e) => {
+                self.game.reset_turn();
+                Some(e.to_string())
+            }
+        };
+    }
+
+    fn submit(&mut self) {
+        if self.engine_to_move() {
+            return;
+        }
+
+        let san = std::mem::take(&mut self.entry);
+        self.message = self.game.advance_san(&san).err().map(|e| e.to_string());
+    }
+
+    fn draw(&self, frame: &mut Frame) {
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(BOARD_WIDTH), Constraint::Min(20)])
+            .split(frame.size());
+        let side = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(4),
+                Constraint::Min(3),
+                Constraint::Length(4),
+            ])
+            .split(columns[1]);
+
+        frame.render_widget(self.board(), columns[0]);
+        frame.render_widget(self.clocks(), side[0]);
+        frame.render_widget(self.moves(), side[1]);
+        self.prompt(frame, side[2]);
+    }
+
+    fn board(&self) -> Paragraph<'static> {
+        let board = self.game.board();
+        let (selected, destinations) = match self.game.turn() {
+            Turn::Select(_, from) => (Some(from), board.clone().legal_destinations(&from)),
+            _ => (None, Vec::new()),
+        };
+
+        let mut lines = Vec::new();
+        for rank in (0..8).rev() {
+            let mut spans = vec![Span::raw(format!("{} ", rank + 1))];
+            for file in 0..8 {
+                let pos = Position::new(file, rank);
+                let background = match Color::from(pos) {
+                    _ if pos == self.cursor => CURSOR_SQUARE,
+                    _ if Some(pos) == selected => SELECTED_SQUARE,
+                    _ if destinations.contains(&pos) => DESTINATION_SQUARE,
+                    Color::White => LIGHT_SQUARE,
+                    Color::Black => DARK_SQUARE,
+                };
+                let glyph = board.at(&pos).map_or(' ', |piece| piece.glyph());
+                spans.push(Span::styled(
+                    format!(" {} ", glyph),
+                    Style::default().bg(background).fg(style::Color::Black),
+                ));
+            }
+            lines.push(Line::from(spans));
+        }
+        lines.push(Line::from("   a  b  c  d  e  f  g  h"));
+
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL))
+    }
+
+    fn clocks(&self) -> Paragraph<'static> {
+        let lines: Vec<Line> = [Color::White, Color::Black]
+            .iter()
+            .map(|color| {
+                let time = self.game.clock().map_or("-".to_string(), |clock| {
+                    clock::format_time(clock.remaining(color))
+                });
+                let running = self
+                    .game
+                    .clock()
+                    .and_then(|clock| clock.running())
+                    .is_some_and(|running| running == *color);
+                let style = match running {
+                    true => Style::default().fg(style::Color::Yellow),
+                    false => Style::default(),
+                };
+                Line::from(Span::styled(format!("{:?} {}", color, time), style))
+            })
+            .collect();
+
+        Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("Clocks"))
+    }
+
+    fn moves(&self) -> Paragraph<'static> {
+        let text = self
+            .game
+            .moves()
+            .chunks(2)
+            .enumerate()
+            .map(|(i, pair)| format!("{}. {}", i + 1, pair.join(" ")))
+            .collect::<Vec<_>>()
+            .join("  ");
+
+        Paragraph::new(text)
+            .wrap(Wrap { trim: true })
+            .block(Block::default().borders(Borders::ALL).title("Moves"))
+    }
+
+    fn prompt(&self, frame: &mut Frame, area: Rect) {
+        let state = match (&self.thinking, self.game.status()) {
+            (Some(_), _) => "Thinking...".to_string(),
+            (None, Ok(status)) => format!("{:?} to move: {}", self.game.color(), status),
+            (None, Err(e)) => e.to_string(),
+        };
+        let lines = vec![
+            Line::from(format!("> {}", self.entry)),
+            Line::from(self.message.clone().unwrap_or(state)),
+        ];
+
+        frame.render_widget(
+            Paragraph::new(lines).block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Arrows and Enter, or type a move - Ctrl+C quits"),
+            ),
+            area,
+        );
+    }
+}
+
+// Plays a game in the terminal until the user quits, restoring the terminal either way.
+#[rustfmt::skip]
+pub fn run(engine: Option<(Color, Difficulty)>, control: TimeControl, bonus: Bonus) -> io::Result<()> {
+    terminal::enable_raw_mode()?;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    let result = play(&mut terminal, Tui::new(engine, control, bonus));
+
+    terminal::disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen)?;
+
+    result
+}
+
+fn play(terminal: &mut Terminal<CrosstermBackend<io::Stdout>>, mut tui: Tui) -> io::Result<()> {
+    loop {
+        tui.update();
+        terminal.draw(|frame| tui.draw(frame))?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press && tui.key(key.code, key.modifiers) {
+                return Ok(());
+            }
+        }
+    }
+}
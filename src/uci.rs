@@ -0,0 +1,230 @@
+use crate::game::Game;
+use crate::piece::Color;
+use crate::search::{Limits, Search, SearchResult, MATE};
+
+use std::io::{self, BufRead};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+const NAME: &str = "chess";
+const AUTHOR: &str = "fwdmyr";
+const DEFAULT_HASH: usize = 16;
+const MAX_HASH: usize = 1024;
+const MAX_DEPTH: usize = 64;
+// Moves assumed to remain when the time control doesn't say.
+const MOVES_TO_GO: u64 = 30;
+// Kept in reserve for the latency between deciding on a move and the GUI receiving it.
+const MOVE_OVERHEAD: u64 = 50;
+
+struct Uci {
+    game: Game,
+    // Engine state, lent to the worker thread while a search runs.
+    search: Option<Search>,
+    worker: Option<JoinHandle<Search>>,
+    stop: Arc<AtomicBool>,
+    hash: usize,
+}
+
+// Speaks the UCI protocol on stdin and stdout until told to quit.
+pub fn run() -> io::Result<()> {
+    let search = Search::new(DEFAULT_HASH);
+
+    let mut uci = Uci {
+        game: Game::new(),
+        stop: search.stop_signal(),
+        search: Some(search),
+        worker: None,
+        hash: DEFAULT_HASH,
+    };
+
+    for line in io::stdin().lock().lines() {
+        let line = line?;
+        let mut tokens = line.split_whitespace();
+
+        match tokens.next() {
+            Some("uci") => {
+                println!("id name {}", NAME);
+                println!("id author {}", AUTHOR);
+                println!(
+                    "option name Hash type spin default {} min 1 max {}",
+                    DEFAULT_HASH, MAX_HASH
+                );
+                println!("uciok");
+            }
+            Some("isready") => println!("readyok"),
+            Some("setoption") => uci.option(tokens.collect()),
+            Some("ucinewgame") => {
+                uci.search().clear();
+                uci.game = Game::new();
+            }
+            Some("position") => uci.position(tokens.collect()),
+            Some("go") => uci.go(tokens.collect()),
+            Some("stop") => uci.finish(),
+            Some("quit") => break,
+            _ => (),
+        }
+    }
+
+    uci.finish();
+
+    Ok(())
+}
+
+impl Uci {
+    // Waits for a running search and takes the engine back from it.
+    fn search(&mut self) -> &mut Search {
+        if let Some(worker) = self.worker.take() {
+            self.search = worker.join().ok();
+        }
+
+        // A worker that panicked took the engine with it.
+        if self.search.is_none() {
+            self.reset(self.hash);
+        }
+
+        self.search.get_or_insert_with(|| Search::new(DEFAULT_HASH))
+    }
+
+    fn reset(&mut self, megabytes: usize) {
+        let search = Search::new(megabytes);
+        self.stop = search.stop_signal();
+        self.search = Some(search);
+        self.hash = megabytes;
+    }
+
+    fn finish(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.search();
+    }
+
+    // Only the size of the transposition table is configurable.
+    fn option(&mut self, tokens: Vec<&str>) {
+        let value = match tokens.as_slice() {
+            ["name", "Hash", "value", value] => value.parse::<usize>().ok(),
+            _ => None,
+        };
+
+        if let Some(megabytes) = value {
+            self.finish();
+            self.reset(megabytes.clamp(1, MAX_HASH));
+        }
+    }
+
+    // position [startpos | fen <fen>] [moves <move>...]
+    fn position(&mut self, tokens: Vec<&str>) {
+        let moves = tokens.iter().position(|token| *token == "moves");
+        let (setup, moves) = match moves {
+            Some(i) => (&tokens[..i], &tokens[i + 1..]),
+            None => (&tokens[..], &[][..]),
+        };
+
+        let game = match setup {
+            ["startpos"] => Ok(Game::new()),
+            ["fen", fen @ ..] => Game::from_fen(&fen.join(" ")).map_err(|e| e.to_string()),
+            _ => Err(format!("Unknown position: {}", setup.join(" "))),
+        };
+
+        let game = game.and_then(|mut game| {
+            moves
+                .iter()
+                .try_for_each(|lan| game.advance_lan(lan).map(|_| ()))
+                .map(|_| game)
+                .map_err(|e| e.to_string())
+        });
+
+        match game {
+            Ok(game) => self.game = game,
+            Err(e) => println!("info string {}", e),
+        }
+    }
+
+    // go [depth <plies>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>] [infinite]
+    fn go(&mut self, tokens: Vec<&str>) {
+        let value = |name: &str| {
+            tokens
+                .iter()
+                .position(|token| *token == name)
+                .and_then(|i| tokens.get(i + 1))
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+
+        let color = self.game.color();
+        let (time, increment) = match color {
+            Color::White => (value("wtime"), value("winc")),
+            Color::Black => (value("btime"), value("binc")),
+        };
+
+        let budget = match (value("movetime"), time) {
+            _ if tokens.contains(&"infinite") => None,
+            (Some(movetime), _) => Some(movetime.saturating_sub(MOVE_OVERHEAD)),
+            (None, Some(time)) => {
+                let moves = value("movestogo").unwrap_or(MOVES_TO_GO).max(1);
+                let budget = time / moves + increment.unwrap_or(0) / 2;
+                Some(budget.min(time / 2).saturating_sub(MOVE_OVERHEAD))
+            }
+            (None, None) => None,
+        };
+
+        let limits = Limits {
+            depth: value("depth").map_or(MAX_DEPTH, |depth| depth as usize),
+            time: budget.map(|millis| Duration::from_millis(millis.max(1))),
+            noise: 0,
+        };
+
+        self.search();
+        let mut search = match self.search.take() {
+            Some(search) => search,
+            None => return,
+        };
+        self.stop.store(false, Ordering::Relaxed);
+
+        let mut board = self.game.board().clone();
+
+        self.worker = Some(thread::spawn(move || {
+            let start = Instant::now();
+
+            let result = search.search_with(&mut board, &color, &limits, |result| {
+                println!("{}", info(result, start.elapsed()));
+            });
+
+            match result.ok().and_then(|result| result.best_move) {
+                Some(best_move) => println!("bestmove {}", best_move),
+                None => println!("bestmove 0000"),
+            }
+
+            search
+        }));
+    }
+}
+
+fn info(result: &SearchResult, elapsed: Duration) -> String {
+    let millis = elapsed.as_millis().max(1) as u64;
+
+    // Mate scores count moves rather than plies, negative when getting mated.
+    let score = match result.score {
+        score if score.abs() >= MATE - MAX_DEPTH as i32 => {
+            let plies = MATE - score.abs();
+            format!("mate {}", score.signum() * (plies + 1) / 2)
+        }
+        score => format!("cp {}", score),
+    };
+
+    let pv = result
+        .pv
+        .iter()
+        .map(|lan| lan.to_string())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    format!(
+        "info depth {} score {} nodes {} nps {} time {} pv {}",
+        result.depth,
+        score,
+        result.nodes,
+        result.nodes * 1000 / millis,
+        millis,
+        pv
+    )
+}
@@ -0,0 +1,76 @@
+use crate::castling::CastlingRights;
+use crate::piece::{Color, Piece};
+use crate::position::Position;
+
+// Key layout: 12 pieces x 64 squares, side to move, 4 castling rights, 8 en passant files.
+const SIDE: usize = 768;
+const CASTLING: usize = 769;
+const ENPASSANT: usize = 773;
+const KEY_COUNT: usize = 781;
+
+static KEYS: [u64; KEY_COUNT] = keys();
+
+// Fixed seed splitmix64 sequence, so hashes are stable across runs.
+const fn keys() -> [u64; KEY_COUNT] {
+    let mut keys = [0; KEY_COUNT];
+    let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut i = 0;
+    while i < KEY_COUNT {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+fn piece_index(piece: &Piece) -> usize {
+    let kind = match piece {
+        Piece::Pawn(_, _) => 0,
+        Piece::Knight(_) => 1,
+        Piece::Bishop(_) => 2,
+        Piece::Rook(_) => 3,
+        Piece::Queen(_) => 4,
+        Piece::King(_) => 5,
+    };
+
+    match piece.color() {
+        Color::White => kind,
+        Color::Black => kind + 6,
+    }
+}
+
+pub fn piece(piece: &Piece, pos: &Position) -> u64 {
+    KEYS[piece_index(piece) * 64 + pos.rank * 8 + pos.file]
+}
+
+pub fn side(color: &Color) -> u64 {
+    match color {
+        Color::White => 0,
+        Color::Black => KEYS[SIDE],
+    }
+}
+
+// Castling rights are indexed as white kingside, white queenside, black kingside, black queenside.
+pub fn castling(index: usize) -> u64 {
+    KEYS[CASTLING + index]
+}
+
+pub fn enpassant(file: usize) -> u64 {
+    KEYS[ENPASSANT + file]
+}
+
+pub fn castling_rights(rights: &CastlingRights) -> u64 {
+    rights
+        .rights()
+        .iter()
+        .enumerate()
+        .filter(|(_, right)| **right)
+        .fold(0, |hash, (index, _)| hash ^ castling(index))
+}
+
+pub fn enpassant_target(target: &Option<Position>) -> u64 {
+    target.map_or(0, |pos| enpassant(pos.file))
+}